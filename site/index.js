@@ -3,6 +3,9 @@ import React from "react";
 import init, * as Dominion from "./node_modules/dominion/dominion.js";
 import SuperTreeview from "react-super-treeview";
 
+// The wasm API this site is written against (see `dominion::wasm`)
+const API_VERSION = 2;
+
 function SetupGenerator({
   makeUnselectedExpansionCards,
   projectCounts,
@@ -23,6 +26,9 @@ function SetupGenerator({
   );
   const [projectCount, setProjectCount] = React.useState(null);
   const [baneCount, setBaneCount] = React.useState(null);
+  const [requireCombo, setRequireCombo] = React.useState(false);
+  const [avoidSwingy, setAvoidSwingy] = React.useState(false);
+  const [separateTopCards, setSeparateTopCards] = React.useState(false);
   const [setup, setSetup] = React.useState(null);
   const [error, setError] = React.useState(null);
 
@@ -38,6 +44,27 @@ function SetupGenerator({
   const includedCards = checkedChildren(includes);
   const bannedCards = checkedChildren(bans);
 
+  const config = () => ({
+    project_count: projectCount,
+    bane_count: baneCount,
+    include_expansions: nullIfEmpty(
+      expansions.filter((v) => v.isChecked).map((e) => e.name)
+    ),
+    include_cards: includedCards,
+    ban_cards: bannedCards,
+    require_combo: requireCombo,
+    avoid_swingy: avoidSwingy,
+    separate_top_cards: separateTopCards,
+  });
+
+  const reroll = (slot) => {
+    try {
+      setSetup(Dominion.reroll_js(setup, slot, config()));
+    } catch (e) {
+      setError(e);
+    }
+  };
+
   return (
     <div>
       <h1>
@@ -105,21 +132,34 @@ function SetupGenerator({
         </>
       ))}
 
+      <h1>Synergies</h1>
+      <input
+        type="checkbox"
+        id="require-combo"
+        onChange={(e) => setRequireCombo(e.target.checked)}
+        checked={requireCombo}
+      />
+      <label htmlFor="require-combo">Require a known combo</label>
+      <input
+        type="checkbox"
+        id="avoid-swingy"
+        onChange={(e) => setAvoidSwingy(e.target.checked)}
+        checked={avoidSwingy}
+      />
+      <label htmlFor="avoid-swingy">Avoid swingy pairs</label>
+      <input
+        type="checkbox"
+        id="separate-top-cards"
+        onChange={(e) => setSeparateTopCards(e.target.checked)}
+        checked={separateTopCards}
+      />
+      <label htmlFor="separate-top-cards">Keep the strongest cards apart</label>
+
       <br />
       <button
         onClick={() => {
           try {
-            setSetup(
-              generate({
-                project_count: projectCount,
-                bane_count: baneCount,
-                include_expansions: nullIfEmpty(
-                  expansions.filter((v) => v.isChecked).map((e) => e.name)
-                ),
-                include_cards: includedCards,
-                ban_cards: bannedCards,
-              })
-            );
+            setSetup(generate(config()));
           } catch (e) {
             setError(e);
           }
@@ -127,14 +167,100 @@ function SetupGenerator({
       >
         Generate!
       </button>
+      <Draft
+        config={config}
+        onFinish={(setup) => {
+          setError(null);
+          setSetup(setup);
+        }}
+      />
       <br />
-      {error && Dominion.gen_error_js(error)}
-      {setup && <Setup setup={setup} cardExpansions={cardExpansions} />}
+      {error && error.message}
+      {setup && (
+        <Setup
+          setup={setup}
+          cardExpansions={cardExpansions}
+          reroll={reroll}
+        />
+      )}
     </div>
   );
 }
 
-function Setup({ setup, cardExpansions }) {
+function Draft({ config, onFinish }) {
+  const [players, setPlayers] = React.useState(2);
+  const [format, setFormat] = React.useState("Snake");
+  const [draft, setDraft] = React.useState(null);
+  const [error, setError] = React.useState(null);
+
+  const attempt = (f) => {
+    try {
+      setError(null);
+      f();
+    } catch (e) {
+      setError(e);
+    }
+  };
+
+  const act = (player, action) =>
+    attempt(() => {
+      const next = Dominion.draft_act_js(draft, player, action);
+      if (Dominion.draft_to_act_js(next) === undefined) {
+        setDraft(null);
+        onFinish(Dominion.draft_finish_js(next));
+      } else {
+        setDraft(next);
+      }
+    });
+
+  const player = draft && Dominion.draft_to_act_js(draft);
+  const verb = format === "VetoOne" ? "Veto" : "Pick";
+
+  return (
+    <>
+      <h1>Draft</h1>
+      <label htmlFor="draft-players">Players</label>
+      <input
+        type="number"
+        id="draft-players"
+        min="1"
+        max="6"
+        value={players}
+        onChange={(e) => setPlayers(parseInt(e.target.value, 10))}
+      />
+      <select value={format} onChange={(e) => setFormat(e.target.value)}>
+        <option value="Snake">Snake</option>
+        <option value="VetoOne">Veto one</option>
+        <option value="PickTwoOfFive">Pick two of five</option>
+      </select>
+      <button
+        onClick={() =>
+          attempt(() =>
+            setDraft(Dominion.draft_new_js(config(), players, format))
+          )
+        }
+      >
+        Start draft
+      </button>
+      {draft && (
+        <div id="draft-hand">
+          <h2>Player {player + 1}</h2>
+          {Dominion.draft_hand_js(draft, player).map((card) => (
+            <button
+              className="draft-card"
+              onClick={() => act(player, { [verb]: card })}
+            >
+              {verb} {card}
+            </button>
+          ))}
+        </div>
+      )}
+      {error && error.message}
+    </>
+  );
+}
+
+function Setup({ setup, cardExpansions, reroll }) {
   const cardsByExpansion = {};
 
   const usedExpansions = new Set();
@@ -157,6 +283,15 @@ function Setup({ setup, cardExpansions }) {
     return spaces(card);
   };
 
+  const slotOf = (card) =>
+    card === setup.bane_card ? "Bane" : { KingdomCard: card };
+
+  const RerollButton = ({ slot }) => (
+    <button className="reroll" title="Reroll" onClick={() => reroll(slot)}>
+      ↻
+    </button>
+  );
+
   Dominion.setup_kingdom_cards_js(setup).forEach((kc) => {
     const expansions = cardExpansions[kc].sort().join("/");
     usedExpansions.add(expansions);
@@ -167,6 +302,16 @@ function Setup({ setup, cardExpansions }) {
 
   const usedExpansionsSorted = Array.from(usedExpansions).sort();
 
+  const downloadSheet = (paper) => {
+    const svg = Dominion.svg_js(setup, "Kingdom", paper);
+    const url = URL.createObjectURL(new Blob([svg], { type: "image/svg+xml" }));
+    const link = document.createElement("a");
+    link.href = url;
+    link.download = `kingdom-${paper.toLowerCase()}.svg`;
+    link.click();
+    URL.revokeObjectURL(url);
+  };
+
   return (
     <>
       <h1>Kingdom</h1>
@@ -178,7 +323,13 @@ function Setup({ setup, cardExpansions }) {
           <>
             <div className="expansion">{expansion}</div>
             <div className="expansion-cards">
-              {cardsByExpansion[expansion].map(formatCard).join(", ")}
+              {cardsByExpansion[expansion].map((card, i) => (
+                <>
+                  {i > 0 && ", "}
+                  {formatCard(card)}
+                  <RerollButton slot={slotOf(card)} />
+                </>
+              ))}
             </div>
           </>
         ))}
@@ -188,18 +339,209 @@ function Setup({ setup, cardExpansions }) {
           <h1>Projects</h1>
           <ul>
             {setup.project_cards.map((project) => (
-              <li className="project-card">{project}</li>
+              <li className="project-card">
+                {project}
+                <RerollButton slot={{ Project: project }} />
+              </li>
             ))}
           </ul>
         </>
       )}
       <h2>Hists</h2>
       <pre>{Dominion.hists_js(setup)}</pre>
+      <Analysis analysis={Dominion.analysis_js(setup)} />
+      <ul id="synergies">
+        {Dominion.synergies_js(setup).map((synergy) => (
+          <li>
+            {synergy.kind}: {synergy.card} +{" "}
+            {synergy.partner.Card || synergy.partner.Project} ({synergy.why})
+          </li>
+        ))}
+      </ul>
+      <button id="download-a4" onClick={() => downloadSheet("A4")}>
+        Download sheet (A4)
+      </button>
+      <button id="download-letter" onClick={() => downloadSheet("Letter")}>
+        Download sheet (US Letter)
+      </button>
+      <Play setup={setup} />
+    </>
+  );
+}
+
+// Play a kingdom in the browser, hot-seat or against bots. Only Base2
+// kingdoms without projects or banes can be played.
+function Play({ setup }) {
+  const [seats, setSeats] = React.useState(["Human", { Bot: "BigMoney" }]);
+  const [session, setSession] = React.useState(null);
+  // Bumped after every choice, as the session changes in place
+  const [, setMoves] = React.useState(0);
+  const [chosen, setChosen] = React.useState([]);
+  const [error, setError] = React.useState(null);
+
+  const attempt = (f) => {
+    try {
+      setError(null);
+      f();
+    } catch (e) {
+      setError(e);
+    }
+  };
+
+  const seatOptions = [
+    ["Human", "Person"],
+    [{ Bot: "BigMoney" }, "Big Money"],
+    [{ Bot: "BigMoneySmithy" }, "Big Money Smithy"],
+  ];
+  const setSeat = (i, seat) =>
+    setSeats(seats.map((s, j) => (i === j ? seat : s)));
+
+  const start = () =>
+    attempt(() => {
+      if (session) {
+        session.free();
+      }
+      setChosen([]);
+      setSession(
+        new Dominion.GameSession(
+          setup,
+          seats,
+          Math.floor(Math.random() * 2 ** 32)
+        )
+      );
+    });
+
+  // Hot-seat: show whoever has to decide, or the first seat when it's over
+  const waitingOn = session && session.waiting_on();
+  const seat = waitingOn === undefined || waitingOn === null ? 0 : waitingOn;
+  const decision = session && session.decision(seat);
+  const view = session && session.view(seat);
+
+  const choose = (choice) =>
+    attempt(() => {
+      session.choose(seat, choice);
+      setChosen([]);
+      setMoves((moves) => moves + 1);
+    });
+
+  const toggle = (i) =>
+    setChosen(
+      chosen.includes(i) ? chosen.filter((j) => j !== i) : chosen.concat([i])
+    );
+
+  return (
+    <div id="play">
+      <h1>Play</h1>
+      {seats.map((seat, i) => (
+        <select
+          className="play-seat"
+          value={JSON.stringify(seat)}
+          onChange={(e) => setSeat(i, JSON.parse(e.target.value))}
+        >
+          {seatOptions.map(([option, label]) => (
+            <option value={JSON.stringify(option)}>{label}</option>
+          ))}
+        </select>
+      ))}
+      <button
+        disabled={seats.length >= 4}
+        onClick={() => setSeats(seats.concat(["Human"]))}
+      >
+        Add seat
+      </button>
+      <button
+        disabled={seats.length <= 2}
+        onClick={() => setSeats(seats.slice(0, -1))}
+      >
+        Remove seat
+      </button>
+      <button id="play-start" onClick={start}>
+        Start game
+      </button>
+      {view && (
+        <>
+          <ul id="play-players">
+            {view.players.map((player, i) => (
+              <li>
+                {player.name}
+                {i === view.current && " (to play)"}: {player.score} VP,{" "}
+                {player.deck} in deck, {player.hand} in hand
+                {player.in_play.length > 0 &&
+                  `, playing ${player.in_play.join(", ")}`}
+              </li>
+            ))}
+          </ul>
+          <div id="play-supply">
+            {view.supply.map((pile) => `${pile.card} ×${pile.count}`).join(", ")}
+          </div>
+          {view.over ? (
+            <h2 id="play-over">Game over: {session.scores().join(" – ")}</h2>
+          ) : (
+            <>
+              <h2>
+                {view.players[seat].name}, turn {view.turn + 1}:{" "}
+                {decision.kind}
+                {decision.source && ` (${decision.source})`}
+              </h2>
+              <p>
+                {view.actions} actions, {view.buys} buys, {view.coins} coins.
+                Choose {decision.min === decision.max
+                  ? decision.min
+                  : `${decision.min} to ${decision.max}`}
+                .
+              </p>
+              <div id="play-options">
+                {decision.options.map((card, i) => (
+                  <button
+                    className={chosen.includes(i) ? "chosen" : ""}
+                    onClick={() => toggle(i)}
+                  >
+                    {card}
+                  </button>
+                ))}
+              </div>
+              <button
+                id="play-choose"
+                onClick={() => choose(chosen.map((i) => decision.options[i]))}
+              >
+                Done
+              </button>
+              <p>Hand: {view.hand.join(", ")}</p>
+            </>
+          )}
+        </>
+      )}
+      {error && error.message}
+    </div>
+  );
+}
+
+function Analysis({ analysis }) {
+  return (
+    <>
+      <h2>Analysis: {analysis.label}</h2>
+      <ul id="analysis-scores">
+        <li>Engine potential: {analysis.engine_potential}/10</li>
+        <li>Attack intensity: {analysis.attack_intensity}/10</li>
+        <li>Counterplay: {analysis.counterplay}/10</li>
+        <li>Big Money viability: {analysis.big_money_viability}/10</li>
+      </ul>
+      <ul id="analysis-reasons">
+        {analysis.reasons.map((reason) => (
+          <li>{reason}</li>
+        ))}
+      </ul>
     </>
   );
 }
 
 init().then(() => {
+  if (Dominion.api_version() !== API_VERSION) {
+    throw new Error(
+      `Expected wasm API version ${API_VERSION}, got ${Dominion.api_version()}`
+    );
+  }
+
   const expansionCards = Dominion.expansion_cards_js();
   const cardExpansions = Object.entries(expansionCards)
     .flatMap(([expansion, cards]) => cards.map((card) => [card, expansion]))
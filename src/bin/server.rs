@@ -0,0 +1,193 @@
+//! Hosts one game over WebSockets for players on the LAN. Every connection
+//! gets its own thread; the room behind a mutex does the rest.
+
+use clap::{Arg, Command};
+use dominion::server::{ClientMessage, Outbox, Room, ServerMessage};
+use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// The room and a way to reach every open connection
+struct Shared {
+    room: Room,
+    connections: HashMap<usize, Sender<ServerMessage>>,
+}
+
+impl Shared {
+    fn send(&self, outbox: Outbox) {
+        for (connection, message) in outbox {
+            if let Some(sender) = self.connections.get(&connection) {
+                // A closed connection is cleaned up by its own thread
+                let _ = sender.send(message);
+            }
+        }
+    }
+}
+
+fn main() {
+    let matches = Command::new("Dominion server")
+        .version("0.1.0")
+        .about("Host a game of a kingdom for browsers on the LAN, over WebSockets")
+        .arg(
+            Arg::new("setup")
+                .long("setup")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("The kingdom to play (JSON), a Base2 kingdom is generated otherwise"),
+        )
+        .arg(
+            Arg::new("players")
+                .long("players")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .default_value("4")
+                .help("How many seats the table has"),
+        )
+        .arg(
+            Arg::new("bind")
+                .long("bind")
+                .takes_value(true)
+                .value_name("ADDRESS")
+                .default_value("0.0.0.0:9000")
+                .help("Where to listen"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help("Seed the game's shuffles"),
+        )
+        .get_matches();
+
+    let setup = match matches.value_of("setup") {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()))
+            .and_then(|setup: dominion::Setup| {
+                let problems: Vec<String> = setup
+                    .validate()
+                    .into_iter()
+                    .map(dominion::pretty::setup_error)
+                    .collect();
+                if problems.is_empty() {
+                    Ok(setup)
+                } else {
+                    Err(format!(
+                        "it breaks the rules of setting up!\n\n{}",
+                        problems.join("\n")
+                    ))
+                }
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Could not read setup {}: {}", path, err);
+                process::exit(1);
+            }),
+        None => dominion::gen_setup(dominion::SetupConfig {
+            include_expansions: Some([dominion::Expansion::Base2].into_iter().collect()),
+            project_count: Some(dominion::ProjectCount::NoProjects),
+            ..dominion::SetupConfig::none()
+        })
+        .expect("Base2 alone makes a kingdom"),
+    };
+    let seed = matches
+        .value_of("seed")
+        .map(|_| matches.value_of_t_or_exit("seed"))
+        .unwrap_or_else(rand::random);
+
+    let bind = matches.value_of("bind").unwrap();
+    let listener = TcpListener::bind(bind).unwrap_or_else(|err| {
+        eprintln!("Could not listen on {}: {}", bind, err);
+        process::exit(1);
+    });
+    println!("{}", dominion::pretty::format_setup(&setup));
+    println!("Listening on ws://{}", bind);
+
+    let shared = Arc::new(Mutex::new(Shared {
+        room: Room::new(setup, matches.value_of_t_or_exit("players"), seed),
+        connections: HashMap::new(),
+    }));
+
+    for (connection, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || serve(connection, stream, shared));
+    }
+}
+
+fn serve(connection: usize, stream: TcpStream, shared: Arc<Mutex<Shared>>) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    // Wake up now and then to pass on messages for this connection
+    let _ = socket
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(50)));
+
+    let (sender, receiver) = channel();
+    shared
+        .lock()
+        .unwrap()
+        .connections
+        .insert(connection, sender);
+
+    talk(connection, &mut socket, &receiver, &shared);
+
+    let mut shared = shared.lock().unwrap();
+    shared.connections.remove(&connection);
+    let outbox = shared.room.disconnect(connection);
+    shared.send(outbox);
+}
+
+/// Pass messages both ways until the connection closes
+fn talk(
+    connection: usize,
+    socket: &mut WebSocket<TcpStream>,
+    receiver: &Receiver<ServerMessage>,
+    shared: &Mutex<Shared>,
+) {
+    loop {
+        for message in receiver.try_iter() {
+            let json = serde_json::to_string(&message).unwrap();
+            if socket.send(Message::text(json)).is_err() {
+                return;
+            }
+        }
+
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(_) => return,
+        };
+
+        let mut shared = shared.lock().unwrap();
+        let outbox = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => shared.room.handle(connection, message),
+            Err(err) => vec![(
+                connection,
+                ServerMessage::Error {
+                    message: format!("Could not read that message: {}", err),
+                },
+            )],
+        };
+        shared.send(outbox);
+    }
+}
@@ -5,9 +5,9 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::str::FromStr;
 use strum::IntoEnumIterator;
 use strum_macros::{EnumCount as EnumCountMacro, EnumIter, EnumString};
-use wasm_bindgen::prelude::*;
 
 #[cfg(test)]
 mod tests {
@@ -49,6 +49,16 @@ mod tests {
             include_cards: None,
             ban_cards: None,
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap_err();
 
@@ -70,6 +80,16 @@ mod tests {
             include_cards: None,
             ban_cards: Some(HashSet::from([KC::Witch, KC::Militia])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -130,6 +150,16 @@ mod tests {
             include_cards: None,
             ban_cards: Some(HashSet::from([KC::YoungWitch])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -146,6 +176,16 @@ mod tests {
             include_cards: None,
             ban_cards: Some(HashSet::from([banned_card.clone()])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         });
         let setup = setup.unwrap();
 
@@ -177,6 +217,16 @@ mod tests {
             include_cards: Some(HashSet::from([included_card.clone()])),
             ban_cards: Some(HashSet::from([KC::YoungWitch])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -193,6 +243,16 @@ mod tests {
             include_cards: Some(HashSet::from([included_card.clone()])),
             ban_cards: None,
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -216,6 +276,16 @@ mod tests {
             include_cards: Some(HashSet::from([card.clone()])),
             ban_cards: Some(HashSet::from([card.clone()])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap_err();
 
@@ -233,6 +303,16 @@ mod tests {
             include_cards: None,
             ban_cards: Some(KC::iter().collect()),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap_err();
 
@@ -252,6 +332,16 @@ mod tests {
                 KC::Menagerie,
             ])),
             bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap_err();
 
@@ -267,6 +357,16 @@ mod tests {
                 include_cards: None,
                 ban_cards: None,
                 bane_count: None,
+                require_combo: false,
+                avoid_swingy: false,
+                max_similarity: None,
+                max_power: None,
+                separate_top_cards: false,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
             })
             .unwrap();
 
@@ -283,6 +383,16 @@ mod tests {
                 include_cards: None,
                 ban_cards: None,
                 bane_count: Some(bane_count),
+                require_combo: false,
+                avoid_swingy: false,
+                max_similarity: None,
+                max_power: None,
+                separate_top_cards: false,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
             })
             .unwrap();
 
@@ -298,6 +408,16 @@ mod tests {
             include_cards: Some(HashSet::from([KC::YoungWitch])),
             ban_cards: None,
             bane_count: Some(BaneCount::ThreeBanes),
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -315,6 +435,16 @@ mod tests {
             include_cards: None,
             ban_cards: None,
             bane_count: Some(BaneCount::ThreeBanes),
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -332,6 +462,16 @@ mod tests {
             include_cards: None,
             ban_cards: None,
             bane_count: Some(BaneCount::ThreeBanes),
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -348,6 +488,16 @@ mod tests {
             include_cards: None,
             ban_cards: None,
             bane_count: Some(BaneCount::ThreeBanes),
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
         })
         .unwrap();
 
@@ -369,730 +519,3415 @@ mod tests {
         assert_eq!(setup.bane_cards.len(), 0);
     }
 
-    fn gen_expansion() -> Expansion {
-        let mut rng = rand::thread_rng();
+    #[test]
+    fn rerolling_a_kingdom_card_replaces_only_that_card() {
+        let setup = gen_setup(SetupConfig {
+            include_expansions: None,
+            project_count: None,
+            include_cards: None,
+            ban_cards: Some(HashSet::from([KC::YoungWitch])),
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        })
+        .unwrap();
+        let old = setup.kingdom_cards[3].clone();
 
-        Expansion::iter()
-            .collect::<Vec<Expansion>>()
-            .choose(&mut rng)
-            .unwrap()
-            .clone()
+        let rerolled = reroll(&setup, Slot::KingdomCard(old.clone()), SetupConfig::none()).unwrap();
+
+        assert!(!rerolled.cards().contains(&old));
+        assert_eq!(rerolled.kingdom_cards.len(), 10);
+        for card in setup.kingdom_cards.iter().filter(|c| *c != &old) {
+            assert!(rerolled.kingdom_cards.contains(card));
+        }
+        assert_eq!(rerolled.project_cards, setup.project_cards);
     }
 
-    fn gen_kingdom_card() -> KC {
-        let mut rng = rand::thread_rng();
+    #[test]
+    fn rerolling_respects_bans_and_expansions() {
+        let setup = Setup::new(
+            vec![KC::Cellar, KC::Chapel, KC::Moat],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let mut config = SetupConfig::including_expansions(HashSet::from([Expansion::Base1]));
+        config.ban_cards = Some(HashSet::from([KC::Village, KC::Smithy]));
 
-        KC::iter()
-            .collect::<Vec<KC>>()
-            .choose(&mut rng)
+        for _ in 0..20 {
+            let rerolled = reroll(&setup, Slot::KingdomCard(KC::Moat), config.clone()).unwrap();
+            let new = rerolled.kingdom_cards[2].clone();
+
+            assert!(expansion_set(&new).contains(&Expansion::Base1));
+            assert!(new != KC::Village && new != KC::Smithy && new != KC::Moat);
+        }
+    }
+
+    #[test]
+    fn rerolling_keeps_to_expansion_counts() {
+        let seaside = |setup: &Setup| {
+            setup
+                .kingdom_cards
+                .iter()
+                .filter(|card| card.expansions().contains(&Expansion::Seaside))
+                .count()
+        };
+        let config = SetupConfig {
+            expansion_counts: Some(HashMap::from([(Expansion::Seaside, "3".parse().unwrap())])),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+                Expansion::Seaside,
+            ]))
+        };
+        let mut setup = gen_setup(config.clone()).unwrap();
+
+        for i in 0..20 {
+            let card = setup.kingdom_cards[i % 10].clone();
+            setup = reroll(&setup, Slot::KingdomCard(card), config.clone()).unwrap();
+            assert_eq!(seaside(&setup), 3);
+        }
+
+        // With every other Seaside card banned, a Seaside card has nothing
+        // to go to that keeps the count
+        let card = setup
+            .kingdom_cards
+            .iter()
+            .find(|card| card.expansions().contains(&Expansion::Seaside))
             .unwrap()
-            .clone()
+            .clone();
+        let no_more_seaside = SetupConfig {
+            ban_cards: Some(
+                KC::iter()
+                    .filter(|kc| kc.expansions().contains(&Expansion::Seaside))
+                    .filter(|kc| !setup.kingdom_cards.contains(kc))
+                    .collect(),
+            ),
+            ..config
+        };
+        assert_eq!(
+            reroll(&setup, Slot::KingdomCard(card), no_more_seaside).unwrap_err(),
+            GenSetupError::CouldNotSatisfyExpansionCounts
+        );
     }
-}
 
-/// A kingdom card
-#[derive(
-    EnumIter,
-    Debug,
-    PartialEq,
-    EnumCountMacro,
-    Clone,
-    Eq,
-    Hash,
-    EnumString,
-    PartialOrd,
-    Ord,
-    Serialize,
-    Deserialize,
-)]
-pub enum KC {
-    ActingTroupe,
-    Adventurer,
-    Advisor,
-    Ambassador,
-    Artisan,
-    Baker,
-    Bandit,
-    Bank,
-    Baron,
-    Bazaar,
-    Bishop,
-    BorderGuard,
-    BorderVillage,
-    Bridge,
-    Bureaucrat,
-    Butcher,
-    Cache,
-    CandlestickMaker,
-    Caravan,
-    CargoShip,
-    Cartographer,
-    Cellar,
-    Chancellor,
-    Chapel,
-    City,
-    Conspirator,
-    Contraband,
-    CouncilRoom,
-    CountingHouse,
-    Courtier,
-    Courtyard,
-    Crossroads,
-    Cutpurse,
-    Develop,
-    Diplomat,
-    Doctor,
-    Ducat,
-    Duchess,
-    Duke,
-    Embargo,
-    Embassy,
-    Expand,
-    Experiment,
-    Explorer,
-    Fairgrounds,
-    FarmingVillage,
-    Farmland,
-    Feast,
-    Festival,
-    FishingVillage,
-    FlagBearer,
-    FoolsGold,
-    Forge,
-    FortuneTeller,
-    Gardens,
-    GhostShip,
-    Goons,
-    GrandMarket,
-    Haggler,
-    Hamlet,
-    Harbinger,
-    Harem,
-    Harvest,
-    Haven,
-    Herald,
-    Hideout,
-    Highway,
-    Hoard,
-    HornOfPlenty,
-    HorseTraders,
-    HuntingParty,
-    IllGottenGains,
-    Improve,
-    Inn,
-    Inventor,
-    Ironworks,
-    Island,
-    JackOfAllTrades,
-    Jester,
-    Journeyman,
-    KingsCourt,
-    Laboratory,
-    Lackeys,
-    Library,
-    Lighthouse,
-    Loan,
-    Lookout,
-    Lurker,
-    Mandarin,
-    Margrave,
-    Market,
-    Masquerade,
-    Masterpiece,
-    Menagerie,
-    Merchant,
-    MerchantGuild,
-    MerchantShip,
-    Militia,
-    Mill,
-    Mine,
-    MiningVillage,
-    Minion,
-    Mint,
-    Moat,
-    Moneylender,
-    Monument,
-    MountainVillage,
-    Mountebank,
-    NativeVillage,
-    Navigator,
-    NobleBrigand,
-    Nobles,
-    NomadCamp,
-    Oasis,
-    OldWitch,
-    Oracle,
-    Outpost,
-    Patrol,
-    Patron,
-    Pawn,
-    PearlDiver,
-    Peddler,
-    PirateShip,
-    Plaza,
-    Poacher,
-    Priest,
-    Quarry,
-    Rabble,
-    Recruiter,
-    Remake,
-    Remodel,
-    Replace,
-    Research,
-    RoyalSeal,
-    Salvager,
-    Scepter,
-    Scheme,
-    Scholar,
-    Sculptor,
-    SeaHag,
-    SecretPassage,
-    Seer,
-    Sentry,
-    ShantyTown,
-    SilkMerchant,
-    SilkRoad,
-    Smithy,
-    Smugglers,
-    Soothsayer,
-    SpiceMerchant,
-    Spices,
-    Spy,
-    Stables,
-    Steward,
-    Stonemason,
-    Swashbuckler,
-    Swindler,
-    Tactician,
-    Talisman,
-    Taxman,
-    Thief,
-    ThroneRoom,
-    Torturer,
-    Tournament,
-    TradeRoute,
-    Trader,
-    TradingPost,
-    TreasureMap,
-    Treasurer,
-    Treasury,
-    Tunnel,
-    Upgrade,
-    Vassal,
-    Vault,
-    Venture,
-    Village,
-    Villain,
-    Warehouse,
-    Watchtower,
-    Wharf,
-    WishingWell,
-    Witch,
-    Woodcutter,
-    WorkersVillage,
-    Workshop,
-    YoungWitch,
-}
+    #[test]
+    fn zero_weights_that_leave_no_bane_give_up() {
+        // Village takes the only 2-3 cost spot, and every other one is
+        // weighted out, so Young Witch never gets a bane
+        let config = SetupConfig {
+            include_cards: Some(HashSet::from([KC::YoungWitch, KC::Village])),
+            card_weights: Some(
+                KC::iter()
+                    .filter(|kc| kc.base_cost() == 2 || kc.base_cost() == 3)
+                    .filter(|kc| kc != &KC::Village)
+                    .map(|kc| (kc, 0.0))
+                    .collect(),
+            ),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+                Expansion::Cornucopia,
+            ]))
+        };
 
-/// What is a card's base cost?
-pub trait BaseCost {
-    /// What is a card's base cost?
-    fn base_cost(&self) -> u8;
-}
+        assert_eq!(
+            gen_setup(config).unwrap_err(),
+            GenSetupError::CouldNotSatisfyBaneCard
+        );
+    }
 
-impl BaseCost for KC {
-    fn base_cost(&self) -> u8 {
-        match self {
-            KC::Embargo => 2,
-            KC::Haven => 2,
-            KC::Lighthouse => 2,
-            KC::NativeVillage => 2,
-            KC::PearlDiver => 2,
-            KC::Ambassador => 3,
-            KC::FishingVillage => 3,
-            KC::Lookout => 3,
-            KC::Smugglers => 3,
-            KC::Warehouse => 3,
-            KC::Caravan => 4,
-            KC::Cutpurse => 4,
-            KC::Island => 4,
-            KC::Navigator => 4,
-            KC::PirateShip => 4,
-            KC::Salvager => 4,
-            KC::SeaHag => 4,
-            KC::TreasureMap => 4,
-            KC::Bazaar => 5,
-            KC::Explorer => 5,
-            KC::GhostShip => 5,
-            KC::MerchantShip => 5,
-            KC::Outpost => 5,
-            KC::Tactician => 5,
-            KC::Treasury => 5,
-            KC::Wharf => 5,
-            KC::Courtyard => 2,
-            KC::Lurker => 2,
-            KC::Pawn => 2,
-            KC::Masquerade => 3,
-            KC::ShantyTown => 3,
-            KC::Steward => 3,
-            KC::Swindler => 3,
-            KC::WishingWell => 3,
-            KC::Baron => 4,
-            KC::Bridge => 4,
-            KC::Conspirator => 4,
-            KC::Diplomat => 4,
-            KC::Ironworks => 4,
-            KC::Mill => 4,
-            KC::MiningVillage => 4,
-            KC::SecretPassage => 4,
-            KC::Courtier => 5,
-            KC::Duke => 5,
-            KC::Minion => 5,
-            KC::Patrol => 5,
-            KC::Replace => 5,
-            KC::Torturer => 5,
-            KC::TradingPost => 5,
-            KC::Upgrade => 5,
-            KC::Harem => 6,
-            KC::Nobles => 6,
-            KC::Harbinger => 3,
-            KC::Merchant => 3,
-            KC::Vassal => 3,
-            KC::Poacher => 4,
-            KC::Sentry => 5,
-            KC::Artisan => 6,
-            KC::ActingTroupe => 3,
-            KC::Adventurer => 6,
-            KC::Advisor => 4,
-            KC::Baker => 5,
-            KC::Bandit => 5,
-            KC::BorderGuard => 2,
-            KC::Bureaucrat => 4,
-            KC::Butcher => 5,
-            KC::CandlestickMaker => 2,
-            KC::CargoShip => 3,
-            KC::Cellar => 2,
-            KC::Chancellor => 3,
-            KC::Chapel => 2,
-            KC::CouncilRoom => 5,
-            KC::Doctor => 3,
-            KC::Ducat => 2,
-            KC::Experiment => 3,
-            KC::Fairgrounds => 6,
-            KC::FarmingVillage => 4,
-            KC::Feast => 4,
-            KC::Festival => 5,
-            KC::FlagBearer => 4,
-            KC::FortuneTeller => 3,
-            KC::Gardens => 4,
-            KC::Hamlet => 2,
-            KC::Harvest => 5,
-            KC::Herald => 4,
-            KC::Hideout => 4,
-            KC::HornOfPlenty => 5,
-            KC::HorseTraders => 4,
-            KC::HuntingParty => 5,
-            KC::Improve => 3,
-            KC::Inventor => 4,
-            KC::Jester => 5,
-            KC::Journeyman => 5,
-            KC::Laboratory => 5,
-            KC::Lackeys => 2,
-            KC::Library => 5,
-            KC::Market => 5,
-            KC::Masterpiece => 3,
-            KC::Menagerie => 3,
-            KC::MerchantGuild => 5,
-            KC::Militia => 4,
-            KC::Mine => 5,
-            KC::Moat => 2,
-            KC::Moneylender => 4,
-            KC::MountainVillage => 4,
-            KC::OldWitch => 5,
-            KC::Patron => 4,
-            KC::Plaza => 4,
-            KC::Priest => 4,
-            KC::Recruiter => 5,
-            KC::Remake => 4,
-            KC::Remodel => 4,
-            KC::Research => 4,
-            KC::Scepter => 5,
-            KC::Scholar => 5,
-            KC::Sculptor => 5,
-            KC::Seer => 5,
-            KC::SilkMerchant => 4,
-            KC::Smithy => 4,
-            KC::Soothsayer => 5,
-            KC::Spices => 5,
-            KC::Spy => 4,
-            KC::Stonemason => 2,
-            KC::Swashbuckler => 5,
-            KC::Taxman => 4,
-            KC::Thief => 4,
-            KC::ThroneRoom => 4,
-            KC::Tournament => 4,
-            KC::Treasurer => 5,
-            KC::Village => 3,
-            KC::Villain => 5,
-            KC::Witch => 5,
-            KC::Woodcutter => 3,
-            KC::Workshop => 3,
-            KC::YoungWitch => 4,
-            KC::Loan => 3,
-            KC::TradeRoute => 3,
-            KC::Watchtower => 3,
-            KC::Bishop => 4,
-            KC::Monument => 4,
-            KC::Quarry => 4,
-            KC::Talisman => 4,
-            KC::WorkersVillage => 4,
-            KC::City => 5,
-            KC::Contraband => 5,
-            KC::CountingHouse => 5,
-            KC::Mint => 5,
-            KC::Mountebank => 5,
-            KC::Rabble => 5,
-            KC::RoyalSeal => 5,
-            KC::Vault => 5,
-            KC::Venture => 5,
-            KC::Goons => 6,
-            KC::GrandMarket => 6,
-            KC::Hoard => 6,
-            KC::Bank => 7,
-            KC::Expand => 7,
-            KC::Forge => 7,
-            KC::KingsCourt => 7,
-            KC::Peddler => 8,
+    #[test]
+    fn rerolling_away_young_witch_drops_the_bane() {
+        let setup = Setup::bane(KC::Chapel, vec![KC::YoungWitch, KC::Smithy]);
 
-            KC::Crossroads => 2,
-            KC::Duchess => 2,
-            KC::FoolsGold => 2,
-            KC::Develop => 3,
-            KC::Oasis => 3,
-            KC::Oracle => 3,
-            KC::Scheme => 3,
-            KC::Tunnel => 3,
-            KC::JackOfAllTrades => 4,
-            KC::NobleBrigand => 4,
-            KC::NomadCamp => 4,
-            KC::SilkRoad => 4,
-            KC::SpiceMerchant => 4,
-            KC::Trader => 4,
-            KC::Cache => 5,
-            KC::Cartographer => 5,
-            KC::Embassy => 5,
-            KC::Haggler => 5,
-            KC::Highway => 5,
-            KC::IllGottenGains => 5,
-            KC::Inn => 5,
-            KC::Mandarin => 5,
-            KC::Margrave => 5,
-            KC::Stables => 5,
-            KC::BorderVillage => 6,
-            KC::Farmland => 6,
+        let rerolled = reroll(
+            &setup,
+            Slot::KingdomCard(KC::YoungWitch),
+            SetupConfig {
+                include_expansions: None,
+                project_count: None,
+                include_cards: None,
+                ban_cards: Some(HashSet::from([KC::YoungWitch])),
+                bane_count: None,
+                require_combo: false,
+                avoid_swingy: false,
+                max_similarity: None,
+                max_power: None,
+                separate_top_cards: false,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
+            },
+        )
+        .unwrap();
+
+        assert!(rerolled.bane_card.is_none());
+        assert_eq!(rerolled.cards().len(), 2);
+    }
+
+    #[test]
+    fn rerolling_into_young_witch_adds_a_bane() {
+        let setup = Setup::new(
+            vec![KC::Hamlet, KC::Smithy],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+
+        for _ in 0..50 {
+            let rerolled = reroll(
+                &setup,
+                Slot::KingdomCard(KC::Hamlet),
+                SetupConfig::including_expansions(HashSet::from([Expansion::Cornucopia])),
+            )
+            .unwrap();
+
+            let young_witch = rerolled.kingdom_cards.contains(&KC::YoungWitch);
+            assert_eq!(rerolled.bane_card.is_some(), young_witch);
+
+            if let Some(bane) = rerolled.bane_card {
+                assert!(bane.base_cost() == 2 || bane.base_cost() == 3);
+                assert!(!rerolled.kingdom_cards.contains(&bane));
+            }
         }
     }
-}
 
-/// Supported expansions
-#[derive(
-    EnumIter,
-    Debug,
-    PartialEq,
-    EnumCountMacro,
-    Eq,
-    Hash,
-    std::clone::Clone,
-    EnumString,
-    PartialOrd,
-    Ord,
-    Serialize,
-    Deserialize,
-)]
-pub enum Expansion {
-    Base1,
-    Base2,
-    Renaissance,
-    Guilds,
-    Cornucopia,
-    Intrigue2,
-    Seaside,
-    Prosperity,
-    Hinterlands,
-}
+    #[test]
+    fn rerolling_the_bane_keeps_the_kingdom() {
+        let setup = Setup::bane(KC::Chapel, vec![KC::YoungWitch, KC::Smithy]);
 
-/// To what expansions does a card belong?
-pub trait Expansions {
-    /// To what expansions does a card belong?
-    fn expansions(&self) -> Vec<Expansion>;
-}
+        let rerolled = reroll(&setup, Slot::Bane, SetupConfig::none()).unwrap();
+        let bane = rerolled.bane_card.unwrap();
 
-impl Expansions for KC {
-    fn expansions(&self) -> std::vec::Vec<Expansion> {
-        match self {
-            KC::Courtyard
-            | KC::Lurker
-            | KC::Pawn
-            | KC::Masquerade
-            | KC::ShantyTown
-            | KC::Steward
-            | KC::Swindler
-            | KC::WishingWell
-            | KC::Baron
-            | KC::Bridge
-            | KC::Conspirator
-            | KC::Diplomat
-            | KC::Ironworks
-            | KC::Mill
-            | KC::MiningVillage
-            | KC::SecretPassage
-            | KC::Courtier
-            | KC::Duke
-            | KC::Minion
-            | KC::Patrol
-            | KC::Replace
-            | KC::Torturer
-            | KC::TradingPost
-            | KC::Upgrade
-            | KC::Harem
-            | KC::Nobles => vec![Expansion::Intrigue2],
+        assert_eq!(rerolled.kingdom_cards, setup.kingdom_cards);
+        assert!(bane != KC::Chapel);
+        assert!(bane.base_cost() == 2 || bane.base_cost() == 3);
+    }
 
-            KC::Harbinger
-            | KC::Vassal
-            | KC::Sentry
-            | KC::Poacher
-            | KC::Merchant
-            | KC::Artisan
-            | KC::Bandit => {
-                vec![Expansion::Base2]
-            }
+    #[test]
+    fn rerolling_a_card_with_a_custom_bane_moves_the_bane() {
+        let setup = Setup::new(
+            vec![KC::Smithy, KC::Village],
+            None,
+            vec![],
+            HashMap::from([(KC::Smithy, BaneCard::Zebra)]),
+            Some(KC::Moat),
+        );
 
-            KC::Cellar
-            | KC::Chapel
-            | KC::Moat
-            | KC::Village
-            | KC::Workshop
-            | KC::Bureaucrat
-            | KC::Gardens
-            | KC::Militia
-            | KC::Moneylender
-            | KC::Remodel
-            | KC::Smithy
-            | KC::ThroneRoom
-            | KC::CouncilRoom
-            | KC::Festival
-            | KC::Laboratory
-            | KC::Library
-            | KC::Market
-            | KC::Mine
-            | KC::Witch => vec![Expansion::Base2, Expansion::Base1],
+        let rerolled = reroll(&setup, Slot::KingdomCard(KC::Smithy), SetupConfig::none()).unwrap();
+        let new = rerolled.kingdom_cards[0].clone();
 
-            KC::Chancellor | KC::Woodcutter | KC::Feast | KC::Spy | KC::Thief | KC::Adventurer => {
-                vec![Expansion::Base1]
-            }
+        assert_eq!(
+            rerolled.bane_cards,
+            HashMap::from([(new.clone(), BaneCard::Zebra)])
+        );
+        assert!(new != KC::Moat);
+        assert_eq!(rerolled.second_zebra, Some(KC::Moat));
+    }
 
-            KC::BorderGuard
-            | KC::Ducat
-            | KC::Lackeys
-            | KC::ActingTroupe
-            | KC::CargoShip
-            | KC::Experiment
-            | KC::Improve
-            | KC::FlagBearer
-            | KC::Hideout
-            | KC::Inventor
-            | KC::MountainVillage
-            | KC::Patron
-            | KC::Priest
-            | KC::Research
-            | KC::SilkMerchant
-            | KC::OldWitch
-            | KC::Recruiter
-            | KC::Scepter
-            | KC::Scholar
-            | KC::Sculptor
-            | KC::Seer
-            | KC::Spices
-            | KC::Swashbuckler
-            | KC::Treasurer
-            | KC::Villain => vec![Expansion::Renaissance],
+    #[test]
+    fn rerolling_a_project_replaces_it() {
+        let setup = Setup::new(
+            vec![KC::Smithy],
+            None,
+            vec![Project::Fleet, Project::Canal],
+            HashMap::new(),
+            None,
+        );
 
-            KC::CandlestickMaker
-            | KC::Stonemason
-            | KC::Doctor
-            | KC::Masterpiece
-            | KC::Advisor
-            | KC::Plaza
-            | KC::Taxman
-            | KC::Herald
-            | KC::Baker
-            | KC::Butcher
-            | KC::Journeyman
-            | KC::MerchantGuild
-            | KC::Soothsayer => vec![Expansion::Guilds],
+        let rerolled = reroll(&setup, Slot::Project(Project::Fleet), SetupConfig::none()).unwrap();
 
-            KC::Hamlet
-            | KC::FortuneTeller
-            | KC::Menagerie
-            | KC::FarmingVillage
-            | KC::HorseTraders
-            | KC::Remake
-            | KC::Tournament
-            | KC::YoungWitch
-            | KC::Harvest
-            | KC::HornOfPlenty
-            | KC::HuntingParty
-            | KC::Jester
-            | KC::Fairgrounds => vec![Expansion::Cornucopia],
+        assert_eq!(rerolled.project_cards.len(), 2);
+        assert!(!rerolled.project_cards.contains(&Project::Fleet));
+        assert_eq!(rerolled.project_cards[1], Project::Canal);
+    }
 
-            KC::Embargo
-            | KC::Haven
-            | KC::Lighthouse
-            | KC::NativeVillage
-            | KC::PearlDiver
-            | KC::Ambassador
-            | KC::FishingVillage
-            | KC::Lookout
-            | KC::Smugglers
-            | KC::Warehouse
-            | KC::Bazaar
-            | KC::Explorer
-            | KC::GhostShip
-            | KC::MerchantShip
-            | KC::Outpost
-            | KC::Tactician
-            | KC::Treasury
-            | KC::Wharf
-            | KC::Caravan
-            | KC::Cutpurse
-            | KC::Island
-            | KC::Navigator
-            | KC::PirateShip
-            | KC::Salvager
-            | KC::SeaHag
-            | KC::TreasureMap => vec![Expansion::Seaside],
+    #[test]
+    fn rerolling_something_not_in_the_setup_is_an_error() {
+        let setup = Setup::new(vec![KC::Smithy], None, vec![], HashMap::new(), None);
+
+        for slot in [
+            Slot::KingdomCard(KC::Village),
+            Slot::Project(Project::Fleet),
+            Slot::Bane,
+            Slot::SecondZebra,
+        ] {
+            let err = reroll(&setup, slot.clone(), SetupConfig::none()).unwrap_err();
+            assert_eq!(err, GenSetupError::SlotNotInSetup(slot));
+        }
+    }
 
-            KC::Loan
-            | KC::TradeRoute
-            | KC::Watchtower
-            | KC::Bishop
-            | KC::Monument
-            | KC::Quarry
-            | KC::Talisman
-            | KC::WorkersVillage
-            | KC::City
-            | KC::Contraband
-            | KC::CountingHouse
-            | KC::Mint
-            | KC::Mountebank
-            | KC::Rabble
-            | KC::RoyalSeal
-            | KC::Vault
-            | KC::Venture
-            | KC::Goons
-            | KC::GrandMarket
-            | KC::Hoard
-            | KC::Bank
-            | KC::Expand
-            | KC::Forge
-            | KC::KingsCourt
-            | KC::Peddler => vec![Expansion::Prosperity],
+    #[test]
+    fn locked_cards_survive_regenerating() {
+        let mut session = interactive::Session::new(SetupConfig::none()).unwrap();
+        session
+            .apply(interactive::Command::Lock(KC::Smithy))
+            .unwrap();
 
-            KC::Crossroads
-            | KC::Duchess
-            | KC::FoolsGold
-            | KC::Develop
-            | KC::Oasis
-            | KC::Oracle
-            | KC::Scheme
-            | KC::Tunnel
-            | KC::JackOfAllTrades
-            | KC::NobleBrigand
-            | KC::NomadCamp
-            | KC::SilkRoad
-            | KC::SpiceMerchant
-            | KC::Trader
-            | KC::Cache
-            | KC::Cartographer
-            | KC::Embassy
-            | KC::Haggler
-            | KC::Highway
-            | KC::IllGottenGains
-            | KC::Inn
-            | KC::Mandarin
-            | KC::Margrave
-            | KC::Stables
-            | KC::BorderVillage
-            | KC::Farmland => vec![Expansion::Hinterlands],
+        for _ in 0..10 {
+            session.apply(interactive::Command::Regenerate).unwrap();
+            assert!(session.setup.cards().contains(&KC::Smithy));
         }
+
+        session
+            .apply(interactive::Command::Unlock(KC::Smithy))
+            .unwrap();
+        assert!(session.locked().is_empty());
     }
-}
 
-/// My custom "Bane" cards
-#[derive(
-    EnumIter,
-    Debug,
-    PartialEq,
-    EnumCountMacro,
-    Eq,
-    Hash,
-    std::clone::Clone,
-    EnumString,
-    PartialOrd,
-    Ord,
-    Serialize,
-    Deserialize,
-)]
-pub enum BaneCard {
-    Bargain,
-    BuyAndBuy,
-    CoverOfDarkness,
-    CursedHeirloom,
-    Exchange,
-    Flank,
-    FoolsGold,
-    Fortification,
-    Frontier,
-    Gambler,
-    MagicShield,
-    Opportune,
-    PlagueCart,
-    Rebate,
-    Sacrifice,
-    SecretPlans,
-    SilverMine,
-    Throne,
-    TreasuryKey,
-    Tunnel,
-    Vault,
-    Zebra,
-}
+    #[test]
+    fn locking_a_card_swaps_it_in_for_one_unlocked_card() {
+        let mut session =
+            interactive::Session::new(SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+            ])))
+            .unwrap();
+        let before = session.setup.kingdom_cards.clone();
+        let outside: Vec<KC> = KC::iter()
+            .filter(|kc| kc.expansions().contains(&Expansion::Base2) && !before.contains(kc))
+            .collect();
 
-/// Card's type -- how it functions
-#[derive(EnumIter, Debug, PartialEq, EnumCountMacro, Eq, Hash, Clone, PartialOrd, Ord)]
-pub enum CardType {
-    Action,
-    Attack,
-    Reaction,
-    Victory,
-    Treasure,
-    Duration,
-}
+        session
+            .apply(interactive::Command::Lock(outside[0].clone()))
+            .unwrap();
+        let kept = before
+            .iter()
+            .filter(|kc| session.setup.kingdom_cards.contains(kc))
+            .count();
+        assert_eq!(kept, 9);
+        assert!(session.setup.kingdom_cards.contains(&outside[0]));
+
+        // The cards locked so far stay put, and with all ten locked there's
+        // nothing left to swap out
+        for card in session.setup.kingdom_cards.clone() {
+            session.apply(interactive::Command::Lock(card)).unwrap();
+        }
+        assert!(session.setup.kingdom_cards.contains(&outside[0]));
+        let locked = session.setup.cards();
+        assert_eq!(
+            session
+                .apply(interactive::Command::Lock(outside[1].clone()))
+                .unwrap_err(),
+            GenSetupError::TooManyCardsIncluded
+        );
+        assert_eq!(session.setup.cards(), locked);
+    }
 
-/// What types does a card have?
-pub trait CardTypes {
-    /// What types does a card have?
-    fn card_types(&self) -> Vec<CardType>;
-}
+    #[test]
+    fn banning_in_a_session_rerolls_the_card_away() {
+        let mut session = interactive::Session::new(SetupConfig::none()).unwrap();
+        let card = session.setup.kingdom_cards[0].clone();
 
-impl CardTypes for KC {
-    fn card_types(&self) -> std::vec::Vec<CardType> {
-        match self {
-            KC::Embargo => vec![CardType::Action],
-            KC::Haven => vec![CardType::Action, CardType::Duration],
-            KC::Lighthouse => vec![CardType::Action, CardType::Duration],
-            KC::NativeVillage => vec![CardType::Action],
-            KC::PearlDiver => vec![CardType::Action],
+        session
+            .apply(interactive::Command::Ban(card.clone()))
+            .unwrap();
+
+        assert!(!session.setup.cards().contains(&card));
+        assert!(session.config.ban_cards.unwrap().contains(&card));
+    }
+
+    #[test]
+    fn toggling_an_expansion_flips_it_in_the_pool() {
+        let mut session = interactive::Session::new(SetupConfig::none()).unwrap();
+
+        session
+            .apply(interactive::Command::ToggleExpansion(Expansion::Seaside))
+            .unwrap();
+        let expansions = session.config.include_expansions.clone().unwrap();
+        assert!(!expansions.contains(&Expansion::Seaside));
+        assert_eq!(expansions.len(), Expansion::iter().count() - 1);
+
+        session
+            .apply(interactive::Command::ToggleExpansion(Expansion::Seaside))
+            .unwrap();
+        assert!(session
+            .config
+            .include_expansions
+            .unwrap()
+            .contains(&Expansion::Seaside));
+    }
+
+    #[test]
+    fn removing_an_expansion_rerolls_its_unlocked_cards() {
+        let seaside_only = |kc: &KC| kc.expansions() == vec![Expansion::Seaside];
+        let mut session =
+            interactive::Session::new(SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+                Expansion::Seaside,
+            ])))
+            .unwrap();
+        session
+            .apply(interactive::Command::Lock(KC::Lookout))
+            .unwrap();
+        let staying: Vec<KC> = session
+            .setup
+            .kingdom_cards
+            .iter()
+            .filter(|kc| !seaside_only(kc))
+            .cloned()
+            .collect();
+
+        session
+            .apply(interactive::Command::ToggleExpansion(Expansion::Seaside))
+            .unwrap();
+        let cards = &session.setup.kingdom_cards;
+        assert_eq!(cards.len(), 10);
+        assert!(cards.contains(&KC::Lookout));
+        assert_eq!(cards.iter().filter(|kc| seaside_only(kc)).count(), 1);
+        assert!(staying.iter().all(|kc| cards.contains(kc)));
+    }
+
+    #[test]
+    fn failed_session_commands_leave_the_session_alone() {
+        let mut session =
+            interactive::Session::new(SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+            ])))
+            .unwrap();
+        let cards = session.setup.cards();
+
+        let err = session
+            .apply(interactive::Command::Projects(ProjectCount::TwoProjects))
+            .unwrap_err();
+
+        assert_eq!(err, GenSetupError::CouldNotSatisfyProjectsFromExpansions);
+        assert_eq!(session.setup.cards(), cards);
+        assert_eq!(session.config.project_count, None);
+    }
+
+    #[test]
+    fn history_lines_round_trip() {
+        let setup = gen_setup(SetupConfig::none()).unwrap();
+        let played = history::Played::now("Game".to_string(), setup.clone());
+        let log = history::line(&played).repeat(2);
+
+        let parsed = history::parse(&log).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].setup.cards(), setup.cards());
+    }
+
+    #[test]
+    fn snake_drafts_reverse_the_order_every_round() {
+        use draft::*;
+        let mut draft = Draft::new(SetupConfig::none(), 3, DraftFormat::Snake).unwrap();
+        let mut order = vec![];
+
+        while let Some(player) = draft.to_act() {
+            order.push(player);
+            let card = draft.hand(player)[0].clone();
+            draft.act(player, Action::Pick(card)).unwrap();
+        }
+
+        assert_eq!(order, vec![0, 1, 2, 2, 1, 0, 0, 1, 2, 2]);
+
+        let setup = draft.finish().unwrap();
+        for card in draft.picked {
+            assert!(setup.kingdom_cards.contains(&card));
+        }
+    }
+
+    #[test]
+    fn veto_drafts_ban_the_vetoed_and_keep_the_rest() {
+        use draft::*;
+        let mut draft = Draft::new(
+            SetupConfig {
+                include_expansions: None,
+                project_count: None,
+                include_cards: None,
+                ban_cards: Some(HashSet::from([KC::YoungWitch])),
+                bane_count: None,
+                require_combo: false,
+                avoid_swingy: false,
+                max_similarity: None,
+                max_power: None,
+                separate_top_cards: false,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
+            },
+            2,
+            DraftFormat::VetoOne,
+        )
+        .unwrap();
+
+        for player in 0..2 {
+            let card = draft.hand(player)[0].clone();
+            draft.act(player, Action::Veto(card)).unwrap();
+        }
+
+        let setup = draft.finish().unwrap();
+        for card in &draft.vetoed {
+            assert!(!setup.cards().contains(card));
+        }
+        for card in draft.hands.concat() {
+            assert!(setup.kingdom_cards.contains(&card));
+        }
+        assert_eq!(setup.kingdom_cards.len(), 10);
+    }
+
+    #[test]
+    fn pick_two_of_five_picks_two_per_player() {
+        use draft::*;
+        let mut draft = Draft::new(SetupConfig::none(), 2, DraftFormat::PickTwoOfFive).unwrap();
+
+        assert!(draft.hands.iter().all(|hand| hand.len() == HAND_SIZE));
+
+        while let Some(player) = draft.to_act() {
+            let card = draft.hand(player)[0].clone();
+            draft.act(player, Action::Pick(card)).unwrap();
+        }
+
+        assert_eq!(draft.picked.len(), 4);
+        assert!(draft.hands.iter().all(|hand| hand.len() == 3));
+        assert_eq!(draft.finish().unwrap().kingdom_cards.len(), 10);
+    }
+
+    #[test]
+    fn drafts_enforce_turns_hands_and_actions() {
+        use draft::*;
+        let mut draft = Draft::new(SetupConfig::none(), 2, DraftFormat::PickTwoOfFive).unwrap();
+        let theirs = draft.hand(1)[0].clone();
+        let mine = draft.hand(0)[0].clone();
+
+        assert_eq!(
+            draft.act(1, Action::Pick(theirs.clone())),
+            Err(DraftError::NotYourTurn {
+                expected: 0,
+                got: 1
+            })
+        );
+        assert_eq!(
+            draft.act(0, Action::Pick(theirs.clone())),
+            Err(DraftError::NotInHand(theirs))
+        );
+        assert_eq!(
+            draft.act(0, Action::Veto(mine.clone())),
+            Err(DraftError::WrongAction(Action::Veto(mine)))
+        );
+        assert_eq!(draft.finish().unwrap_err(), DraftError::NotFinished);
+        assert_eq!(
+            Draft::new(SetupConfig::none(), 6, DraftFormat::PickTwoOfFive).unwrap_err(),
+            DraftError::BadPlayerCount(6)
+        );
+    }
+
+    #[test]
+    fn drafts_refuse_more_than_ten_included_cards() {
+        use draft::*;
+        let eleven: HashSet<KC> = KC::iter()
+            .filter(|kc| kc.expansions().contains(&Expansion::Base2))
+            .take(11)
+            .collect();
+
+        for format in DraftFormat::iter() {
+            assert_eq!(
+                Draft::new(
+                    SetupConfig::including_cards(eleven.clone()),
+                    2,
+                    format.clone()
+                )
+                .unwrap_err(),
+                DraftError::Setup(GenSetupError::TooManyCardsIncluded)
+            );
+        }
+
+        // A draft handed back with too many included cards is simply over
+        let mut draft = Draft::new(SetupConfig::none(), 2, DraftFormat::Snake).unwrap();
+        draft.config.include_cards = Some(eleven);
+        assert_eq!(draft.to_act(), None);
+    }
+
+    fn no_young_witch() -> SetupConfig {
+        SetupConfig {
+            include_expansions: None,
+            project_count: None,
+            include_cards: None,
+            ban_cards: Some(HashSet::from([KC::YoungWitch])),
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        }
+    }
+
+    #[test]
+    fn evolving_swaps_that_many_cards_and_remembers_the_parent() {
+        let parent = gen_setup(no_young_witch()).unwrap();
+
+        let child = evolve(
+            &parent,
+            &EvolveConfig {
+                kingdom_swaps: 3,
+                project_swaps: 0,
+                prefer: SwapPreference::Random,
+            },
+            no_young_witch(),
+            &[],
+        )
+        .unwrap();
+
+        let kept = child
+            .kingdom_cards
+            .iter()
+            .filter(|c| parent.kingdom_cards.contains(c))
+            .count();
+        assert_eq!(kept, 7);
+        assert_eq!(child.kingdom_cards.len(), 10);
+        assert_eq!(child.project_cards, parent.project_cards);
+        assert_eq!(
+            pretty::setup_from_code(&child.parent.unwrap())
+                .unwrap()
+                .cards(),
+            parent.cards()
+        );
+    }
+
+    #[test]
+    fn evolving_prefers_the_most_played_cards() {
+        let parent = Setup::new(
+            vec![KC::Smithy, KC::Village, KC::Market],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let played = |cards: Vec<KC>, rating| history::Played {
+            name: "Game".to_string(),
+            at: "today".to_string(),
+            setup: Setup::new(cards, None, vec![], HashMap::new(), None),
+            rating: Some(rating),
+            scores: vec![],
+        };
+        let history = vec![
+            played(vec![KC::Smithy, KC::Village], 5),
+            played(vec![KC::Smithy, KC::Market], 1),
+        ];
+        let swap_one = |prefer| EvolveConfig {
+            kingdom_swaps: 1,
+            project_swaps: 0,
+            prefer,
+        };
+
+        let child = evolve(
+            &parent,
+            &swap_one(SwapPreference::MostPlayed),
+            no_young_witch(),
+            &history,
+        )
+        .unwrap();
+        assert!(!child.kingdom_cards.contains(&KC::Smithy));
+
+        let child = evolve(
+            &parent,
+            &swap_one(SwapPreference::LowestRated),
+            no_young_witch(),
+            &history,
+        )
+        .unwrap();
+        assert!(!child.kingdom_cards.contains(&KC::Market));
+    }
+
+    #[test]
+    fn seeded_generation_is_repeatable() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let config = SetupConfig {
+            include_expansions: None,
+            project_count: None,
+            include_cards: Some(HashSet::from([KC::Smithy, KC::Witch, KC::Moat])),
+            ban_cards: None,
+            bane_count: Some(BaneCount::ThreeBanes),
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        };
+        let generate = || {
+            let setup =
+                gen_setup_with_rng(config.clone(), &[], &mut StdRng::seed_from_u64(42)).unwrap();
+            serde_json::to_value(&setup).unwrap()
+        };
+
+        assert_eq!(generate(), generate());
+    }
+
+    #[test]
+    fn json_output_has_the_documented_fields() {
+        let config = SetupConfig::none();
+        let setup = gen_setup(config.clone()).unwrap();
+
+        let output: serde_json::Value =
+            serde_json::from_str(&json::setup(None, Some(3), &config, &setup)).unwrap();
+
+        assert_eq!(output["version"], json::SCHEMA_VERSION);
+        assert_eq!(output["seed"], 3);
+        assert!(output["name"].is_null());
+        assert_eq!(output["setup"], serde_json::to_value(&setup).unwrap());
+        assert_eq!(output["config"]["project_count"], serde_json::Value::Null);
+        let cost_total: u64 = output["hists"]["costs"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|v| v.as_u64().unwrap())
+            .sum();
+        assert_eq!(cost_total as usize, setup.cards().len());
+        assert!(output["hists"]["types"]["Action"].as_u64().is_some());
+        assert!(output["hists"]["expansions"].is_object());
+    }
+
+    fn zebra_setup() -> Setup {
+        Setup::new(
+            vec![KC::YoungWitch, KC::Smithy, KC::Village],
+            Some(KC::Chapel),
+            vec![Project::StarChart],
+            HashMap::from([(KC::Smithy, BaneCard::Zebra)]),
+            Some(KC::Moat),
+        )
+    }
+
+    #[test]
+    fn markdown_groups_cards_and_marks_banes() {
+        let doc = pretty::render(&pretty::Markdown, "Game", &zebra_setup());
+
+        assert!(doc.starts_with("# Game\n"));
+        assert!(doc.contains(
+            "**Base2/Base1**\n\n- Chapel (Bane)\n- Smithy (Zebra with Moat)\n- Village\n"
+        ));
+        assert!(doc.contains("**Cornucopia**\n\n- Young Witch\n"));
+        assert!(doc.contains("## Project Cards\n\n**Renaissance**\n\n- Star Chart\n"));
+        assert!(doc.contains("## Custom Bane Cards"));
+        assert!(doc.contains("| Action | 4 |"));
+    }
+
+    #[test]
+    fn html_is_a_whole_escaped_page() {
+        let doc = pretty::render(&pretty::Html, "Cards & <Coins>", &zebra_setup());
+
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.contains("<title>Cards &amp; &lt;Coins&gt;</title>"));
+        assert!(doc.contains("<li>Chapel (Bane)</li>"));
+        assert!(doc.contains("<tr><td>Base2</td><td>3</td></tr>"));
+        assert!(!doc.contains("<link"));
+        assert!(doc.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn svg_sheets_have_a_tile_per_card_and_project() {
+        let doc = svg::sheet(&zebra_setup(), "Game", &svg::PaperSize::Letter);
+
+        // Three kingdom cards, the bane, the second zebra and a project
+        assert_eq!(doc.matches("<g class=\"tile\">").count(), 6);
+        assert!(doc.contains("width=\"215.9mm\" height=\"279.4mm\""));
+        assert!(doc.contains(">Young Witch<"));
+        assert!(doc.contains(">Bane (Young Witch)<"));
+        assert!(doc.contains(">Zebra with Moat<"));
+        assert!(doc.contains(">Second Zebra<"));
+        assert!(doc.contains(">Star Chart<"));
+        assert!(doc.contains(">Base2 / Base1<"));
+    }
+
+    #[test]
+    fn svg_tiles_fit_on_the_page() {
+        let setup = gen_setup(SetupConfig {
+            include_expansions: None,
+            project_count: Some(ProjectCount::TwoProjects),
+            include_cards: Some(HashSet::from([KC::YoungWitch])),
+            ban_cards: None,
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        })
+        .unwrap();
+
+        for paper in svg::PaperSize::iter() {
+            let (_, height) = paper.size();
+            let doc = svg::sheet(&setup, "Game", &paper);
+            let bottom = doc
+                .lines()
+                .filter(|l| l.starts_with("<rect x="))
+                .map(|l| {
+                    let attr = |name: &str| -> f64 {
+                        let start = l.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+                        l[start..].split('"').next().unwrap().parse().unwrap()
+                    };
+                    attr("y") + attr("height")
+                })
+                .fold(0.0, f64::max);
+
+            assert!(bottom <= height);
+        }
+    }
+
+    #[test]
+    fn analysis_spots_a_classic_engine() {
+        let setup = Setup::new(
+            vec![
+                KC::Village,
+                KC::Laboratory,
+                KC::Smithy,
+                KC::Market,
+                KC::Chapel,
+                KC::Cellar,
+                KC::Harbinger,
+                KC::Merchant,
+                KC::Vassal,
+                KC::Poacher,
+            ],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let analysis = analysis::analyze(&setup);
+
+        assert_eq!(analysis.engine_potential, 10);
+        assert_eq!(analysis.label, analysis::Label::Engine);
+        assert!(analysis.reasons.iter().any(|r| r.contains("Chapel")));
+    }
+
+    #[test]
+    fn analysis_calls_junk_without_trashing_a_slog() {
+        let setup = Setup::new(
+            vec![
+                KC::Witch,
+                KC::SeaHag,
+                KC::Gardens,
+                KC::Workshop,
+                KC::Village,
+                KC::Smithy,
+                KC::Market,
+                KC::Cellar,
+                KC::Harbinger,
+                KC::Vassal,
+            ],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let analysis = analysis::analyze(&setup);
+
+        assert_eq!(analysis.label, analysis::Label::Slog);
+        assert!(analysis.attack_intensity >= 8);
+        assert!(analysis
+            .reasons
+            .contains(&"Nothing defends against the attacks".to_string()));
+    }
+
+    #[test]
+    fn analysis_favours_money_with_a_lone_terminal_draw() {
+        let setup = Setup::new(
+            vec![
+                KC::Smithy,
+                KC::Bureaucrat,
+                KC::Harbinger,
+                KC::Merchant,
+                KC::Vassal,
+                KC::Poacher,
+                KC::Gardens,
+                KC::Workshop,
+                KC::Mine,
+                KC::Artisan,
+            ],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let analysis = analysis::analyze(&setup);
+
+        assert_eq!(analysis.label, analysis::Label::BigMoney);
+        assert!(analysis.big_money_viability > analysis.engine_potential);
+    }
+
+    #[test]
+    fn projects_count_towards_the_analysis() {
+        let mut setup = Setup::new(
+            vec![KC::Smithy, KC::Moat],
+            None,
+            vec![],
+            HashMap::new(),
+            None,
+        );
+        let before = analysis::analyze(&setup).engine_potential;
+        setup.project_cards = vec![Project::Barracks, Project::Fair];
+
+        assert_eq!(analysis::analyze(&setup).engine_potential, before + 5);
+    }
+
+    #[test]
+    fn synergies_are_found_in_either_order_and_with_projects() {
+        let setup = Setup::new(
+            vec![KC::Feast, KC::Smithy, KC::Village, KC::ThroneRoom],
+            None,
+            vec![Project::Barracks],
+            HashMap::new(),
+            None,
+        );
+        let combos: Vec<(KC, synergy::Partner)> = synergy::combos(&setup)
+            .into_iter()
+            .map(|s| (s.card.clone(), s.partner.clone()))
+            .collect();
+
+        assert!(combos.contains(&(KC::ThroneRoom, synergy::Partner::Card(KC::Feast))));
+        assert!(combos.contains(&(KC::Village, synergy::Partner::Card(KC::Smithy))));
+        assert!(combos.contains(&(KC::Smithy, synergy::Partner::Project(Project::Barracks))));
+    }
+
+    #[test]
+    fn synergy_table_has_no_self_pairs_or_duplicates() {
+        let mut seen = HashSet::new();
+
+        for s in synergy::SYNERGIES {
+            let pair = match &s.partner {
+                synergy::Partner::Card(card) => {
+                    assert_ne!(&s.card, card);
+                    let mut pair = vec![format!("{:?}", s.card), format!("{:?}", card)];
+                    pair.sort();
+                    pair
+                }
+                synergy::Partner::Project(project) => {
+                    vec![format!("{:?}", s.card), format!("{:?}", project)]
+                }
+            };
+
+            assert!(seen.insert(pair), "{:?} is listed twice", s);
+        }
+    }
+
+    #[test]
+    fn require_combo_only_gives_kingdoms_with_a_combo() {
+        let config = SetupConfig {
+            require_combo: true,
+            ..SetupConfig::none()
+        };
+
+        for _ in 0..10 {
+            let setup = gen_setup(config.clone()).unwrap();
+            assert!(!synergy::combos(&setup).is_empty());
+        }
+    }
+
+    #[test]
+    fn avoid_swingy_gives_up_when_includes_force_a_swingy_pair() {
+        let config = SetupConfig {
+            include_cards: Some(HashSet::from([KC::KingsCourt, KC::Torturer])),
+            avoid_swingy: true,
+            ..SetupConfig::none()
+        };
+
+        assert_eq!(
+            gen_setup(config).unwrap_err(),
+            GenSetupError::CouldNotSatisfySynergies
+        );
+    }
+
+    fn kingdom(cards: &[KC]) -> Setup {
+        Setup::new(cards.to_vec(), None, vec![], HashMap::new(), None)
+    }
+
+    #[test]
+    fn swapping_one_card_keeps_kingdoms_similar() {
+        let a = kingdom(&[
+            KC::Village,
+            KC::Smithy,
+            KC::Market,
+            KC::Chapel,
+            KC::Witch,
+            KC::Moat,
+            KC::Cellar,
+            KC::Festival,
+            KC::Mine,
+            KC::Gardens,
+        ]);
+        let mut b = a.clone();
+        b.kingdom_cards[0] = KC::MiningVillage;
+        let c = kingdom(&[
+            KC::Duke,
+            KC::Harem,
+            KC::Island,
+            KC::Nobles,
+            KC::Fairgrounds,
+            KC::SilkRoad,
+            KC::Farmland,
+            KC::Tunnel,
+            KC::Bank,
+            KC::Venture,
+        ]);
+
+        let close = similarity::similarity(&a, &b);
+        let far = similarity::similarity(&a, &c);
+
+        assert!(close > 0.75 && close < 1.0);
+        assert_eq!(close, similarity::similarity(&b, &a));
+        assert!(far < 0.25);
+    }
+
+    #[test]
+    fn projects_count_towards_similarity() {
+        let a = kingdom(&[KC::Village, KC::Smithy]);
+        let mut b = a.clone();
+        b.project_cards = vec![Project::Fleet];
+
+        assert!(similarity::similarity(&a, &b) < similarity::similarity(&a, &a));
+    }
+
+    #[test]
+    fn nearest_puts_the_most_alike_first() {
+        let setup = gen_setup(no_young_witch()).unwrap();
+        let history: Vec<history::Played> = (0..5)
+            .map(|i| {
+                let mut other = gen_setup(no_young_witch()).unwrap();
+                other.kingdom_cards.truncate(10 - i);
+                other
+                    .kingdom_cards
+                    .extend(setup.kingdom_cards.iter().take(i).cloned());
+                history::Played::now(format!("Game {}", i), other)
+            })
+            .collect();
+
+        let nearest = similarity::nearest(&setup, &history, 3);
+
+        assert_eq!(nearest.len(), 3);
+        assert!(nearest
+            .windows(2)
+            .all(|pair| pair[0].similarity >= pair[1].similarity));
+    }
+
+    #[test]
+    fn max_similarity_rejects_kingdoms_like_previous_ones() {
+        let previous = gen_setup(no_young_witch()).unwrap();
+        let config = SetupConfig {
+            max_similarity: Some(0.4),
+            ..no_young_witch()
+        };
+        let history = [previous.clone()];
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let setup = gen_setup_with_rng(config.clone(), &history, &mut rng).unwrap();
+            assert!(similarity::similarity(&setup, &previous) <= 0.4);
+        }
+
+        let config = SetupConfig {
+            include_cards: Some(previous.kingdom_cards.iter().cloned().collect()),
+            project_count: Some(ProjectCount::NoProjects),
+            ..config
+        };
+        assert!(gen_setup(config.clone()).is_ok());
+        assert_eq!(
+            gen_setup_with_rng(config, &history, &mut rng).unwrap_err(),
+            GenSetupError::CouldNotSatisfyMaxSimilarity
+        );
+    }
+
+    fn base2_game(players: usize) -> engine::Game {
+        engine::Game::new(&base2_game_setup(), players, 7).unwrap()
+    }
+
+    fn base2_game_setup() -> Setup {
+        kingdom(&[
+            KC::Chapel,
+            KC::Gardens,
+            KC::Library,
+            KC::Merchant,
+            KC::Militia,
+            KC::Moat,
+            KC::Smithy,
+            KC::ThroneRoom,
+            KC::Village,
+            KC::Witch,
+        ])
+    }
+
+    /// Play the current turn with `choices`, buying nothing, and return the
+    /// game as it was at the start of the buy phase
+    fn until_buy(game: &mut engine::Game, choices: Vec<Vec<engine::Card>>) -> engine::Game {
+        let mut choices = engine::Scripted::new(choices);
+        let mut at_buy = None;
+        let mut decider = |g: &engine::Game, d: &engine::Decision| {
+            if d.kind == engine::DecisionKind::Buy {
+                at_buy.get_or_insert_with(|| g.clone());
+                Some(vec![])
+            } else {
+                engine::Decider::decide(&mut choices, g, d)
+            }
+        };
+        game.take_turn(&mut decider).unwrap();
+        at_buy.unwrap()
+    }
+
+    fn big_money(_: &engine::Game, d: &engine::Decision) -> Option<Vec<engine::Card>> {
+        use engine::{Card, DecisionKind};
+
+        Some(match d.kind {
+            DecisionKind::Buy => d
+                .options
+                .iter()
+                .filter(|c| c != &&Card::Curse)
+                .rev()
+                .max_by_key(|c| c.cost())
+                .cloned()
+                .into_iter()
+                .collect(),
+            _ => d
+                .options
+                .iter()
+                .take(d.min.max(1).min(d.max))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn engine_sets_up_the_supply_by_player_count() {
+        use engine::Card;
+
+        let game = base2_game(2);
+        assert_eq!(game.pile(&Card::Province).unwrap().count, 8);
+        assert_eq!(game.pile(&Card::Kingdom(KC::Gardens)).unwrap().count, 8);
+        assert_eq!(game.pile(&Card::Kingdom(KC::Smithy)).unwrap().count, 10);
+        assert_eq!(game.pile(&Card::Curse).unwrap().count, 10);
+        assert_eq!(game.pile(&Card::Copper).unwrap().count, 46);
+        for player in &game.players {
+            assert_eq!(player.hand.len(), 5);
+            assert_eq!(player.deck.len(), 5);
+        }
+
+        let game = base2_game(4);
+        assert_eq!(game.pile(&Card::Province).unwrap().count, 12);
+        assert_eq!(game.pile(&Card::Curse).unwrap().count, 30);
+        assert_eq!(game.score(3), 3);
+    }
+
+    #[test]
+    fn engine_games_replay_from_their_seed_and_choices() {
+        let mut a = base2_game(2);
+        let mut b = base2_game(2);
+        a.run(&mut big_money).unwrap();
+        b.run(&mut big_money).unwrap();
+
+        assert!(a.is_over());
+        assert_eq!(a, b);
+        assert_eq!(
+            a.take_turn(&mut big_money),
+            Err(engine::EngineError::GameOver)
+        );
+
+        let mut suspended = base2_game(2);
+        assert_eq!(
+            suspended.run(&mut engine::Scripted::default()),
+            Err(engine::EngineError::Suspended)
+        );
+    }
+
+    #[test]
+    fn engine_resolves_draw_and_coin_cards() {
+        use engine::Card;
+
+        let mut game = base2_game(2);
+        game.players[0].hand = vec![
+            Card::Kingdom(KC::ThroneRoom),
+            Card::Kingdom(KC::Smithy),
+            Card::Copper,
+            Card::Copper,
+            Card::Copper,
+        ];
+        game.players[0].deck.extend(vec![Card::Silver; 3]);
+        let at_buy = until_buy(
+            &mut game,
+            vec![
+                vec![Card::Kingdom(KC::ThroneRoom)],
+                vec![Card::Kingdom(KC::Smithy)],
+            ],
+        );
+        let player = &at_buy.players[0];
+        assert_eq!(player.in_play.len() + player.hand.len(), 5 + 6);
+
+        let mut game = base2_game(2);
+        game.players[0].hand = vec![
+            Card::Kingdom(KC::Merchant),
+            Card::Silver,
+            Card::Silver,
+            Card::Copper,
+            Card::Estate,
+        ];
+        game.players[0].deck.push(Card::Estate);
+        let at_buy = until_buy(&mut game, vec![vec![Card::Kingdom(KC::Merchant)]]);
+        assert_eq!(at_buy.coins, 2 + 2 + 1 + 1);
+
+        let mut game = base2_game(2);
+        game.players[0].hand = vec![Card::Kingdom(KC::Library), Card::Copper, Card::Copper];
+        game.players[0].deck = vec![
+            Card::Estate,
+            Card::Estate,
+            Card::Estate,
+            Card::Estate,
+            Card::Estate,
+            Card::Kingdom(KC::Village),
+        ];
+        let at_buy = until_buy(
+            &mut game,
+            vec![
+                vec![Card::Kingdom(KC::Library)],
+                vec![Card::Kingdom(KC::Village)],
+            ],
+        );
+        let player = &at_buy.players[0];
+        assert_eq!(player.hand, vec![Card::Estate; 5]);
+        assert_eq!(player.discard.last(), Some(&Card::Kingdom(KC::Village)));
+    }
+
+    #[test]
+    fn engine_resolves_attacks_and_moat() {
+        use engine::{Card, EngineError};
+
+        let mut game = base2_game(3);
+        game.players[0].hand[0] = Card::Kingdom(KC::Witch);
+        game.players[1].hand[0] = Card::Kingdom(KC::Moat);
+        until_buy(
+            &mut game,
+            vec![
+                vec![Card::Kingdom(KC::Witch)],
+                vec![Card::Kingdom(KC::Moat)],
+            ],
+        );
+        assert!(!game.players[1].cards().any(|c| c == &Card::Curse));
+        assert!(game.players[2].cards().any(|c| c == &Card::Curse));
+        assert_eq!(game.pile(&Card::Curse).unwrap().count, 19);
+
+        let mut game = base2_game(2);
+        game.players[0].hand[0] = Card::Kingdom(KC::Militia);
+        game.players[1].hand = vec![
+            Card::Copper,
+            Card::Copper,
+            Card::Copper,
+            Card::Estate,
+            Card::Estate,
+        ];
+        until_buy(
+            &mut game,
+            vec![
+                vec![Card::Kingdom(KC::Militia)],
+                vec![Card::Estate, Card::Estate],
+            ],
+        );
+        assert_eq!(game.players[1].hand, vec![Card::Copper; 3]);
+
+        let mut game = base2_game(2);
+        game.players[0].hand[0] = Card::Kingdom(KC::Militia);
+        let mut decider = engine::Scripted::new(vec![
+            vec![Card::Kingdom(KC::Militia)],
+            vec![Card::Gold, Card::Gold],
+        ]);
+        assert!(matches!(
+            game.take_turn(&mut decider),
+            Err(EngineError::InvalidChoice { .. })
+        ));
+    }
+
+    #[test]
+    fn engine_trashes_with_chapel() {
+        use engine::Card;
+
+        let mut game = base2_game(2);
+        game.players[0].hand = vec![
+            Card::Kingdom(KC::Chapel),
+            Card::Copper,
+            Card::Estate,
+            Card::Estate,
+            Card::Estate,
+        ];
+        let score = game.score(0);
+        let at_buy = until_buy(
+            &mut game,
+            vec![
+                vec![Card::Kingdom(KC::Chapel)],
+                vec![Card::Estate, Card::Estate, Card::Estate],
+            ],
+        );
+        assert_eq!(at_buy.trash, vec![Card::Estate; 3]);
+        assert_eq!(at_buy.coins, 1);
+        assert_eq!(game.score(0), score - 3);
+    }
+
+    #[test]
+    fn engine_ends_on_provinces_or_three_piles() {
+        use engine::Card;
+
+        let mut game = base2_game(2);
+        assert!(!game.is_over());
+        for card in [Card::Kingdom(KC::Smithy), Card::Kingdom(KC::Village)] {
+            game.supply
+                .iter_mut()
+                .find(|p| p.card == card)
+                .unwrap()
+                .count = 0;
+        }
+        assert!(!game.is_over());
+        game.supply
+            .iter_mut()
+            .find(|p| p.card == Card::Curse)
+            .unwrap()
+            .count = 0;
+        assert!(game.is_over());
+
+        let mut game = base2_game(2);
+        game.supply
+            .iter_mut()
+            .find(|p| p.card == Card::Province)
+            .unwrap()
+            .count = 0;
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn engine_turns_away_what_it_cant_play() {
+        use engine::{EngineError, Game};
+
+        let setup = kingdom(&[KC::Smithy, KC::Wharf]);
+        assert_eq!(
+            Game::new(&setup, 2, 0),
+            Err(EngineError::UnsupportedCard(KC::Wharf))
+        );
+        assert_eq!(
+            Game::new(&kingdom(&[KC::Smithy]), 5, 0),
+            Err(EngineError::BadPlayerCount(5))
+        );
+        assert_eq!(
+            Game::new(&zebra_setup(), 2, 0),
+            Err(EngineError::UnsupportedSetup)
+        );
+    }
+
+    fn every_base2_card() -> Setup {
+        kingdom(
+            &KC::iter()
+                .filter(|card| card.expansions().contains(&Expansion::Base2))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn big_money_smithy_beats_big_money() {
+        use bots::Bot;
+
+        let result = bots::simulate(
+            &base2_game_setup(),
+            &[Bot::BigMoney, Bot::BigMoneySmithy],
+            40,
+            3,
+        )
+        .unwrap();
+
+        assert_eq!(result.games, 40);
+        assert!(result.average_turns < bots::TURN_LIMIT as f64);
+        let wins: Vec<f64> = result.standings.iter().map(|s| s.wins).collect();
+        assert_eq!(wins.iter().sum::<f64>(), 40.0);
+        assert!(wins[1] > wins[0]);
+    }
+
+    #[test]
+    fn average_turns_are_per_player() {
+        use bots::Bot;
+
+        let turns = |players: usize| {
+            bots::simulate(&base2_game_setup(), &vec![Bot::BigMoney; players], 20, 5)
+                .unwrap()
+                .average_turns
+        };
+
+        // More players split the same Provinces, so nobody takes more turns
+        let (two, four) = (turns(2), turns(4));
+        assert!(two < 30.0, "{}", two);
+        assert!(four <= two, "{} > {}", four, two);
+    }
+
+    #[test]
+    fn simulations_dont_depend_on_the_thread_count() {
+        use bots::Bot;
+
+        let bots = [
+            Bot::BigMoney,
+            Bot::SingleCard(KC::Witch),
+            Bot::BigMoneySmithy,
+        ];
+        let one = bots::simulate_on(&base2_game_setup(), &bots, 30, 5, 1).unwrap();
+        let three = bots::simulate_on(&base2_game_setup(), &bots, 30, 5, 3).unwrap();
+        assert_eq!(one, three);
+
+        let witch = &one.standings[1];
+        assert!(witch.buys.count(&engine::Card::Kingdom(KC::Witch)) > 0);
+        assert_eq!(
+            one.standings[0]
+                .buys
+                .count(&engine::Card::Kingdom(KC::Witch)),
+            0
+        );
+        assert!(one.tie_rate <= 1.0);
+    }
+
+    #[test]
+    fn simulations_flag_dominant_bots() {
+        use bots::Bot;
+
+        let result = bots::simulate(
+            &base2_game_setup(),
+            &[Bot::BigMoney, Bot::SingleCard(KC::Witch)],
+            40,
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            result.dominant(0.6).map(|standing| &standing.bot),
+            Some(&Bot::SingleCard(KC::Witch))
+        );
+        assert_eq!(result.dominant(1.0), None);
+    }
+
+    #[test]
+    fn single_card_bots_play_every_base2_card() {
+        use bots::Bot;
+
+        let setup = every_base2_card();
+        for card in setup.kingdom_cards.clone() {
+            let result =
+                bots::simulate(&setup, &[Bot::SingleCard(card), Bot::BigMoney], 2, 11).unwrap();
+            assert_eq!(result.standings.iter().map(|s| s.wins).sum::<f64>(), 2.0);
+        }
+
+        assert_eq!(
+            bots::simulate(&base2_game_setup(), &[Bot::SingleCard(KC::Cellar)], 1, 0),
+            Err(engine::EngineError::NotInSupply(KC::Cellar))
+        );
+    }
+
+    #[test]
+    fn ties_go_to_fewer_turns_then_are_shared() {
+        use engine::Card;
+
+        let mut game = base2_game(2);
+        assert_eq!(bots::winners(&game), vec![0.5, 0.5]);
+
+        game.turn = 1;
+        assert_eq!(bots::winners(&game), vec![0.0, 1.0]);
+
+        game.players[0].hand.push(Card::Estate);
+        assert_eq!(bots::winners(&game), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn engine_records_what_happens() {
+        use engine::{Card, Event};
+
+        let game = base2_game(2);
+        assert_eq!(
+            game.events,
+            vec![
+                Event::Shuffle {
+                    player: 0,
+                    seed: game.shuffle_seed(0)
+                },
+                Event::Shuffle {
+                    player: 1,
+                    seed: game.shuffle_seed(1)
+                },
+            ]
+        );
+        assert_eq!(game.scores, vec![3, 3]);
+
+        let mut game = base2_game(3);
+        game.players[0].hand[0] = Card::Kingdom(KC::Witch);
+        until_buy(&mut game, vec![vec![Card::Kingdom(KC::Witch)]]);
+        let events = &game.events[3..];
+        assert_eq!(events[0], Event::Turn { player: 0, turn: 0 });
+        assert!(
+            matches!(&events[1], Event::Decision { choice, .. } if choice == &vec![Card::Kingdom(KC::Witch)])
+        );
+        assert!(events.contains(&Event::Gain {
+            player: 2,
+            card: Card::Curse
+        }));
+        assert!(events.contains(&Event::Score {
+            player: 1,
+            score: 2
+        }));
+    }
+
+    #[test]
+    fn game_logs_replay_and_catch_tampering() {
+        use bots::Bot;
+
+        let setup = base2_game_setup();
+        let (game, _) = bots::play(
+            &setup,
+            &[Bot::SingleCard(KC::Militia), Bot::BigMoneySmithy],
+            9,
+        )
+        .unwrap();
+        assert!(matches!(
+            game.events.last(),
+            Some(engine::Event::End { .. })
+        ));
+
+        let log = gamelog::write(&setup, vec!["A".to_string(), "B".to_string()], &game);
+        assert_eq!(gamelog::verify(&log), Ok(game.clone()));
+
+        let (header, events) = gamelog::parse(&log).unwrap();
+        assert_eq!(header.names, vec!["A", "B"]);
+        let turns = gamelog::turns(&events);
+        assert_eq!(turns.len(), game.turn + 1);
+        assert!(turns[1..]
+            .iter()
+            .all(|turn| matches!(turn[0], engine::Event::Turn { .. })));
+
+        let reseeded = log.replacen(&format!("\"seed\":{}", game.seed), "\"seed\":12345", 1);
+        assert!(matches!(
+            gamelog::verify(&reseeded),
+            Err(gamelog::LogError::Mismatch { line: 2, .. })
+        ));
+
+        // Logs of unfinished games check out too, as long as turns are whole
+        let fifth_turn = log
+            .lines()
+            .position(|line| line.contains("\"turn\":4"))
+            .unwrap();
+        let unfinished: String = log
+            .lines()
+            .take(fifth_turn)
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(gamelog::verify(&unfinished).unwrap().turn, 4);
+
+        let doubled = log.replacen("\"Chapel\"", "\"Gardens\"", 1);
+        assert_eq!(
+            gamelog::verify(&doubled),
+            Err(gamelog::LogError::InvalidSetup(vec![
+                SetupError::DuplicateCard(KC::Gardens)
+            ]))
+        );
+
+        let broken = log.replacen("Turn", "Tern", 1);
+        assert!(matches!(
+            gamelog::verify(&broken),
+            Err(gamelog::LogError::Parse { .. })
+        ));
+        assert_eq!(gamelog::verify(""), Err(gamelog::LogError::Empty));
+    }
+
+    #[test]
+    fn sessions_play_bots_and_wait_for_people() {
+        use bots::Bot;
+        use session::{Seat, Session};
+
+        let setup = base2_game_setup();
+        let bots = Session::new(&setup, vec![Seat::Bot(Bot::BigMoney); 2], 4).unwrap();
+        assert!(bots.pending().is_none());
+        assert_eq!(
+            bots.game(),
+            &bots::play(&setup, &[Bot::BigMoney, Bot::BigMoney], 4)
+                .unwrap()
+                .0
+        );
+
+        let mut session =
+            Session::new(&setup, vec![Seat::Human, Seat::Bot(Bot::BigMoney)], 4).unwrap();
+        assert_eq!(session.pending().unwrap().player, 0);
+        assert!(session.view(0).decision.is_some());
+        assert!(session.view(1).decision.is_none());
+        assert_eq!(session.decision(0), session.pending());
+        assert_eq!(session.decision(1), None);
+        assert_eq!(session.view(1).waiting_on, Some(0));
+        assert_eq!(session.view(0).players[1].name, "BigMoney");
+
+        let before = session.clone();
+        assert_eq!(
+            session.choose(1, vec![]),
+            Err(engine::EngineError::NotYourDecision(1))
+        );
+        assert!(matches!(
+            session.choose(0, vec![engine::Card::Kingdom(KC::Cellar)]),
+            Err(engine::EngineError::InvalidChoice { .. })
+        ));
+        assert_eq!(session, before);
+
+        while let Some(decision) = session.pending().cloned() {
+            assert_eq!(decision.player, 0);
+            let choice = big_money(session.game(), &decision).unwrap();
+            session.choose(0, choice).unwrap();
+        }
+        assert!(session.game().is_over());
+        assert!(matches!(
+            session.game().events.last(),
+            Some(engine::Event::End { .. })
+        ));
+        assert_eq!(
+            session.choose(0, vec![]),
+            Err(engine::EngineError::GameOver)
+        );
+        assert!(session
+            .events(1, 0)
+            .iter()
+            .all(|event| !matches!(event, engine::Event::Shuffle { .. })));
+    }
+
+    #[test]
+    fn scoring_counts_every_victory_card() {
+        use scoring::{Card, Deck, ScoringError};
+
+        let setup = kingdom(&[
+            KC::Duke,
+            KC::Fairgrounds,
+            KC::Farmland,
+            KC::Gardens,
+            KC::Harem,
+            KC::Island,
+            KC::Mill,
+            KC::Nobles,
+            KC::SilkRoad,
+            KC::Tunnel,
+        ]);
+        let deck = |cards: &[(Card, usize)]| Deck {
+            name: "Ann".to_string(),
+            cards: cards.iter().cloned().collect(),
+            vp_tokens: 0,
+        };
+        let points = |cards: &[(Card, usize)]| scoring::score(&setup, &deck(cards)).unwrap().total;
+
+        assert_eq!(points(&[(Card::Copper, 7), (Card::Estate, 3)]), 3);
+        assert_eq!(points(&[(Card::Province, 2), (Card::Curse, 3)]), 9);
+        assert_eq!(
+            points(&[(Card::Duchy, 3), (Card::Kingdom(KC::Duke), 2)]),
+            9 + 2 * 3
+        );
+        // 19 cards make Gardens worth 1, 20 make it worth 2
+        assert_eq!(
+            points(&[(Card::Copper, 17), (Card::Kingdom(KC::Gardens), 2)]),
+            2
+        );
+        assert_eq!(
+            points(&[(Card::Copper, 18), (Card::Kingdom(KC::Gardens), 2)]),
+            4
+        );
+        // Five differently named cards
+        assert_eq!(
+            points(&[
+                (Card::Copper, 1),
+                (Card::Silver, 1),
+                (Card::Gold, 1),
+                (Card::Curse, 1),
+                (Card::Kingdom(KC::Fairgrounds), 1),
+            ]),
+            2 - 1
+        );
+        // Eight victory cards, Silk Road among them
+        assert_eq!(
+            points(&[(Card::Estate, 6), (Card::Kingdom(KC::SilkRoad), 2)]),
+            6 + 2 * 2
+        );
+        assert_eq!(
+            points(&[
+                (Card::Kingdom(KC::Harem), 1),
+                (Card::Kingdom(KC::Nobles), 1),
+                (Card::Kingdom(KC::Farmland), 1),
+                (Card::Kingdom(KC::Island), 1),
+                (Card::Kingdom(KC::Tunnel), 1),
+                (Card::Kingdom(KC::Mill), 1),
+            ]),
+            11
+        );
+
+        let score = scoring::score(
+            &setup,
+            &Deck {
+                vp_tokens: 4,
+                ..deck(&[(Card::Province, 1), (Card::Copper, 7)])
+            },
+        )
+        .unwrap();
+        assert_eq!(score.total, 10);
+        assert_eq!(score.cards, [(Card::Province, 6)].into_iter().collect());
+
+        assert_eq!(
+            scoring::score(&setup, &deck(&[(Card::Kingdom(KC::Smithy), 1)])),
+            Err(ScoringError::NotInKingdom(KC::Smithy))
+        );
+
+        // Prosperity's basics score without being kingdom cards
+        assert_eq!(
+            points(&[(Card::Colony, 2), (Card::Platinum, 3), (Card::Province, 1)]),
+            2 * 10 + 6
+        );
+        assert_eq!(
+            points(&[(Card::Colony, 2), (Card::Kingdom(KC::SilkRoad), 2)]),
+            2 * 10 + 2
+        );
+        let decks: Vec<Deck> =
+            serde_json::from_str(r#"[{"name": "Ann", "cards": {"Colony": 1, "Platinum": 2}}]"#)
+                .unwrap();
+        assert_eq!(scoring::score(&setup, &decks[0]).unwrap().total, 10);
+    }
+
+    #[test]
+    fn scored_games_are_kept_in_the_history() {
+        let setup = kingdom(&[KC::Gardens]);
+        let decks: Vec<scoring::Deck> = serde_json::from_str(
+            r#"[{"name": "Ann", "cards": {"Gardens": 2, "Copper": 18}, "vp_tokens": 1},
+                {"name": "Bo", "cards": {"Province": 1}}]"#,
+        )
+        .unwrap();
+        let scores = scoring::score_all(&setup, &decks).unwrap();
+        assert_eq!(
+            scores.iter().map(|score| score.total).collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+
+        let played = history::Played {
+            scores,
+            ..history::Played::now("Game".to_string(), setup.clone())
+        };
+        let parsed = history::parse(&history::line(&played)).unwrap();
+        assert_eq!(parsed[0].scores, played.scores);
+
+        // Unscored games are written as before
+        let unscored = history::line(&history::Played::now("Game".to_string(), setup));
+        assert!(!unscored.contains("scores"));
+    }
+
+    #[test]
+    fn config_layers_file_then_preset_then_flags() {
+        use config::{ConfigError, ConfigFile, Preset};
+
+        let file = ConfigFile::parse(
+            r#"
+            include_expansions = ["Base2", "Intrigue2"]
+            ban_cards = ["Witch"]
+            avoid_swingy = true
+
+            [presets.casual]
+            project_count = 0
+            ban_cards = ["Militia"]
+            "#,
+            false,
+        )
+        .unwrap();
+
+        let plain = file.resolve(None, Preset::default()).unwrap();
+        assert_eq!(plain.ban_cards, Some(HashSet::from([KC::Witch])));
+        assert!(plain.avoid_swingy);
+        assert_eq!(plain.project_count, None);
+
+        // The file's casual replaces the built-in one
+        let casual = file.resolve(Some("casual"), Preset::default()).unwrap();
+        assert_eq!(casual.ban_cards, Some(HashSet::from([KC::Militia])));
+        assert_eq!(casual.project_count, Some(ProjectCount::NoProjects));
+        assert!(!casual.separate_top_cards);
+        assert_eq!(
+            casual.include_expansions,
+            Some(HashSet::from([Expansion::Base2, Expansion::Intrigue2]))
+        );
+
+        let flagged = file
+            .resolve(
+                Some("casual"),
+                Preset {
+                    ban_cards: Some(HashSet::from([KC::Moat])),
+                    project_count: Some(ProjectCount::OneProject),
+                    ..Preset::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(flagged.ban_cards, Some(HashSet::from([KC::Moat])));
+        assert_eq!(flagged.project_count, Some(ProjectCount::OneProject));
+
+        let intro = ConfigFile::default()
+            .resolve(Some("intro-friends"), Preset::default())
+            .unwrap();
+        assert_eq!(
+            intro.include_expansions,
+            Some(HashSet::from([Expansion::Base2]))
+        );
+        assert!(gen_setup(intro).is_ok());
+
+        assert!(matches!(
+            file.resolve(Some("tournament"), Preset::default()),
+            Err(ConfigError::UnknownPreset { .. })
+        ));
+        assert_eq!(
+            ConfigFile::parse(r#"{"presets": {"quick": {"bane_count": 0}}}"#, true)
+                .unwrap()
+                .presets["quick"]
+                .bane_count,
+            Some(BaneCount::NoBanes)
+        );
+        assert!(matches!(
+            ConfigFile::parse("project_count = \"lots\"", false),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn catalog_lists_filter_on_every_attribute() {
+        use catalog::{details, list, Filter, Listing};
+
+        let cheap_base = Filter {
+            expansions: Some(HashSet::from([Expansion::Base2])),
+            costs: Some(HashSet::from([2])),
+            ..Filter::default()
+        };
+        let names: Vec<String> = list(Listing::Cards, &cheap_base)
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["Cellar", "Chapel", "Moat"]);
+
+        let attacks = Filter {
+            types: Some(HashSet::from([CardType::Attack])),
+            ..Filter::default()
+        };
+        assert!(list(Listing::Cards, &attacks)
+            .iter()
+            .all(|entry| entry.types.contains(&CardType::Attack)));
+
+        // Expansions have no cost, so filtering on one leaves none
+        assert!(list(Listing::Expansions, &cheap_base).is_empty());
+        assert_eq!(
+            list(Listing::Banes, &Filter::default()).len(),
+            BaneCard::iter().count()
+        );
+
+        let text = pretty::listing(
+            &pretty::Text,
+            Listing::Cards,
+            &list(Listing::Cards, &cheap_base),
+        );
+        assert!(text.contains("  Moat (2, Action, Reaction) [Base2/Base1]"));
+
+        let witch = details(KC::Witch);
+        assert_eq!(witch.cost, 5);
+        assert!(witch.types.contains(&CardType::Attack));
+    }
+
+    #[test]
+    fn validation_finds_every_problem() {
+        assert!(base2_game_setup().validate().is_empty());
+
+        let mut setup = Setup::bane(KC::Smithy, vec![KC::YoungWitch]);
+        setup.bane_cards.insert(KC::Moat, BaneCard::Flank);
+        assert_eq!(
+            setup.validate(),
+            vec![
+                SetupError::KingdomCardCount(1),
+                SetupError::BaneCost(KC::Smithy),
+                SetupError::BaneCardNotInKingdom(KC::Moat),
+            ]
+        );
+
+        let config = SetupConfig {
+            ban_cards: Some(HashSet::from([KC::Witch, KC::Moat])),
+            include_cards: Some(HashSet::from([KC::Witch, KC::Moat])),
+            ..SetupConfig::none()
+        };
+        assert_eq!(
+            config.validate(),
+            vec![GenSetupError::IntersectingCardBansAndIncludes(vec![
+                KC::Moat,
+                KC::Witch
+            ])]
+        );
+        assert!(SetupConfig::none().validate().is_empty());
+
+        assert_eq!(
+            config::ConfigFile::parse("[presets.quick]\nban_crads = [\"Witch\"]", false),
+            Err(config::ConfigError::Parse(
+                "unknown setting `ban_crads`".to_string()
+            ))
+        );
+
+        let problems = vec![
+            ("Defaults".to_string(), vec![]),
+            ("Preset quick".to_string(), vec!["Too many!".to_string()]),
+        ];
+        let text = pretty::validation(&pretty::Text, "config.toml", &problems);
+        assert!(text.contains("Preset quick:\n  Too many!"));
+        assert!(!text.contains("Defaults"));
+    }
+
+    #[test]
+    fn setups_follow_the_rules_of_setting_up() {
+        let mut with_young_witch = base2_game_setup();
+        with_young_witch.kingdom_cards[0] = KC::YoungWitch;
+        assert_eq!(with_young_witch.validate(), vec![SetupError::MissingBane]);
+
+        with_young_witch.bane_card = Some(KC::Moat);
+        assert_eq!(
+            with_young_witch.validate(),
+            vec![SetupError::DuplicateCard(KC::Moat)]
+        );
+
+        let mut unneeded = base2_game_setup();
+        unneeded.bane_card = Some(KC::Cellar);
+        assert_eq!(
+            unneeded.validate(),
+            vec![SetupError::UnneededBane(KC::Cellar)]
+        );
+
+        let mut zebra = base2_game_setup();
+        zebra.bane_cards.insert(KC::Smithy, BaneCard::Zebra);
+        assert_eq!(zebra.validate(), vec![SetupError::MissingSecondZebra]);
+        zebra.second_zebra = Some(KC::Village);
+        assert_eq!(
+            zebra.validate(),
+            vec![SetupError::SecondZebraInKingdom(KC::Village)]
+        );
+        zebra.second_zebra = Some(KC::Cellar);
+        assert!(zebra.validate().is_empty());
+        zebra.bane_cards.clear();
+        assert_eq!(
+            zebra.validate(),
+            vec![SetupError::UnneededSecondZebra(KC::Cellar)]
+        );
+
+        let mut crowded = base2_game_setup();
+        crowded.kingdom_cards.push(KC::Moat);
+        crowded.project_cards = vec![Project::Academy, Project::Barracks, Project::Canal];
+        assert_eq!(
+            crowded.validate(),
+            vec![
+                SetupError::KingdomCardCount(11),
+                SetupError::DuplicateCard(KC::Moat),
+                SetupError::TooManyProjects(3),
+            ]
+        );
+
+        // Every generated setup follows them
+        for _ in 0..50 {
+            let setup = gen_setup(SetupConfig {
+                bane_count: Some(BaneCount::ThreeBanes),
+                ..SetupConfig::including_cards(HashSet::from([KC::YoungWitch]))
+            })
+            .unwrap();
+            assert_eq!(setup.validate(), vec![], "{:?}", setup);
+        }
+    }
+
+    #[test]
+    fn counting_covers_every_piece_and_sampling_is_uniform() {
+        use count::{count, sample_with_rng};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // Two cards to pick: from three that could be the bane and two that
+        // couldn't
+        let keep = [KC::Cellar, KC::Chapel, KC::Moat, KC::ThroneRoom, KC::Bandit];
+        let forced = HashSet::from([
+            KC::YoungWitch,
+            KC::Bureaucrat,
+            KC::Gardens,
+            KC::Militia,
+            KC::Moneylender,
+            KC::Poacher,
+            KC::Remodel,
+            KC::Smithy,
+        ]);
+        let config = SetupConfig {
+            include_expansions: Some(HashSet::from([Expansion::Base2])),
+            project_count: Some(ProjectCount::NoProjects),
+            ban_cards: Some(
+                KC::iter()
+                    .filter(|kc| !keep.contains(kc) && !forced.contains(kc))
+                    .collect(),
+            ),
+            ..SetupConfig::including_cards(forced)
+        };
+
+        // No cheap card picked leaves three for the bane, one leaves two
+        // and two leave one
+        let counted = count(&config).unwrap();
+        assert_eq!(counted.pool, 5);
+        assert_eq!(counted.kingdoms, 1 + 3 * 2 + 3);
+        assert_eq!(counted.young_witch_kingdoms, counted.kingdoms);
+        assert_eq!(counted.total, 3 + 6 * 2 + 3);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen: HashMap<(Vec<KC>, Option<KC>), usize> = HashMap::new();
+        for _ in 0..counted.total * 150 {
+            let setup = sample_with_rng(config.clone(), &[], &mut rng).unwrap();
+            assert!(setup.validate().is_empty());
+            let mut kingdom = setup.kingdom_cards.clone();
+            kingdom.sort();
+            *seen.entry((kingdom, setup.bane_card)).or_default() += 1;
+        }
+        assert_eq!(seen.len() as u128, counted.total);
+        assert!(seen.values().all(|n| (100..200).contains(n)), "{:?}", seen);
+
+        // A Zebra needs a cheap card past the bane
+        let banes = BaneCard::iter().count() as u128;
+        let with_banes = count(&SetupConfig {
+            bane_count: Some(BaneCount::OneBane),
+            ..config.clone()
+        })
+        .unwrap();
+        assert_eq!(with_banes.custom_banes, 10 * banes);
+        assert_eq!(
+            with_banes.total,
+            3 * (10 * (banes - 1) + 10 * 2)
+                + 6 * 2 * (10 * (banes - 1) + 10)
+                + 3 * 10 * (banes - 1)
+        );
+        for _ in 0..200 {
+            let setup = sample_with_rng(
+                SetupConfig {
+                    bane_count: Some(BaneCount::ThreeBanes),
+                    ..config.clone()
+                },
+                &[],
+                &mut rng,
+            )
+            .unwrap();
+            assert!(setup.validate().is_empty(), "{:?}", setup);
+        }
+
+        // Past what a u64 holds
+        let all = count(&SetupConfig {
+            bane_count: Some(BaneCount::ThreeBanes),
+            ..SetupConfig::none()
+        })
+        .unwrap();
+        let projects = Project::iter().count() as u128;
+        assert_eq!(all.projects, 1 + projects + projects * (projects - 1) / 2);
+        assert!(all.total > u64::MAX as u128);
+    }
+
+    #[test]
+    fn weights_make_cards_likelier_or_leave_them_out() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let base_and_cornucopia = SetupConfig {
+            project_count: Some(ProjectCount::NoProjects),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+                Expansion::Cornucopia,
+            ]))
+        };
+        let mut cornucopia_cards = |config: &SetupConfig| {
+            (0..300)
+                .map(|_| gen_setup_with_rng(config.clone(), &[], &mut rng).unwrap())
+                .flat_map(|setup| setup.kingdom_cards)
+                .filter(|card| card.expansions().contains(&Expansion::Cornucopia))
+                .count() as f64
+                / 300.0
+        };
+
+        // 13 of the 39 cards, or half the weight with equal expansions
+        let plain = cornucopia_cards(&base_and_cornucopia);
+        let equal = cornucopia_cards(&SetupConfig {
+            equal_expansions: true,
+            expansion_counts: None,
+            min_per_expansion: None,
+            ..base_and_cornucopia.clone()
+        });
+        let weighted = cornucopia_cards(&SetupConfig {
+            expansion_weights: Some(HashMap::from([(Expansion::Cornucopia, 4.0)])),
+            ..base_and_cornucopia.clone()
+        });
+        assert!((3.0..3.7).contains(&plain), "{}", plain);
+        assert!((4.6..5.4).contains(&equal), "{}", equal);
+        assert!(weighted > equal, "{}", weighted);
+
+        let no_witches = SetupConfig {
+            card_weights: Some(HashMap::from([(KC::Witch, 0.0), (KC::YoungWitch, 0.0)])),
+            ..base_and_cornucopia.clone()
+        };
+        for _ in 0..100 {
+            let setup = gen_setup_with_rng(no_witches.clone(), &[], &mut rng).unwrap();
+            assert!(!setup.cards().contains(&KC::Witch));
+            assert!(!setup.cards().contains(&KC::YoungWitch));
+        }
+
+        let nothing_left = SetupConfig {
+            expansion_weights: Some(HashMap::from([(Expansion::Base2, 0.0)])),
+            ..SetupConfig::including_expansions(HashSet::from([Expansion::Base2]))
+        };
+        assert_eq!(
+            nothing_left.validate(),
+            vec![GenSetupError::CouldNotSatisfyKingdomCards]
+        );
+        assert_eq!(
+            gen_setup(nothing_left).unwrap_err(),
+            GenSetupError::CouldNotSatisfyKingdomCards
+        );
+
+        for weight in [f64::INFINITY, f64::NAN, -1.0] {
+            let unweighable = SetupConfig {
+                card_weights: Some(HashMap::from([(KC::Witch, weight)])),
+                ..SetupConfig::none()
+            };
+            assert_eq!(unweighable.validate(), vec![GenSetupError::InvalidWeight]);
+            assert_eq!(
+                gen_setup(unweighable).unwrap_err(),
+                GenSetupError::InvalidWeight
+            );
+        }
+    }
+
+    #[test]
+    fn expansion_counts_bound_every_kingdom() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let introducing_renaissance = SetupConfig {
+            expansion_counts: Some(HashMap::from([
+                (Expansion::Renaissance, "3".parse().unwrap()),
+                (Expansion::Base2, "..2".parse().unwrap()),
+            ])),
+            min_per_expansion: Some(1),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base2,
+                Expansion::Renaissance,
+                Expansion::Seaside,
+            ]))
+        };
+        let from = |setup: &Setup, expansion: Expansion| {
+            setup
+                .kingdom_cards
+                .iter()
+                .filter(|card| card.expansions().contains(&expansion))
+                .count()
+        };
+
+        for _ in 0..100 {
+            let setup = gen_setup_with_rng(introducing_renaissance.clone(), &[], &mut rng).unwrap();
+            assert_eq!(from(&setup, Expansion::Renaissance), 3);
+            assert!(from(&setup, Expansion::Base2) <= 2);
+            assert!(from(&setup, Expansion::Seaside) >= 1);
+        }
+        let setup = count::sample_with_rng(introducing_renaissance, &[], &mut rng).unwrap();
+        assert_eq!(from(&setup, Expansion::Renaissance), 3);
+
+        // Militia is in Base1 too, so no Base2 leaves only the first
+        // edition's own cards
+        let first_edition = SetupConfig {
+            expansion_counts: Some(HashMap::from([(Expansion::Base2, "0".parse().unwrap())])),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Base1,
+                Expansion::Base2,
+                Expansion::Seaside,
+            ]))
+        };
+        for _ in 0..20 {
+            let setup = gen_setup_with_rng(first_edition.clone(), &[], &mut rng).unwrap();
+            assert_eq!(from(&setup, Expansion::Base2), 0);
+        }
+        // Each fits, but not together in ten cards
+        assert_eq!(
+            gen_setup(SetupConfig {
+                expansion_counts: Some(HashMap::from([
+                    (Expansion::Base2, "0".parse().unwrap()),
+                    (Expansion::Base1, "4..".parse().unwrap()),
+                    (Expansion::Seaside, "7..".parse().unwrap()),
+                ])),
+                ..first_edition.clone()
+            })
+            .unwrap_err(),
+            GenSetupError::ImpossibleExpansionCount(Expansion::Seaside)
+        );
+        // Two from each of every expansion is more than ten cards, even
+        // with the cards in both editions of Base counting twice
+        assert_eq!(
+            SetupConfig {
+                min_per_expansion: Some(2),
+                ..SetupConfig::none()
+            }
+            .validate(),
+            vec![GenSetupError::ImpossibleExpansionCount(
+                Expansion::Prosperity
+            )]
+        );
+
+        // Counting goes by the counts too: at most k of the s Base2 cards
+        // also in Base1 leaves sum(j <= k) C(s, j) * C(26 - s, 10 - j)
+        let shared = KC::iter()
+            .filter(|card| {
+                card.expansions().contains(&Expansion::Base1)
+                    && card.expansions().contains(&Expansion::Base2)
+            })
+            .count() as u128;
+        let choose = |n: u128, k: u128| -> u128 {
+            if k > n {
+                return 0;
+            }
+            (0..k).fold(1, |ways, i| ways * (n - i) / (i + 1))
+        };
+        let at_most = |max: u128| SetupConfig {
+            expansion_counts: Some(HashMap::from([(
+                Expansion::Base1,
+                format!("..{}", max).parse().unwrap(),
+            )])),
+            ..SetupConfig::including_expansions(HashSet::from([Expansion::Base2]))
+        };
+        // Only seven of Base2's cards are new
+        assert_eq!(26 - shared, 7);
+        assert_eq!(
+            at_most(2).validate(),
+            vec![GenSetupError::ImpossibleExpansionCount(Expansion::Base1)]
+        );
+        for max in 3..6 {
+            let config = at_most(max);
+            let ways: u128 = (0..=max)
+                .map(|j| choose(shared, j) * choose(26 - shared, 10 - j))
+                .sum();
+            assert_eq!(count::count(&config).unwrap().total, ways);
+
+            for _ in 0..10 {
+                let setup = count::sample_with_rng(config.clone(), &[], &mut rng).unwrap();
+                assert!(from(&setup, Expansion::Base1) <= max as usize);
+            }
+        }
+
+        let too_many = SetupConfig {
+            expansion_counts: Some(HashMap::from([(
+                Expansion::Renaissance,
+                "30".parse().unwrap(),
+            )])),
+            ..SetupConfig::none()
+        };
+        assert_eq!(
+            too_many.validate(),
+            vec![GenSetupError::ImpossibleExpansionCount(
+                Expansion::Renaissance
+            )]
+        );
+        assert_eq!(
+            gen_setup(too_many).unwrap_err(),
+            GenSetupError::ImpossibleExpansionCount(Expansion::Renaissance)
+        );
+
+        let militia_is_base1 = SetupConfig {
+            expansion_counts: Some(HashMap::from([(Expansion::Base1, "0".parse().unwrap())])),
+            ..SetupConfig::including_cards(HashSet::from([KC::Militia]))
+        };
+        assert_eq!(
+            militia_is_base1.validate(),
+            vec![GenSetupError::ImpossibleExpansionCount(Expansion::Base1)]
+        );
+
+        let no_room = SetupConfig {
+            expansion_counts: Some(HashMap::from([
+                (Expansion::Renaissance, "..4".parse().unwrap()),
+                (Expansion::Seaside, "..4".parse().unwrap()),
+            ])),
+            ..SetupConfig::including_expansions(HashSet::from([
+                Expansion::Renaissance,
+                Expansion::Seaside,
+            ]))
+        };
+        assert_eq!(
+            no_room.validate(),
+            vec![GenSetupError::CouldNotSatisfyExpansionCounts]
+        );
+    }
+
+    fn server_room() -> server::Harness {
+        server::Harness::new(server::Room::new(base2_game_setup(), 2, 4))
+    }
+
+    fn join(harness: &mut server::Harness, connection: usize, name: &str) -> String {
+        harness.send(
+            connection,
+            server::ClientMessage::Join {
+                name: name.to_string(),
+            },
+        );
+        match harness.take(connection).first() {
+            Some(server::ServerMessage::Welcome { token, .. }) => token.clone(),
+            other => panic!("expected a welcome, got {:?}", other),
+        }
+    }
+
+    fn errored(harness: &mut server::Harness, connection: usize) -> bool {
+        harness
+            .take(connection)
+            .iter()
+            .any(|message| matches!(message, server::ServerMessage::Error { .. }))
+    }
+
+    #[test]
+    fn server_seats_players_and_hides_their_hands() {
+        let mut harness = server_room();
+        join(&mut harness, 10, "Ann");
+        join(&mut harness, 11, "Bo");
+        harness.send(
+            12,
+            server::ClientMessage::Join {
+                name: "Cy".to_string(),
+            },
+        );
+        assert!(errored(&mut harness, 12));
+
+        harness.send(11, server::ClientMessage::Start);
+        assert!(errored(&mut harness, 11));
+        harness.send(10, server::ClientMessage::Start);
+
+        let game = harness.room.game().unwrap().clone();
+        for (seat, connection) in [(0, 10), (1, 11)] {
+            let view = harness.view(connection).unwrap();
+            assert_eq!(view.seat, seat);
+            assert_eq!(view.hand, game.players[seat].hand);
+            assert_eq!(
+                view.players[1 - seat].hand,
+                game.players[1 - seat].hand.len()
+            );
+            assert_eq!(view.players[1 - seat].name, ["Ann", "Bo"][1 - seat]);
+        }
+        assert_eq!(harness.view(10).unwrap().waiting_on, Some(0));
+        assert!(harness.view(11).unwrap().decision.is_none());
+
+        let events: Vec<engine::Event> = harness
+            .take(11)
+            .into_iter()
+            .flat_map(|message| match message {
+                server::ServerMessage::Events { events } => events,
+                _ => vec![],
+            })
+            .collect();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|event| !matches!(
+            event,
+            engine::Event::Shuffle { .. }
+                | engine::Event::Decision {
+                    decision: engine::Decision { player: 0, .. },
+                    ..
+                }
+        )));
+    }
+
+    #[test]
+    fn server_plays_a_game_to_the_end() {
+        let mut harness = server_room();
+        join(&mut harness, 10, "Ann");
+        join(&mut harness, 11, "Bo");
+        harness.send(10, server::ClientMessage::Start);
+
+        let mut answers = 0;
+        while !harness.room.game().unwrap().is_over() {
+            answers += 1;
+            assert!(answers < 5000, "the game should end");
+
+            let (connection, decision) = [10, 11]
+                .into_iter()
+                .find_map(|c| Some((c, harness.view(c)?.decision.clone()?)))
+                .expect("someone has a decision");
+            let other = 21 - connection;
+
+            harness.send(other, server::ClientMessage::Choose { choice: vec![] });
+            assert!(errored(&mut harness, other));
+
+            let game = harness.room.game().unwrap().clone();
+            let choice = big_money(&game, &decision).unwrap();
+            if decision.kind == engine::DecisionKind::Buy {
+                harness.send(
+                    connection,
+                    server::ClientMessage::Choose {
+                        choice: vec![engine::Card::Curse; 2],
+                    },
+                );
+                assert!(errored(&mut harness, connection));
+                assert_eq!(harness.room.game().unwrap(), &game);
+            }
+            harness.send(connection, server::ClientMessage::Choose { choice });
+        }
+
+        assert!(harness.view(10).unwrap().over);
+        assert!(harness.view(11).unwrap().decision.is_none());
+    }
+
+    #[test]
+    fn server_gives_seats_back_on_rejoin() {
+        let mut harness = server_room();
+        join(&mut harness, 10, "Ann");
+        let token = join(&mut harness, 11, "Bo");
+        harness.send(10, server::ClientMessage::Start);
+        harness.take(10);
+
+        harness.disconnect(11);
+        let lobby = harness.take(10);
+        assert!(lobby.iter().any(|message| matches!(
+            message,
+            server::ServerMessage::Lobby { seats, .. } if !seats[1].connected
+        )));
+
+        harness.send(
+            20,
+            server::ClientMessage::Rejoin {
+                token: "nope".to_string(),
+            },
+        );
+        assert!(errored(&mut harness, 20));
+
+        harness.send(20, server::ClientMessage::Rejoin { token });
+        let welcome = harness.take(20);
+        assert!(matches!(
+            welcome.first(),
+            Some(server::ServerMessage::Welcome { seat: 1, .. })
+        ));
+        assert!(welcome.iter().any(
+            |message| matches!(message, server::ServerMessage::State { view } if view.seat == 1)
+        ));
+
+        harness.send(
+            21,
+            server::ClientMessage::Join {
+                name: "Cy".to_string(),
+            },
+        );
+        assert!(errored(&mut harness, 21));
+    }
+
+    #[test]
+    fn power_table_covers_every_playable_card_strongest_first() {
+        let table = power::table();
+        let cards: HashSet<KC> = table.iter().map(|r| r.card.clone()).collect();
+        let playable: HashSet<KC> = every_base2_card().kingdom_cards.into_iter().collect();
+        assert_eq!(cards, playable);
+        assert!(table.windows(2).all(|w| w[0].win_rate >= w[1].win_rate));
+
+        let top = power::top_decile(table);
+        assert_eq!(top.len(), 3);
+        assert!(top.contains(&table[0].card));
+
+        let ranked = power::rank(4, 0);
+        assert_eq!(ranked.len(), table.len());
+    }
+
+    #[test]
+    fn power_limits_keep_strong_cards_apart() {
+        let table = power::table();
+        let top: Vec<KC> = power::top_decile(table).into_iter().collect();
+        let base2 = || SetupConfig {
+            include_expansions: Some(HashSet::from([Expansion::Base2])),
+            project_count: Some(ProjectCount::NoProjects),
+            ..SetupConfig::none()
+        };
+
+        for _ in 0..10 {
+            let setup = gen_setup(SetupConfig {
+                separate_top_cards: true,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
+                ..base2()
+            })
+            .unwrap();
+            assert!(
+                setup
+                    .kingdom_cards
+                    .iter()
+                    .filter(|c| top.contains(c))
+                    .count()
+                    <= 1
+            );
+
+            let setup = gen_setup(SetupConfig {
+                max_power: Some(4.5),
+                ..base2()
+            })
+            .unwrap();
+            assert!(power::power(table, &setup) <= 4.5);
+        }
+
+        assert_eq!(
+            gen_setup(SetupConfig {
+                include_cards: Some(top.iter().cloned().collect()),
+                separate_top_cards: true,
+                expansion_weights: None,
+                card_weights: None,
+                equal_expansions: false,
+                expansion_counts: None,
+                min_per_expansion: None,
+                ..base2()
+            })
+            .unwrap_err(),
+            GenSetupError::CouldNotSatisfyPower
+        );
+    }
+
+    #[test]
+    fn typescript_definitions_are_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/types.d.ts");
+        let generated = wasm::typescript();
+
+        if std::env::var_os("DOMINION_UPDATE_TYPES").is_some() {
+            std::fs::write(path, &generated).unwrap();
+        }
+
+        assert!(
+            std::fs::read_to_string(path).unwrap() == generated,
+            "src/types.d.ts is stale, regenerate it with `DOMINION_UPDATE_TYPES=1 cargo test typescript`"
+        );
+    }
+
+    #[test]
+    fn typescript_interfaces_have_every_serialized_field() {
+        let ts = wasm::typescript();
+        let setup = zebra_setup();
+        let session =
+            session::Session::new(&base2_game_setup(), vec![session::Seat::Human; 2], 1).unwrap();
+        let scored = scoring::score_all(
+            &setup,
+            &[scoring::Deck {
+                name: "Ann".to_string(),
+                cards: [(scoring::Card::Estate, 3)].into_iter().collect(),
+                vp_tokens: 0,
+            }],
+        )
+        .unwrap();
+        let objects = [
+            (
+                "SetupConfig",
+                serde_json::to_value(SetupConfig::none()).unwrap(),
+            ),
+            ("Setup", serde_json::to_value(&setup).unwrap()),
+            (
+                "EvolveConfig",
+                serde_json::to_value(EvolveConfig {
+                    kingdom_swaps: 3,
+                    project_swaps: 0,
+                    prefer: SwapPreference::Random,
+                })
+                .unwrap(),
+            ),
+            (
+                "Played",
+                serde_json::to_value(history::Played::now("Game".to_string(), setup)).unwrap(),
+            ),
+            (
+                "Draft",
+                serde_json::to_value(
+                    draft::Draft::new(no_young_witch(), 2, draft::DraftFormat::Snake).unwrap(),
+                )
+                .unwrap(),
+            ),
+            ("View", serde_json::to_value(session.view(0)).unwrap()),
+            (
+                "PlayerView",
+                serde_json::to_value(&session.view(0).players[0]).unwrap(),
+            ),
+            ("Decision", serde_json::to_value(session.pending()).unwrap()),
+            ("Score", serde_json::to_value(&scored[0]).unwrap()),
+            (
+                "Played",
+                serde_json::to_value(history::Played {
+                    scores: scored.clone(),
+                    ..history::Played::now("Game".to_string(), zebra_setup())
+                })
+                .unwrap(),
+            ),
+        ];
+
+        for (name, object) in objects {
+            let start = ts.find(&format!("export interface {} {{", name)).unwrap();
+            let interface = &ts[start..start + ts[start..].find('}').unwrap()];
+
+            for field in object.as_object().unwrap().keys() {
+                assert!(
+                    interface.contains(&format!("\n  {}:", field))
+                        || interface.contains(&format!("\n  {}?:", field)),
+                    "{} is missing {}",
+                    name,
+                    field
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn typescript_errors_cover_every_variant() {
+        let ts = wasm::typescript();
+        let errors = [
+            GenSetupError::CouldNotSatisfyProjectsFromExpansions,
+            GenSetupError::CouldNotSatisfyKingdomCards,
+            GenSetupError::CouldNotSatisfyBaneCard,
+            GenSetupError::CouldNotSatisfySecondZebra,
+            GenSetupError::IntersectingCardBansAndIncludes(vec![]),
+            GenSetupError::TooManyCardsIncluded,
+            GenSetupError::SlotNotInSetup(Slot::Bane),
+            GenSetupError::CouldNotSatisfySynergies,
+            GenSetupError::CouldNotSatisfyMaxSimilarity,
+            GenSetupError::CouldNotSatisfyPower,
+            GenSetupError::ImpossibleExpansionCount(Expansion::Base2),
+            GenSetupError::CouldNotSatisfyExpansionCounts,
+        ];
+
+        for error in errors {
+            let json = serde_json::to_string(&error).unwrap();
+            let tag = json
+                .trim_matches(|c| c == '{' || c == '"')
+                .split('"')
+                .next()
+                .unwrap();
+            assert!(ts.contains(&format!("\"{}\"", tag)) || ts.contains(&format!("{{ {}:", tag)));
+        }
+
+        let setup_errors = [
+            SetupError::KingdomCardCount(9),
+            SetupError::DuplicateCard(KC::Moat),
+            SetupError::MissingBane,
+            SetupError::UnneededBane(KC::Moat),
+            SetupError::BaneCost(KC::Moat),
+            SetupError::BaneCardNotInKingdom(KC::Moat),
+            SetupError::MissingSecondZebra,
+            SetupError::UnneededSecondZebra(KC::Moat),
+            SetupError::SecondZebraInKingdom(KC::Moat),
+            SetupError::TooManyProjects(3),
+        ];
+        let start = ts.find("export type SetupError =").unwrap();
+        let union = &ts[start..start + ts[start..].find("\n\n").unwrap()];
+
+        for error in setup_errors {
+            let json = serde_json::to_string(&error).unwrap();
+            let tag = json
+                .trim_matches(|c| c == '{' || c == '"')
+                .split('"')
+                .next()
+                .unwrap();
+            assert!(
+                union.contains(&format!("\"{}\"", tag)) || union.contains(&format!("{{ {}:", tag)),
+                "SetupError is missing {}",
+                tag
+            );
+        }
+
+        let decision = engine::Decision {
+            player: 0,
+            kind: engine::DecisionKind::Buy,
+            source: None,
+            options: vec![],
+            min: 0,
+            max: 1,
+        };
+        let engine_errors = [
+            engine::EngineError::UnsupportedCard(KC::Wharf),
+            engine::EngineError::UnsupportedSetup,
+            engine::EngineError::BadPlayerCount(1),
+            engine::EngineError::NotInSupply(KC::Wharf),
+            engine::EngineError::Suspended,
+            engine::EngineError::NotYourDecision(1),
+            engine::EngineError::InvalidChoice {
+                decision,
+                choice: vec![],
+            },
+            engine::EngineError::GameOver,
+        ];
+        let start = ts.find("export type EngineError =").unwrap();
+        let union = &ts[start..start + ts[start..].find("\n\n").unwrap()];
+
+        for error in engine_errors {
+            let json = serde_json::to_string(&error).unwrap();
+            let tag = json
+                .trim_matches(|c| c == '{' || c == '"')
+                .split('"')
+                .next()
+                .unwrap();
+            assert!(
+                union.contains(&format!("\"{}\"", tag)) || union.contains(&format!("{{ {}:", tag)),
+                "EngineError is missing {}",
+                tag
+            );
+        }
+    }
+
+    #[test]
+    fn api_errors_are_tagged_and_carry_a_message() {
+        let error = wasm::ApiError::from(GenSetupError::TooManyCardsIncluded);
+        let json = serde_json::to_value(&error).unwrap();
+
+        assert_eq!(json["kind"], "GenSetup");
+        assert_eq!(json["error"], "TooManyCardsIncluded");
+        assert_eq!(
+            json["message"],
+            pretty::gen_error(GenSetupError::TooManyCardsIncluded)
+        );
+    }
+
+    fn gen_expansion() -> Expansion {
+        let mut rng = rand::thread_rng();
+
+        Expansion::iter()
+            .collect::<Vec<Expansion>>()
+            .choose(&mut rng)
+            .unwrap()
+            .clone()
+    }
+
+    fn gen_kingdom_card() -> KC {
+        let mut rng = rand::thread_rng();
+
+        KC::iter()
+            .collect::<Vec<KC>>()
+            .choose(&mut rng)
+            .unwrap()
+            .clone()
+    }
+}
+
+/// A kingdom card
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Clone,
+    Eq,
+    Hash,
+    EnumString,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum KC {
+    ActingTroupe,
+    Adventurer,
+    Advisor,
+    Ambassador,
+    Artisan,
+    Baker,
+    Bandit,
+    Bank,
+    Baron,
+    Bazaar,
+    Bishop,
+    BorderGuard,
+    BorderVillage,
+    Bridge,
+    Bureaucrat,
+    Butcher,
+    Cache,
+    CandlestickMaker,
+    Caravan,
+    CargoShip,
+    Cartographer,
+    Cellar,
+    Chancellor,
+    Chapel,
+    City,
+    Conspirator,
+    Contraband,
+    CouncilRoom,
+    CountingHouse,
+    Courtier,
+    Courtyard,
+    Crossroads,
+    Cutpurse,
+    Develop,
+    Diplomat,
+    Doctor,
+    Ducat,
+    Duchess,
+    Duke,
+    Embargo,
+    Embassy,
+    Expand,
+    Experiment,
+    Explorer,
+    Fairgrounds,
+    FarmingVillage,
+    Farmland,
+    Feast,
+    Festival,
+    FishingVillage,
+    FlagBearer,
+    FoolsGold,
+    Forge,
+    FortuneTeller,
+    Gardens,
+    GhostShip,
+    Goons,
+    GrandMarket,
+    Haggler,
+    Hamlet,
+    Harbinger,
+    Harem,
+    Harvest,
+    Haven,
+    Herald,
+    Hideout,
+    Highway,
+    Hoard,
+    HornOfPlenty,
+    HorseTraders,
+    HuntingParty,
+    IllGottenGains,
+    Improve,
+    Inn,
+    Inventor,
+    Ironworks,
+    Island,
+    JackOfAllTrades,
+    Jester,
+    Journeyman,
+    KingsCourt,
+    Laboratory,
+    Lackeys,
+    Library,
+    Lighthouse,
+    Loan,
+    Lookout,
+    Lurker,
+    Mandarin,
+    Margrave,
+    Market,
+    Masquerade,
+    Masterpiece,
+    Menagerie,
+    Merchant,
+    MerchantGuild,
+    MerchantShip,
+    Militia,
+    Mill,
+    Mine,
+    MiningVillage,
+    Minion,
+    Mint,
+    Moat,
+    Moneylender,
+    Monument,
+    MountainVillage,
+    Mountebank,
+    NativeVillage,
+    Navigator,
+    NobleBrigand,
+    Nobles,
+    NomadCamp,
+    Oasis,
+    OldWitch,
+    Oracle,
+    Outpost,
+    Patrol,
+    Patron,
+    Pawn,
+    PearlDiver,
+    Peddler,
+    PirateShip,
+    Plaza,
+    Poacher,
+    Priest,
+    Quarry,
+    Rabble,
+    Recruiter,
+    Remake,
+    Remodel,
+    Replace,
+    Research,
+    RoyalSeal,
+    Salvager,
+    Scepter,
+    Scheme,
+    Scholar,
+    Sculptor,
+    SeaHag,
+    SecretPassage,
+    Seer,
+    Sentry,
+    ShantyTown,
+    SilkMerchant,
+    SilkRoad,
+    Smithy,
+    Smugglers,
+    Soothsayer,
+    SpiceMerchant,
+    Spices,
+    Spy,
+    Stables,
+    Steward,
+    Stonemason,
+    Swashbuckler,
+    Swindler,
+    Tactician,
+    Talisman,
+    Taxman,
+    Thief,
+    ThroneRoom,
+    Torturer,
+    Tournament,
+    TradeRoute,
+    Trader,
+    TradingPost,
+    TreasureMap,
+    Treasurer,
+    Treasury,
+    Tunnel,
+    Upgrade,
+    Vassal,
+    Vault,
+    Venture,
+    Village,
+    Villain,
+    Warehouse,
+    Watchtower,
+    Wharf,
+    WishingWell,
+    Witch,
+    Woodcutter,
+    WorkersVillage,
+    Workshop,
+    YoungWitch,
+}
+
+/// What is a card's base cost?
+pub trait BaseCost {
+    /// What is a card's base cost?
+    fn base_cost(&self) -> u8;
+}
+
+impl BaseCost for KC {
+    fn base_cost(&self) -> u8 {
+        match self {
+            KC::Embargo => 2,
+            KC::Haven => 2,
+            KC::Lighthouse => 2,
+            KC::NativeVillage => 2,
+            KC::PearlDiver => 2,
+            KC::Ambassador => 3,
+            KC::FishingVillage => 3,
+            KC::Lookout => 3,
+            KC::Smugglers => 3,
+            KC::Warehouse => 3,
+            KC::Caravan => 4,
+            KC::Cutpurse => 4,
+            KC::Island => 4,
+            KC::Navigator => 4,
+            KC::PirateShip => 4,
+            KC::Salvager => 4,
+            KC::SeaHag => 4,
+            KC::TreasureMap => 4,
+            KC::Bazaar => 5,
+            KC::Explorer => 5,
+            KC::GhostShip => 5,
+            KC::MerchantShip => 5,
+            KC::Outpost => 5,
+            KC::Tactician => 5,
+            KC::Treasury => 5,
+            KC::Wharf => 5,
+            KC::Courtyard => 2,
+            KC::Lurker => 2,
+            KC::Pawn => 2,
+            KC::Masquerade => 3,
+            KC::ShantyTown => 3,
+            KC::Steward => 3,
+            KC::Swindler => 3,
+            KC::WishingWell => 3,
+            KC::Baron => 4,
+            KC::Bridge => 4,
+            KC::Conspirator => 4,
+            KC::Diplomat => 4,
+            KC::Ironworks => 4,
+            KC::Mill => 4,
+            KC::MiningVillage => 4,
+            KC::SecretPassage => 4,
+            KC::Courtier => 5,
+            KC::Duke => 5,
+            KC::Minion => 5,
+            KC::Patrol => 5,
+            KC::Replace => 5,
+            KC::Torturer => 5,
+            KC::TradingPost => 5,
+            KC::Upgrade => 5,
+            KC::Harem => 6,
+            KC::Nobles => 6,
+            KC::Harbinger => 3,
+            KC::Merchant => 3,
+            KC::Vassal => 3,
+            KC::Poacher => 4,
+            KC::Sentry => 5,
+            KC::Artisan => 6,
+            KC::ActingTroupe => 3,
+            KC::Adventurer => 6,
+            KC::Advisor => 4,
+            KC::Baker => 5,
+            KC::Bandit => 5,
+            KC::BorderGuard => 2,
+            KC::Bureaucrat => 4,
+            KC::Butcher => 5,
+            KC::CandlestickMaker => 2,
+            KC::CargoShip => 3,
+            KC::Cellar => 2,
+            KC::Chancellor => 3,
+            KC::Chapel => 2,
+            KC::CouncilRoom => 5,
+            KC::Doctor => 3,
+            KC::Ducat => 2,
+            KC::Experiment => 3,
+            KC::Fairgrounds => 6,
+            KC::FarmingVillage => 4,
+            KC::Feast => 4,
+            KC::Festival => 5,
+            KC::FlagBearer => 4,
+            KC::FortuneTeller => 3,
+            KC::Gardens => 4,
+            KC::Hamlet => 2,
+            KC::Harvest => 5,
+            KC::Herald => 4,
+            KC::Hideout => 4,
+            KC::HornOfPlenty => 5,
+            KC::HorseTraders => 4,
+            KC::HuntingParty => 5,
+            KC::Improve => 3,
+            KC::Inventor => 4,
+            KC::Jester => 5,
+            KC::Journeyman => 5,
+            KC::Laboratory => 5,
+            KC::Lackeys => 2,
+            KC::Library => 5,
+            KC::Market => 5,
+            KC::Masterpiece => 3,
+            KC::Menagerie => 3,
+            KC::MerchantGuild => 5,
+            KC::Militia => 4,
+            KC::Mine => 5,
+            KC::Moat => 2,
+            KC::Moneylender => 4,
+            KC::MountainVillage => 4,
+            KC::OldWitch => 5,
+            KC::Patron => 4,
+            KC::Plaza => 4,
+            KC::Priest => 4,
+            KC::Recruiter => 5,
+            KC::Remake => 4,
+            KC::Remodel => 4,
+            KC::Research => 4,
+            KC::Scepter => 5,
+            KC::Scholar => 5,
+            KC::Sculptor => 5,
+            KC::Seer => 5,
+            KC::SilkMerchant => 4,
+            KC::Smithy => 4,
+            KC::Soothsayer => 5,
+            KC::Spices => 5,
+            KC::Spy => 4,
+            KC::Stonemason => 2,
+            KC::Swashbuckler => 5,
+            KC::Taxman => 4,
+            KC::Thief => 4,
+            KC::ThroneRoom => 4,
+            KC::Tournament => 4,
+            KC::Treasurer => 5,
+            KC::Village => 3,
+            KC::Villain => 5,
+            KC::Witch => 5,
+            KC::Woodcutter => 3,
+            KC::Workshop => 3,
+            KC::YoungWitch => 4,
+            KC::Loan => 3,
+            KC::TradeRoute => 3,
+            KC::Watchtower => 3,
+            KC::Bishop => 4,
+            KC::Monument => 4,
+            KC::Quarry => 4,
+            KC::Talisman => 4,
+            KC::WorkersVillage => 4,
+            KC::City => 5,
+            KC::Contraband => 5,
+            KC::CountingHouse => 5,
+            KC::Mint => 5,
+            KC::Mountebank => 5,
+            KC::Rabble => 5,
+            KC::RoyalSeal => 5,
+            KC::Vault => 5,
+            KC::Venture => 5,
+            KC::Goons => 6,
+            KC::GrandMarket => 6,
+            KC::Hoard => 6,
+            KC::Bank => 7,
+            KC::Expand => 7,
+            KC::Forge => 7,
+            KC::KingsCourt => 7,
+            KC::Peddler => 8,
+
+            KC::Crossroads => 2,
+            KC::Duchess => 2,
+            KC::FoolsGold => 2,
+            KC::Develop => 3,
+            KC::Oasis => 3,
+            KC::Oracle => 3,
+            KC::Scheme => 3,
+            KC::Tunnel => 3,
+            KC::JackOfAllTrades => 4,
+            KC::NobleBrigand => 4,
+            KC::NomadCamp => 4,
+            KC::SilkRoad => 4,
+            KC::SpiceMerchant => 4,
+            KC::Trader => 4,
+            KC::Cache => 5,
+            KC::Cartographer => 5,
+            KC::Embassy => 5,
+            KC::Haggler => 5,
+            KC::Highway => 5,
+            KC::IllGottenGains => 5,
+            KC::Inn => 5,
+            KC::Mandarin => 5,
+            KC::Margrave => 5,
+            KC::Stables => 5,
+            KC::BorderVillage => 6,
+            KC::Farmland => 6,
+        }
+    }
+}
+
+/// Supported expansions
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Eq,
+    Hash,
+    std::clone::Clone,
+    EnumString,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum Expansion {
+    Base1,
+    Base2,
+    Renaissance,
+    Guilds,
+    Cornucopia,
+    Intrigue2,
+    Seaside,
+    Prosperity,
+    Hinterlands,
+}
+
+/// To what expansions does a card belong?
+pub trait Expansions {
+    /// To what expansions does a card belong?
+    fn expansions(&self) -> Vec<Expansion>;
+}
+
+impl Expansions for KC {
+    fn expansions(&self) -> std::vec::Vec<Expansion> {
+        match self {
+            KC::Courtyard
+            | KC::Lurker
+            | KC::Pawn
+            | KC::Masquerade
+            | KC::ShantyTown
+            | KC::Steward
+            | KC::Swindler
+            | KC::WishingWell
+            | KC::Baron
+            | KC::Bridge
+            | KC::Conspirator
+            | KC::Diplomat
+            | KC::Ironworks
+            | KC::Mill
+            | KC::MiningVillage
+            | KC::SecretPassage
+            | KC::Courtier
+            | KC::Duke
+            | KC::Minion
+            | KC::Patrol
+            | KC::Replace
+            | KC::Torturer
+            | KC::TradingPost
+            | KC::Upgrade
+            | KC::Harem
+            | KC::Nobles => vec![Expansion::Intrigue2],
+
+            KC::Harbinger
+            | KC::Vassal
+            | KC::Sentry
+            | KC::Poacher
+            | KC::Merchant
+            | KC::Artisan
+            | KC::Bandit => {
+                vec![Expansion::Base2]
+            }
+
+            KC::Cellar
+            | KC::Chapel
+            | KC::Moat
+            | KC::Village
+            | KC::Workshop
+            | KC::Bureaucrat
+            | KC::Gardens
+            | KC::Militia
+            | KC::Moneylender
+            | KC::Remodel
+            | KC::Smithy
+            | KC::ThroneRoom
+            | KC::CouncilRoom
+            | KC::Festival
+            | KC::Laboratory
+            | KC::Library
+            | KC::Market
+            | KC::Mine
+            | KC::Witch => vec![Expansion::Base2, Expansion::Base1],
+
+            KC::Chancellor | KC::Woodcutter | KC::Feast | KC::Spy | KC::Thief | KC::Adventurer => {
+                vec![Expansion::Base1]
+            }
+
+            KC::BorderGuard
+            | KC::Ducat
+            | KC::Lackeys
+            | KC::ActingTroupe
+            | KC::CargoShip
+            | KC::Experiment
+            | KC::Improve
+            | KC::FlagBearer
+            | KC::Hideout
+            | KC::Inventor
+            | KC::MountainVillage
+            | KC::Patron
+            | KC::Priest
+            | KC::Research
+            | KC::SilkMerchant
+            | KC::OldWitch
+            | KC::Recruiter
+            | KC::Scepter
+            | KC::Scholar
+            | KC::Sculptor
+            | KC::Seer
+            | KC::Spices
+            | KC::Swashbuckler
+            | KC::Treasurer
+            | KC::Villain => vec![Expansion::Renaissance],
+
+            KC::CandlestickMaker
+            | KC::Stonemason
+            | KC::Doctor
+            | KC::Masterpiece
+            | KC::Advisor
+            | KC::Plaza
+            | KC::Taxman
+            | KC::Herald
+            | KC::Baker
+            | KC::Butcher
+            | KC::Journeyman
+            | KC::MerchantGuild
+            | KC::Soothsayer => vec![Expansion::Guilds],
+
+            KC::Hamlet
+            | KC::FortuneTeller
+            | KC::Menagerie
+            | KC::FarmingVillage
+            | KC::HorseTraders
+            | KC::Remake
+            | KC::Tournament
+            | KC::YoungWitch
+            | KC::Harvest
+            | KC::HornOfPlenty
+            | KC::HuntingParty
+            | KC::Jester
+            | KC::Fairgrounds => vec![Expansion::Cornucopia],
+
+            KC::Embargo
+            | KC::Haven
+            | KC::Lighthouse
+            | KC::NativeVillage
+            | KC::PearlDiver
+            | KC::Ambassador
+            | KC::FishingVillage
+            | KC::Lookout
+            | KC::Smugglers
+            | KC::Warehouse
+            | KC::Bazaar
+            | KC::Explorer
+            | KC::GhostShip
+            | KC::MerchantShip
+            | KC::Outpost
+            | KC::Tactician
+            | KC::Treasury
+            | KC::Wharf
+            | KC::Caravan
+            | KC::Cutpurse
+            | KC::Island
+            | KC::Navigator
+            | KC::PirateShip
+            | KC::Salvager
+            | KC::SeaHag
+            | KC::TreasureMap => vec![Expansion::Seaside],
+
+            KC::Loan
+            | KC::TradeRoute
+            | KC::Watchtower
+            | KC::Bishop
+            | KC::Monument
+            | KC::Quarry
+            | KC::Talisman
+            | KC::WorkersVillage
+            | KC::City
+            | KC::Contraband
+            | KC::CountingHouse
+            | KC::Mint
+            | KC::Mountebank
+            | KC::Rabble
+            | KC::RoyalSeal
+            | KC::Vault
+            | KC::Venture
+            | KC::Goons
+            | KC::GrandMarket
+            | KC::Hoard
+            | KC::Bank
+            | KC::Expand
+            | KC::Forge
+            | KC::KingsCourt
+            | KC::Peddler => vec![Expansion::Prosperity],
+
+            KC::Crossroads
+            | KC::Duchess
+            | KC::FoolsGold
+            | KC::Develop
+            | KC::Oasis
+            | KC::Oracle
+            | KC::Scheme
+            | KC::Tunnel
+            | KC::JackOfAllTrades
+            | KC::NobleBrigand
+            | KC::NomadCamp
+            | KC::SilkRoad
+            | KC::SpiceMerchant
+            | KC::Trader
+            | KC::Cache
+            | KC::Cartographer
+            | KC::Embassy
+            | KC::Haggler
+            | KC::Highway
+            | KC::IllGottenGains
+            | KC::Inn
+            | KC::Mandarin
+            | KC::Margrave
+            | KC::Stables
+            | KC::BorderVillage
+            | KC::Farmland => vec![Expansion::Hinterlands],
+        }
+    }
+}
+
+/// My custom "Bane" cards
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Eq,
+    Hash,
+    std::clone::Clone,
+    EnumString,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum BaneCard {
+    Bargain,
+    BuyAndBuy,
+    CoverOfDarkness,
+    CursedHeirloom,
+    Exchange,
+    Flank,
+    FoolsGold,
+    Fortification,
+    Frontier,
+    Gambler,
+    MagicShield,
+    Opportune,
+    PlagueCart,
+    Rebate,
+    Sacrifice,
+    SecretPlans,
+    SilverMine,
+    Throne,
+    TreasuryKey,
+    Tunnel,
+    Vault,
+    Zebra,
+}
+
+/// Card's type -- how it functions
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Eq,
+    Hash,
+    Clone,
+    PartialOrd,
+    Ord,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+pub enum CardType {
+    Action,
+    Attack,
+    Reaction,
+    Victory,
+    Treasure,
+    Duration,
+}
+
+/// What types does a card have?
+pub trait CardTypes {
+    /// What types does a card have?
+    fn card_types(&self) -> Vec<CardType>;
+}
+
+impl CardTypes for KC {
+    fn card_types(&self) -> std::vec::Vec<CardType> {
+        match self {
+            KC::Embargo => vec![CardType::Action],
+            KC::Haven => vec![CardType::Action, CardType::Duration],
+            KC::Lighthouse => vec![CardType::Action, CardType::Duration],
+            KC::NativeVillage => vec![CardType::Action],
+            KC::PearlDiver => vec![CardType::Action],
             KC::Ambassador => vec![CardType::Action, CardType::Attack],
             KC::FishingVillage => vec![CardType::Action, CardType::Duration],
             KC::Lookout => vec![CardType::Action],
@@ -1249,960 +4084,9571 @@ impl CardTypes for KC {
             KC::KingsCourt => vec![CardType::Action],
             KC::Peddler => vec![CardType::Action],
 
-            KC::Crossroads => vec![CardType::Action],
-            KC::Duchess => vec![CardType::Action],
-            KC::FoolsGold => vec![CardType::Treasure, CardType::Reaction],
-            KC::Develop => vec![CardType::Action],
-            KC::Oasis => vec![CardType::Action],
-            KC::Oracle => vec![CardType::Action, CardType::Attack],
-            KC::Scheme => vec![CardType::Action],
-            KC::Tunnel => vec![CardType::Victory, CardType::Reaction],
-            KC::JackOfAllTrades => vec![CardType::Action],
-            KC::NobleBrigand => vec![CardType::Action, CardType::Attack],
-            KC::NomadCamp => vec![CardType::Action],
-            KC::SilkRoad => vec![CardType::Victory],
-            KC::SpiceMerchant => vec![CardType::Action],
-            KC::Trader => vec![CardType::Action, CardType::Reaction],
-            KC::Cache => vec![CardType::Treasure],
-            KC::Cartographer => vec![CardType::Action],
-            KC::Embassy => vec![CardType::Action],
-            KC::Haggler => vec![CardType::Action],
-            KC::Highway => vec![CardType::Action],
-            KC::IllGottenGains => vec![CardType::Treasure],
-            KC::Inn => vec![CardType::Action],
-            KC::Mandarin => vec![CardType::Action],
-            KC::Margrave => vec![CardType::Action, CardType::Attack],
-            KC::Stables => vec![CardType::Action],
-            KC::BorderVillage => vec![CardType::Action],
-            KC::Farmland => vec![CardType::Victory],
+            KC::Crossroads => vec![CardType::Action],
+            KC::Duchess => vec![CardType::Action],
+            KC::FoolsGold => vec![CardType::Treasure, CardType::Reaction],
+            KC::Develop => vec![CardType::Action],
+            KC::Oasis => vec![CardType::Action],
+            KC::Oracle => vec![CardType::Action, CardType::Attack],
+            KC::Scheme => vec![CardType::Action],
+            KC::Tunnel => vec![CardType::Victory, CardType::Reaction],
+            KC::JackOfAllTrades => vec![CardType::Action],
+            KC::NobleBrigand => vec![CardType::Action, CardType::Attack],
+            KC::NomadCamp => vec![CardType::Action],
+            KC::SilkRoad => vec![CardType::Victory],
+            KC::SpiceMerchant => vec![CardType::Action],
+            KC::Trader => vec![CardType::Action, CardType::Reaction],
+            KC::Cache => vec![CardType::Treasure],
+            KC::Cartographer => vec![CardType::Action],
+            KC::Embassy => vec![CardType::Action],
+            KC::Haggler => vec![CardType::Action],
+            KC::Highway => vec![CardType::Action],
+            KC::IllGottenGains => vec![CardType::Treasure],
+            KC::Inn => vec![CardType::Action],
+            KC::Mandarin => vec![CardType::Action],
+            KC::Margrave => vec![CardType::Action, CardType::Attack],
+            KC::Stables => vec![CardType::Action],
+            KC::BorderVillage => vec![CardType::Action],
+            KC::Farmland => vec![CardType::Victory],
+        }
+    }
+}
+
+/// What a card does for a deck, roughly. Used to size up a kingdom (see
+/// `analysis`), so it leans on what the card is usually bought for.
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Eq,
+    Hash,
+    Clone,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum Role {
+    /// +2 Actions or more (or Villagers)
+    Village,
+
+    /// +2 Cards or more, or draws up to a hand size
+    Draw,
+
+    PlusBuy,
+
+    /// Trashes cards from the deck
+    Trasher,
+
+    /// Gains cards without buying them
+    Gainer,
+
+    /// +$2 or more, or a strong treasure
+    Payload,
+
+    /// Gives other players Curses or other junk
+    Junker,
+
+    /// Makes other players discard down or otherwise shrinks their hands
+    HandsizeAttack,
+
+    /// Blocks attacks or softens them
+    Defense,
+
+    /// Victory points from something other than the usual Victory cards
+    AltVp,
+
+    /// Plays an action more than once
+    Throne,
+
+    /// A terminal that turns a deck of treasures into a strong one
+    BigMoney,
+}
+
+/// What is a card good for?
+pub trait CardRoles {
+    /// What is a card good for?
+    fn roles(&self) -> Vec<Role>;
+}
+
+impl CardRoles for KC {
+    fn roles(&self) -> Vec<Role> {
+        match self {
+            KC::ActingTroupe => vec![Role::Village],
+            KC::Adventurer => vec![Role::Payload],
+            KC::Advisor => vec![Role::Draw],
+            KC::Ambassador => vec![Role::Trasher, Role::Junker],
+            KC::Artisan => vec![Role::Gainer],
+            KC::Baker => vec![Role::Payload],
+            KC::Bandit => vec![Role::Gainer],
+            KC::Bank => vec![Role::Payload],
+            KC::Baron => vec![Role::PlusBuy, Role::Payload],
+            KC::Bazaar => vec![Role::Village],
+            KC::Bishop => vec![Role::Trasher, Role::AltVp],
+            KC::BorderGuard => vec![],
+            KC::BorderVillage => vec![Role::Village, Role::Gainer],
+            KC::Bridge => vec![Role::PlusBuy, Role::Payload],
+            KC::Bureaucrat => vec![Role::Gainer],
+            KC::Butcher => vec![Role::Trasher, Role::Gainer],
+            KC::Cache => vec![Role::Payload],
+            KC::CandlestickMaker => vec![Role::PlusBuy],
+            KC::Caravan => vec![],
+            KC::CargoShip => vec![Role::Payload],
+            KC::Cartographer => vec![],
+            KC::Cellar => vec![],
+            KC::Chancellor => vec![Role::Payload],
+            KC::Chapel => vec![Role::Trasher],
+            KC::City => vec![Role::Village],
+            KC::Conspirator => vec![Role::Payload],
+            KC::Contraband => vec![Role::PlusBuy, Role::Payload],
+            KC::CouncilRoom => vec![Role::Draw, Role::PlusBuy, Role::BigMoney],
+            KC::CountingHouse => vec![Role::Payload],
+            KC::Courtier => vec![Role::Payload, Role::Gainer],
+            KC::Courtyard => vec![Role::Draw, Role::BigMoney],
+            KC::Crossroads => vec![Role::Village, Role::Draw],
+            KC::Cutpurse => vec![Role::Payload],
+            KC::Develop => vec![Role::Trasher, Role::Gainer],
+            KC::Diplomat => vec![Role::Village, Role::Draw, Role::Defense],
+            KC::Doctor => vec![Role::Trasher],
+            KC::Ducat => vec![Role::PlusBuy, Role::Trasher],
+            KC::Duchess => vec![Role::Payload],
+            KC::Duke => vec![Role::AltVp],
+            KC::Embargo => vec![Role::Payload],
+            KC::Embassy => vec![Role::Draw, Role::BigMoney],
+            KC::Expand => vec![Role::Trasher, Role::Gainer],
+            KC::Experiment => vec![Role::Draw],
+            KC::Explorer => vec![Role::Gainer],
+            KC::Fairgrounds => vec![Role::AltVp],
+            KC::FarmingVillage => vec![Role::Village],
+            KC::Farmland => vec![Role::Gainer, Role::AltVp],
+            KC::Feast => vec![Role::Gainer],
+            KC::Festival => vec![Role::Village, Role::PlusBuy, Role::Payload],
+            KC::FishingVillage => vec![Role::Village, Role::Payload],
+            KC::FlagBearer => vec![Role::Payload],
+            KC::FoolsGold => vec![Role::Payload],
+            KC::Forge => vec![Role::Trasher, Role::Gainer],
+            KC::FortuneTeller => vec![Role::Payload],
+            KC::Gardens => vec![Role::AltVp],
+            KC::GhostShip => vec![Role::Draw, Role::HandsizeAttack, Role::BigMoney],
+            KC::Goons => vec![
+                Role::PlusBuy,
+                Role::Payload,
+                Role::HandsizeAttack,
+                Role::AltVp,
+            ],
+            KC::GrandMarket => vec![Role::PlusBuy, Role::Payload],
+            KC::Haggler => vec![Role::Gainer, Role::Payload],
+            KC::Hamlet => vec![Role::Village, Role::PlusBuy],
+            KC::Harbinger => vec![],
+            KC::Harem => vec![Role::Payload, Role::AltVp],
+            KC::Harvest => vec![Role::Payload],
+            KC::Haven => vec![],
+            KC::Herald => vec![],
+            KC::Hideout => vec![Role::Village, Role::Trasher],
+            KC::Highway => vec![Role::Payload],
+            KC::Hoard => vec![Role::Gainer, Role::Payload],
+            KC::HornOfPlenty => vec![Role::Gainer],
+            KC::HorseTraders => vec![Role::PlusBuy, Role::Payload, Role::Defense],
+            KC::HuntingParty => vec![Role::Draw],
+            KC::IllGottenGains => vec![Role::Payload, Role::Junker],
+            KC::Improve => vec![Role::Trasher, Role::Gainer, Role::Payload],
+            KC::Inn => vec![Role::Village],
+            KC::Inventor => vec![Role::Gainer],
+            KC::Ironworks => vec![Role::Gainer],
+            KC::Island => vec![Role::AltVp],
+            KC::JackOfAllTrades => vec![Role::Trasher, Role::Gainer, Role::BigMoney],
+            KC::Jester => vec![Role::Gainer, Role::Payload, Role::Junker],
+            KC::Journeyman => vec![Role::Draw, Role::BigMoney],
+            KC::KingsCourt => vec![Role::Throne],
+            KC::Laboratory => vec![Role::Draw],
+            KC::Lackeys => vec![Role::Village, Role::Draw],
+            KC::Library => vec![Role::Draw, Role::Defense, Role::BigMoney],
+            KC::Lighthouse => vec![Role::Defense],
+            KC::Loan => vec![Role::Trasher],
+            KC::Lookout => vec![Role::Trasher],
+            KC::Lurker => vec![Role::Gainer],
+            KC::Mandarin => vec![Role::Payload],
+            KC::Margrave => vec![
+                Role::Draw,
+                Role::PlusBuy,
+                Role::HandsizeAttack,
+                Role::BigMoney,
+            ],
+            KC::Market => vec![Role::PlusBuy],
+            KC::Masquerade => vec![Role::Draw, Role::Trasher, Role::BigMoney],
+            KC::Masterpiece => vec![Role::Gainer],
+            KC::Menagerie => vec![Role::Draw],
+            KC::Merchant => vec![],
+            KC::MerchantGuild => vec![Role::PlusBuy, Role::Payload],
+            KC::MerchantShip => vec![Role::Payload],
+            KC::Militia => vec![Role::Payload, Role::HandsizeAttack],
+            KC::Mill => vec![Role::Payload, Role::AltVp],
+            KC::Mine => vec![Role::Payload],
+            KC::MiningVillage => vec![Role::Village, Role::Payload],
+            KC::Minion => vec![Role::Payload, Role::HandsizeAttack],
+            KC::Mint => vec![Role::Trasher, Role::Gainer],
+            KC::Moat => vec![Role::Draw, Role::Defense],
+            KC::Moneylender => vec![Role::Trasher, Role::Payload],
+            KC::Monument => vec![Role::Payload, Role::AltVp, Role::BigMoney],
+            KC::MountainVillage => vec![Role::Village],
+            KC::Mountebank => vec![Role::Payload, Role::Junker, Role::BigMoney],
+            KC::NativeVillage => vec![Role::Village],
+            KC::Navigator => vec![Role::Payload],
+            KC::NobleBrigand => vec![Role::Payload],
+            KC::Nobles => vec![Role::Village, Role::Draw, Role::AltVp],
+            KC::NomadCamp => vec![Role::PlusBuy, Role::Payload],
+            KC::Oasis => vec![],
+            KC::OldWitch => vec![Role::Draw, Role::Junker, Role::BigMoney],
+            KC::Oracle => vec![Role::Draw],
+            KC::Outpost => vec![],
+            KC::Patrol => vec![Role::Draw],
+            KC::Patron => vec![Role::Payload],
+            KC::Pawn => vec![],
+            KC::PearlDiver => vec![],
+            KC::Peddler => vec![],
+            KC::PirateShip => vec![Role::Payload],
+            KC::Plaza => vec![Role::Village, Role::Payload],
+            KC::Poacher => vec![Role::Payload],
+            KC::Priest => vec![Role::Trasher, Role::Payload],
+            KC::Quarry => vec![Role::Payload],
+            KC::Rabble => vec![Role::Draw],
+            KC::Recruiter => vec![Role::Village, Role::Draw, Role::Trasher],
+            KC::Remake => vec![Role::Trasher, Role::Gainer],
+            KC::Remodel => vec![Role::Trasher, Role::Gainer],
+            KC::Replace => vec![Role::Trasher, Role::Gainer, Role::Junker],
+            KC::Research => vec![Role::Draw, Role::Trasher],
+            KC::RoyalSeal => vec![Role::Payload],
+            KC::Salvager => vec![Role::PlusBuy, Role::Trasher, Role::Payload],
+            KC::Scepter => vec![Role::Payload, Role::Throne],
+            KC::Scheme => vec![],
+            KC::Scholar => vec![Role::Draw],
+            KC::Sculptor => vec![Role::Village, Role::Gainer],
+            KC::SeaHag => vec![Role::Junker],
+            KC::SecretPassage => vec![Role::Draw],
+            KC::Seer => vec![Role::Draw],
+            KC::Sentry => vec![Role::Trasher],
+            KC::ShantyTown => vec![Role::Village, Role::Draw],
+            KC::SilkMerchant => vec![Role::PlusBuy],
+            KC::SilkRoad => vec![Role::AltVp],
+            KC::Smithy => vec![Role::Draw, Role::BigMoney],
+            KC::Smugglers => vec![Role::Gainer],
+            KC::Soothsayer => vec![Role::Gainer, Role::Junker],
+            KC::SpiceMerchant => vec![Role::PlusBuy, Role::Trasher],
+            KC::Spices => vec![Role::PlusBuy, Role::Payload],
+            KC::Spy => vec![],
+            KC::Stables => vec![Role::Draw],
+            KC::Steward => vec![Role::Draw, Role::Trasher, Role::Payload],
+            KC::Stonemason => vec![Role::Trasher, Role::Gainer],
+            KC::Swashbuckler => vec![Role::Draw, Role::Payload],
+            KC::Swindler => vec![Role::Payload],
+            KC::Tactician => vec![Role::Draw, Role::PlusBuy, Role::BigMoney],
+            KC::Talisman => vec![Role::Gainer, Role::Payload],
+            KC::Taxman => vec![Role::Trasher, Role::Gainer],
+            KC::Thief => vec![],
+            KC::ThroneRoom => vec![Role::Throne],
+            KC::Torturer => vec![
+                Role::Draw,
+                Role::Junker,
+                Role::HandsizeAttack,
+                Role::BigMoney,
+            ],
+            KC::Tournament => vec![Role::Gainer, Role::Payload],
+            KC::TradeRoute => vec![Role::PlusBuy, Role::Trasher],
+            KC::Trader => vec![Role::Trasher, Role::Defense],
+            KC::TradingPost => vec![Role::Trasher, Role::Gainer],
+            KC::TreasureMap => vec![Role::Gainer],
+            KC::Treasurer => vec![Role::Gainer, Role::Payload],
+            KC::Treasury => vec![Role::Payload],
+            KC::Tunnel => vec![Role::Gainer],
+            KC::Upgrade => vec![Role::Trasher, Role::Gainer],
+            KC::Vassal => vec![Role::Payload],
+            KC::Vault => vec![Role::Draw, Role::Payload, Role::BigMoney],
+            KC::Venture => vec![Role::Payload],
+            KC::Village => vec![Role::Village],
+            KC::Villain => vec![Role::Payload, Role::HandsizeAttack],
+            KC::Warehouse => vec![],
+            KC::Watchtower => vec![Role::Draw, Role::Trasher, Role::Defense],
+            KC::Wharf => vec![Role::Draw, Role::PlusBuy, Role::BigMoney],
+            KC::WishingWell => vec![],
+            KC::Witch => vec![Role::Draw, Role::Junker, Role::BigMoney],
+            KC::Woodcutter => vec![Role::PlusBuy, Role::Payload],
+            KC::WorkersVillage => vec![Role::Village, Role::PlusBuy],
+            KC::Workshop => vec![Role::Gainer],
+            KC::YoungWitch => vec![Role::Draw, Role::Junker],
+        }
+    }
+}
+
+/// A project card
+#[derive(
+    EnumIter,
+    Debug,
+    PartialEq,
+    EnumCountMacro,
+    Eq,
+    Hash,
+    Clone,
+    Ord,
+    PartialOrd,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+pub enum Project {
+    Academy,
+    Barracks,
+    Canal,
+    Capitalism,
+    Cathedral,
+    Citadel,
+    CityGate,
+    CropRotation,
+    Exploration,
+    Fair,
+    Fleet,
+    Guildhall,
+    Innovation,
+    Pageant,
+    Piazza,
+    RoadNetwork,
+    Sewers,
+    Silos,
+    SinisterPlot,
+    StarChart,
+}
+
+impl Expansions for Project {
+    fn expansions(&self) -> Vec<Expansion> {
+        vec![Expansion::Renaissance]
+    }
+}
+
+impl BaseCost for Project {
+    fn base_cost(&self) -> u8 {
+        match self {
+            Project::Cathedral => 3,
+            Project::CityGate => 3,
+            Project::Pageant => 3,
+            Project::Sewers => 3,
+            Project::StarChart => 3,
+            Project::Exploration => 4,
+            Project::Fair => 4,
+            Project::Silos => 4,
+            Project::SinisterPlot => 4,
+            Project::Academy => 5,
+            Project::Capitalism => 5,
+            Project::Fleet => 5,
+            Project::Guildhall => 5,
+            Project::Piazza => 5,
+            Project::RoadNetwork => 5,
+            Project::Barracks => 6,
+            Project::CropRotation => 6,
+            Project::Innovation => 6,
+            Project::Canal => 7,
+            Project::Citadel => 8,
+        }
+    }
+}
+
+impl CardRoles for Project {
+    fn roles(&self) -> Vec<Role> {
+        match self {
+            Project::Academy => vec![Role::Village],
+            Project::Barracks => vec![Role::Village],
+            Project::Canal => vec![Role::Payload],
+            Project::Capitalism => vec![Role::Payload],
+            Project::Cathedral => vec![Role::Trasher],
+            Project::Citadel => vec![Role::Throne],
+            Project::CityGate => vec![],
+            Project::CropRotation => vec![Role::Draw],
+            Project::Exploration => vec![Role::Payload],
+            Project::Fair => vec![Role::PlusBuy],
+            Project::Fleet => vec![],
+            Project::Guildhall => vec![Role::Payload],
+            Project::Innovation => vec![Role::Gainer],
+            Project::Pageant => vec![Role::Payload],
+            Project::Piazza => vec![Role::Village],
+            Project::RoadNetwork => vec![Role::Draw],
+            Project::Sewers => vec![Role::Trasher],
+            Project::Silos => vec![],
+            Project::SinisterPlot => vec![Role::Draw],
+            Project::StarChart => vec![],
+        }
+    }
+}
+
+/// A game's setup
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Setup {
+    pub kingdom_cards: Vec<KC>,
+    pub bane_card: Option<KC>,
+    pub project_cards: Vec<Project>,
+    pub bane_cards: HashMap<KC, BaneCard>,
+    pub second_zebra: Option<KC>,
+
+    /// The setup code (see `pretty::format_setup`) of the kingdom this one
+    /// was evolved from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+impl Setup {
+    pub fn new(
+        kingdom_cards: Vec<KC>,
+        bane_card: Option<KC>,
+        project_cards: Vec<Project>,
+        bane_cards: HashMap<KC, BaneCard>,
+        second_zebra: Option<KC>,
+    ) -> Self {
+        Self {
+            kingdom_cards,
+            bane_card,
+            project_cards,
+            bane_cards,
+            second_zebra,
+            parent: None,
+        }
+    }
+
+    pub fn bane(bane: KC, other_kingdom: Vec<KC>) -> Self {
+        Self {
+            kingdom_cards: other_kingdom,
+            bane_card: Some(bane),
+            project_cards: vec![],
+            bane_cards: HashMap::new(),
+            second_zebra: None,
+            parent: None,
+        }
+    }
+
+    pub fn cards(&self) -> Vec<KC> {
+        let mut results = self.kingdom_cards.clone();
+        for bane in &self.bane_card {
+            results.push(bane.clone());
+        }
+        results
+    }
+
+    /// Every rule the setup breaks, none if it's fine
+    ///
+    ///```
+    ///use dominion::{Setup, SetupError, KC};
+    ///let setup = Setup::bane(KC::Smithy, vec![KC::YoungWitch]);
+    ///assert!(setup.validate().contains(&SetupError::BaneCost(KC::Smithy)));
+    ///assert!(setup.validate().contains(&SetupError::KingdomCardCount(1)));
+    ///```
+    pub fn validate(&self) -> Vec<SetupError> {
+        let mut errors = vec![];
+
+        if self.kingdom_cards.len() != 10 {
+            errors.push(SetupError::KingdomCardCount(self.kingdom_cards.len()));
+        }
+
+        // The bane is an eleventh pile, so it can't be a kingdom card too
+        let mut seen = HashSet::new();
+        let mut duplicates = HashSet::new();
+        for card in self.cards() {
+            if !seen.insert(card.clone()) && duplicates.insert(card.clone()) {
+                errors.push(SetupError::DuplicateCard(card));
+            }
+        }
+
+        let young_witch = self.kingdom_cards.contains(&KC::YoungWitch);
+        match &self.bane_card {
+            None if young_witch => errors.push(SetupError::MissingBane),
+            Some(bane) if !young_witch => errors.push(SetupError::UnneededBane(bane.clone())),
+            _ => {}
+        }
+
+        if let Some(bane) = &self.bane_card {
+            if !is_bane_cost(bane) {
+                errors.push(SetupError::BaneCost(bane.clone()));
+            }
+        }
+
+        let mut banes: Vec<&KC> = self.bane_cards.keys().collect();
+        banes.sort();
+        for card in banes {
+            if !self.kingdom_cards.contains(card) {
+                errors.push(SetupError::BaneCardNotInKingdom(card.clone()));
+            }
+        }
+
+        let zebra = self
+            .bane_cards
+            .values()
+            .any(|bane| *bane == BaneCard::Zebra);
+        match &self.second_zebra {
+            None if zebra => errors.push(SetupError::MissingSecondZebra),
+            Some(card) if !zebra => errors.push(SetupError::UnneededSecondZebra(card.clone())),
+            Some(card) if self.cards().contains(card) => {
+                errors.push(SetupError::SecondZebraInKingdom(card.clone()))
+            }
+            _ => {}
+        }
+
+        if self.project_cards.len() > 2 {
+            errors.push(SetupError::TooManyProjects(self.project_cards.len()));
+        }
+
+        errors
+    }
+}
+
+/// A rule of setting up a game that a setup breaks
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum SetupError {
+    /// There have to be ten kingdom cards, not this many
+    KingdomCardCount(usize),
+
+    /// A card is in the kingdom twice, or is both a kingdom card and the bane
+    DuplicateCard(KC),
+
+    /// Young Witch is in the kingdom without a bane
+    MissingBane,
+
+    /// There's a bane without a Young Witch to need it
+    UnneededBane(KC),
+
+    /// The Young Witch bane has to cost 2 or 3
+    BaneCost(KC),
+
+    /// A custom bane card is attached to a card outside the kingdom
+    BaneCardNotInKingdom(KC),
+
+    /// A Zebra bane needs a second card set aside
+    MissingSecondZebra,
+
+    /// There's a second Zebra card without a Zebra bane
+    UnneededSecondZebra(KC),
+
+    /// The second Zebra card is already in the kingdom
+    SecondZebraInKingdom(KC),
+
+    /// There are more than two projects
+    TooManyProjects(usize),
+}
+
+/// A single piece of a setup that can be swapped out on its own
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub enum Slot {
+    /// One of the ten kingdom cards
+    KingdomCard(KC),
+
+    /// One of the projects
+    Project(Project),
+
+    /// The Young Witch bane card
+    Bane,
+
+    /// The card set aside by a Zebra bane
+    SecondZebra,
+}
+
+impl FromStr for Slot {
+    type Err = String;
+
+    /// Parse a slot from a card or project name, or the literal `Bane` or
+    /// `SecondZebra`
+    ///
+    ///```
+    ///use dominion::{Slot, KC, Project};
+    ///assert_eq!("Smithy".parse(), Ok(Slot::KingdomCard(KC::Smithy)));
+    ///assert_eq!("Fleet".parse(), Ok(Slot::Project(Project::Fleet)));
+    ///assert_eq!("Bane".parse(), Ok(Slot::Bane));
+    ///```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Bane" => Ok(Slot::Bane),
+            "SecondZebra" => Ok(Slot::SecondZebra),
+            _ => s
+                .parse()
+                .map(Slot::KingdomCard)
+                .or_else(|_| s.parse().map(Slot::Project))
+                .map_err(|_| format!("{} is not a kingdom card, project, Bane or SecondZebra", s)),
+        }
+    }
+}
+
+/// The number of projects allowed in a game
+#[derive(EnumString, Debug, PartialEq, Deserialize_repr, Serialize_repr, EnumIter, Clone)]
+#[repr(u8)]
+pub enum ProjectCount {
+    #[strum(serialize = "0")]
+    NoProjects = 0,
+    #[strum(serialize = "1")]
+    OneProject = 1,
+    #[strum(serialize = "2")]
+    TwoProjects = 2,
+}
+
+impl ProjectCount {
+    /// Convert enum to actual count
+    ///
+    ///```
+    ///let count = dominion::ProjectCount::OneProject.count();
+    ///assert_eq!(count, 1);
+    ///```
+    pub fn count(&self) -> usize {
+        match self {
+            ProjectCount::NoProjects => 0,
+            ProjectCount::OneProject => 1,
+            ProjectCount::TwoProjects => 2,
+        }
+    }
+}
+
+/// The number of custom bane cards allowed in a game
+#[derive(EnumString, Debug, PartialEq, Deserialize_repr, Serialize_repr, EnumIter, Clone)]
+#[repr(u8)]
+pub enum BaneCount {
+    #[strum(serialize = "0")]
+    NoBanes = 0,
+    #[strum(serialize = "1")]
+    OneBane = 1,
+    #[strum(serialize = "2")]
+    TwoBanes = 2,
+    #[strum(serialize = "3")]
+    ThreeBanes = 3,
+}
+
+impl BaneCount {
+    /// Convert enum to actual count
+    ///
+    ///```
+    ///let count = dominion::BaneCount::OneBane.count();
+    ///assert_eq!(count, 1);
+    ///```
+    pub fn count(&self) -> usize {
+        match self {
+            BaneCount::NoBanes => 0,
+            BaneCount::OneBane => 1,
+            BaneCount::TwoBanes => 2,
+            BaneCount::ThreeBanes => 3,
+        }
+    }
+}
+
+/// How many kingdom cards may come from an expansion
+///
+///```
+///use dominion::ExpansionCount;
+///assert_eq!("3".parse(), Ok(ExpansionCount { min: 3, max: Some(3) }));
+///assert_eq!("..2".parse(), Ok(ExpansionCount { min: 0, max: Some(2) }));
+///assert_eq!("1..".parse(), Ok(ExpansionCount { min: 1, max: None }));
+///```
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct ExpansionCount {
+    #[serde(default)]
+    pub min: usize,
+
+    /// No limit when missing
+    pub max: Option<usize>,
+}
+
+impl ExpansionCount {
+    fn allows(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl FromStr for ExpansionCount {
+    type Err = String;
+
+    /// `N` for exactly N, or a range: `MIN..MAX`, `MIN..` or `..MAX`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("{} isn't a count, try 3, 1..3, 1.. or ..3", s))
+        };
+
+        match s.split_once("..") {
+            None => number(s).map(|n| ExpansionCount {
+                min: n,
+                max: Some(n),
+            }),
+            Some((min, max)) => Ok(ExpansionCount {
+                min: if min.is_empty() { 0 } else { number(min)? },
+                max: if max.is_empty() {
+                    None
+                } else {
+                    Some(number(max)?)
+                },
+            }),
+        }
+    }
+}
+
+/// How to setup a game
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetupConfig {
+    /// Which specific expansions to include
+    pub include_expansions: Option<HashSet<Expansion>>,
+
+    /// Cards to be sure _not_ to include
+    pub ban_cards: Option<HashSet<KC>>,
+
+    /// Cards to be sure to include. It is okay for this to be inconsistent with
+    /// `include_expansions` (e.g. value here not member of listed expansions).
+    pub include_cards: Option<HashSet<KC>>,
+
+    /// How many projects to include (for random of count)
+    /// If expansions are provided and we can't pick enough projects to satisfy
+    /// this count, we'll return an error
+    pub project_count: Option<ProjectCount>,
+
+    /// How many bane cards to include (for random of count)
+    /// The "Bane Expansion" is my custom expansion
+    pub bane_count: Option<BaneCount>,
+
+    /// Only accept kingdoms with at least one known combo (see `synergy`)
+    #[serde(default)]
+    pub require_combo: bool,
+
+    /// Reject kingdoms with a known swingy pair (see `synergy`)
+    #[serde(default)]
+    pub avoid_swingy: bool,
+
+    /// Reject kingdoms more similar than this (from 0 to 1, see `similarity`)
+    /// to any of the kingdoms already played, which are passed to
+    /// `gen_setup_with_rng` rather than kept in the config
+    pub max_similarity: Option<f64>,
+
+    /// Reject kingdoms whose cards' summed win rates against Big Money are
+    /// above this (see `power`)
+    pub max_power: Option<f64>,
+
+    /// Reject kingdoms with two cards from the top tenth of the power table
+    #[serde(default)]
+    pub separate_top_cards: bool,
+
+    /// How much likelier an expansion's cards are to be picked than
+    /// others' (1 when missing). A card in several expansions gets the
+    /// average of theirs.
+    pub expansion_weights: Option<HashMap<Expansion, f64>>,
+
+    /// How much likelier a card is to be picked, on top of its expansions'
+    /// weights (1 when missing). A weight of 0 never picks the card.
+    pub card_weights: Option<HashMap<KC, f64>>,
+
+    /// Weight expansions by how many cards they have to offer, so each is
+    /// as likely to show up as the others however big it is
+    #[serde(default)]
+    pub equal_expansions: bool,
+
+    /// How many kingdom cards may come from each of these expansions. A card
+    /// in several expansions (e.g. Militia, in Base1 and Base2) counts for
+    /// each of them, and included cards count too.
+    pub expansion_counts: Option<HashMap<Expansion, ExpansionCount>>,
+
+    /// At least this many kingdom cards from each of `include_expansions`
+    /// (every expansion, when that's unset)
+    pub min_per_expansion: Option<usize>,
+}
+
+impl SetupConfig {
+    /// Give us a totally random game
+    pub fn none() -> SetupConfig {
+        SetupConfig {
+            include_expansions: None,
+            ban_cards: None,
+            include_cards: None,
+            project_count: None,
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        }
+    }
+
+    /// Only include these expansions
+    pub fn including_expansions(expansions: HashSet<Expansion>) -> SetupConfig {
+        SetupConfig {
+            include_expansions: Some(expansions),
+            ban_cards: None,
+            include_cards: None,
+            project_count: None,
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        }
+    }
+
+    /// Be sure to include (at least) these cards
+    pub fn including_cards(cards: HashSet<KC>) -> SetupConfig {
+        SetupConfig {
+            include_expansions: None,
+            ban_cards: None,
+            include_cards: Some(cards),
+            project_count: None,
+            bane_count: None,
+            require_combo: false,
+            avoid_swingy: false,
+            max_similarity: None,
+            max_power: None,
+            separate_top_cards: false,
+            expansion_weights: None,
+            card_weights: None,
+            equal_expansions: false,
+            expansion_counts: None,
+            min_per_expansion: None,
+        }
+    }
+
+    /// The errors generation is bound to hit with this config, without
+    /// generating anything. A config passing this can still fail on
+    /// `require_combo` and the like, which take trying.
+    ///
+    ///```
+    ///use dominion::{GenSetupError, SetupConfig, KC};
+    ///use std::collections::HashSet;
+    ///let config = SetupConfig {
+    ///    ban_cards: Some(HashSet::from([KC::Smithy])),
+    ///    ..SetupConfig::including_cards(HashSet::from([KC::Smithy]))
+    ///};
+    ///assert_eq!(config.validate(), vec![GenSetupError::IntersectingCardBansAndIncludes(vec![KC::Smithy])]);
+    ///```
+    pub fn validate(&self) -> Vec<GenSetupError> {
+        let mut errors = vec![];
+        let banned = self.ban_cards.clone().unwrap_or_default();
+        let included = self.include_cards.clone().unwrap_or_default();
+
+        let mut both: Vec<KC> = banned.intersection(&included).cloned().collect();
+        both.sort();
+        if !both.is_empty() {
+            errors.push(GenSetupError::IntersectingCardBansAndIncludes(both));
+        }
+
+        if included.len() > 10 {
+            errors.push(GenSetupError::TooManyCardsIncluded);
+        }
+
+        let expansions = desired_expansions(self);
+        let pool: Vec<KC> = KC::iter()
+            .filter(|card| !expansion_set(card).is_disjoint(&expansions))
+            .filter(|card| !banned.contains(card) && !included.contains(card))
+            .collect();
+        let available = card_weights(self, &pool)
+            .into_iter()
+            .filter(|weight| *weight > 0.0)
+            .count()
+            + included.difference(&banned).count();
+        if available < 10 {
+            errors.push(GenSetupError::CouldNotSatisfyKingdomCards);
+        }
+
+        errors.extend(expansion_count_errors(self));
+        errors.extend(check_weights(self).err());
+
+        let projects = Project::iter()
+            .filter(|project| !expansion_set(project).is_disjoint(&expansions))
+            .count();
+        if self
+            .project_count
+            .as_ref()
+            .is_some_and(|count| count.count() > projects)
+        {
+            errors.push(GenSetupError::CouldNotSatisfyProjectsFromExpansions);
+        }
+
+        errors
+    }
+}
+
+/// Which kingdom cards go first when evolving a setup
+#[derive(Debug, PartialEq, Clone, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum SwapPreference {
+    /// Any of them
+    Random,
+
+    /// Cards from the lowest rated kingdoms in the history log
+    LowestRated,
+
+    /// Cards that show up the most in the history log
+    MostPlayed,
+}
+
+/// How to evolve the next kingdom from the last one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvolveConfig {
+    /// How many kingdom cards to swap out
+    pub kingdom_swaps: usize,
+
+    /// How many projects to swap out
+    pub project_swaps: usize,
+
+    /// Which kingdom cards to swap out first
+    pub prefer: SwapPreference,
+}
+
+/// Errors we may encounter when generating a setup. These are mostly due to
+/// incoherent configurations.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum GenSetupError {
+    /// Asked for some number of projects but didn't supply enough expansions to
+    /// choose them.
+    CouldNotSatisfyProjectsFromExpansions,
+
+    /// Filtered in such a way as to not allow us to pick enough kingdom cards.
+    CouldNotSatisfyKingdomCards,
+
+    /// Filtered in such a way as to not allow us to pick a bane card, or
+    /// none of `CANDIDATE_ATTEMPTS` kingdoms left one to pick.
+    CouldNotSatisfyBaneCard,
+
+    /// Filtered in such a way as to not allow us to pick a second zebra card.
+    CouldNotSatisfySecondZebra,
+
+    /// Asked to ban and include one or more cards.
+    IntersectingCardBansAndIncludes(Vec<KC>),
+
+    /// Asked to include more cards than the kingdom can handle. Right now this
+    /// just errors if given more than 10. Technically we should be able to
+    /// handle an 11th if `KC::YoungWitch` is one of them.
+    TooManyCardsIncluded,
+
+    /// Asked to reroll a slot that the setup doesn't have.
+    SlotNotInSetup(Slot),
+
+    /// Couldn't find a kingdom with a combo (or without a swingy pair) after
+    /// `CANDIDATE_ATTEMPTS` tries.
+    CouldNotSatisfySynergies,
+
+    /// Every kingdom tried in `CANDIDATE_ATTEMPTS` was too similar to one of
+    /// the previous kingdoms.
+    CouldNotSatisfyMaxSimilarity,
+
+    /// Every kingdom tried in `CANDIDATE_ATTEMPTS` was too strong, or paired
+    /// two top cards.
+    CouldNotSatisfyPower,
+
+    /// Asked for a number of cards from an expansion that it can't give,
+    /// e.g. more than it has or fewer than the included cards from it.
+    ImpossibleExpansionCount(Expansion),
+
+    /// Couldn't find a kingdom meeting every expansion's count together
+    /// after `CANDIDATE_ATTEMPTS` tries.
+    CouldNotSatisfyExpansionCounts,
+
+    /// A weight is negative, infinite or not a number.
+    InvalidWeight,
+}
+
+fn expansion_set<T: Expansions>(v: &T) -> HashSet<Expansion> {
+    v.expansions().into_iter().collect()
+}
+
+fn desired_expansions(config: &SetupConfig) -> HashSet<Expansion> {
+    config
+        .include_expansions
+        .clone()
+        .unwrap_or_else(|| Expansion::iter().collect())
+}
+
+fn is_bane_cost(card: &KC) -> bool {
+    card.base_cost() == 2 || card.base_cost() == 3
+}
+
+/// Weights must be finite, or a single infinite one would always be picked
+/// first and the order would go by declaration instead of chance
+fn check_weights(config: &SetupConfig) -> Result<(), GenSetupError> {
+    let expansion_weights = config.expansion_weights.iter().flat_map(|w| w.values());
+    let card_weights = config.card_weights.iter().flat_map(|w| w.values());
+    if expansion_weights
+        .chain(card_weights)
+        .all(|weight| weight.is_finite() && *weight >= 0.0)
+    {
+        Ok(())
+    } else {
+        Err(GenSetupError::InvalidWeight)
+    }
+}
+
+/// How likely each of `pool`'s cards is to be picked next to the others,
+/// going by the config's weights
+fn card_weights(config: &SetupConfig, pool: &[KC]) -> Vec<f64> {
+    let desired = desired_expansions(config);
+    let expansions = |card: &KC| -> Vec<Expansion> {
+        card.expansions()
+            .into_iter()
+            .filter(|expansion| desired.contains(expansion))
+            .collect()
+    };
+
+    let mut sizes: HashMap<Expansion, usize> = HashMap::new();
+    for card in pool {
+        for expansion in expansions(card) {
+            *sizes.entry(expansion).or_default() += 1;
+        }
+    }
+
+    pool.iter()
+        .map(|card| {
+            let weights: Vec<f64> = expansions(card)
+                .iter()
+                .map(|expansion| {
+                    let weight = config
+                        .expansion_weights
+                        .as_ref()
+                        .and_then(|weights| weights.get(expansion))
+                        .copied()
+                        .unwrap_or(1.0);
+
+                    // Each expansion's weight is shared out between its cards
+                    if config.equal_expansions {
+                        weight / sizes[expansion] as f64
+                    } else {
+                        weight
+                    }
+                })
+                .collect();
+            let expansion_weight = if config.equal_expansions {
+                weights.iter().sum()
+            } else {
+                weights.iter().sum::<f64>() / weights.len().max(1) as f64
+            };
+
+            expansion_weight
+                * config
+                    .card_weights
+                    .as_ref()
+                    .and_then(|weights| weights.get(card))
+                    .copied()
+                    .unwrap_or(1.0)
+        })
+        .collect()
+}
+
+/// Put `cards` in a random order with heavier cards likelier to come first,
+/// dropping those that weigh nothing. Taking the first few is weighted
+/// sampling without replacement: each card gets the key `u^(1/weight)` for a
+/// uniform `u`, and the highest keys go first (Efraimidis and Spirakis).
+/// Without any weights it's a plain shuffle, so seeds give what they always
+/// have.
+fn weighted_shuffle<R: Rng>(cards: &mut Vec<KC>, config: &SetupConfig, rng: &mut R) {
+    if config.expansion_weights.is_none()
+        && config.card_weights.is_none()
+        && !config.equal_expansions
+    {
+        cards.shuffle(rng);
+        return;
+    }
+
+    let weights = card_weights(config, cards);
+    // `ln(u) / weight` orders the same as `u^(1/weight)` without underflowing
+    let mut keyed: Vec<(f64, KC)> = cards
+        .drain(..)
+        .zip(weights)
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(card, weight)| (rng.gen::<f64>().ln() / weight, card))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    cards.extend(keyed.into_iter().map(|(_, card)| card));
+}
+
+/// The bounds on each expansion's kingdom cards, from `expansion_counts` and
+/// `min_per_expansion`
+fn expansion_counts(config: &SetupConfig) -> HashMap<Expansion, ExpansionCount> {
+    let mut counts = config.expansion_counts.clone().unwrap_or_default();
+
+    if let Some(min) = config.min_per_expansion {
+        for expansion in desired_expansions(config) {
+            let count = counts.entry(expansion).or_default();
+            count.min = count.min.max(min);
+        }
+    }
+
+    counts
+}
+
+/// How many of `cards` come from each expansion, counting a card for every
+/// expansion it's in
+fn expansion_tally<'a>(cards: impl IntoIterator<Item = &'a KC>) -> HashMap<Expansion, usize> {
+    let mut tally = HashMap::new();
+    for card in cards {
+        for expansion in card.expansions() {
+            *tally.entry(expansion).or_default() += 1;
+        }
+    }
+    tally
+}
+
+/// The expansion counts that no kingdom can meet, going by the cards there
+/// are to pick from
+fn expansion_count_errors(config: &SetupConfig) -> Vec<GenSetupError> {
+    let counts = expansion_counts(config);
+    if counts.is_empty() {
+        return vec![];
+    }
+
+    let banned = config.ban_cards.clone().unwrap_or_default();
+    let included = config.include_cards.clone().unwrap_or_default();
+    let desired = desired_expansions(config);
+    let pool: Vec<KC> = KC::iter()
+        .filter(|card| !expansion_set(card).is_disjoint(&desired))
+        .filter(|card| !banned.contains(card) && !included.contains(card))
+        .collect();
+    let pickable: Vec<KC> = pool
+        .iter()
+        .zip(card_weights(config, &pool))
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(card, _)| card.clone())
+        .collect();
+
+    let forced = expansion_tally(&included);
+    let available = expansion_tally(&pickable);
+
+    let mut errors: Vec<GenSetupError> = expansions_in_order(&counts)
+        .into_iter()
+        .filter(|expansion| {
+            let count = &counts[*expansion];
+            let forced = forced.get(*expansion).copied().unwrap_or(0);
+            let available = available.get(*expansion).copied().unwrap_or(0);
+
+            count.min > 10
+                || count.max.is_some_and(|max| max < count.min || max < forced)
+                || count.min > forced + available
+        })
+        .map(|expansion| GenSetupError::ImpossibleExpansionCount(expansion.clone()))
+        .collect();
+
+    // Every card picked counts for at least one desired expansion, so when
+    // they all have a maximum the maximums have to leave room for ten
+    let maxes: Option<usize> = desired
+        .iter()
+        .map(|expansion| counts.get(expansion).and_then(|count| count.max))
+        .sum();
+    let outside = included
+        .iter()
+        .filter(|card| expansion_set(*card).is_disjoint(&desired))
+        .count();
+    if errors.is_empty() && maxes.is_some_and(|maxes| maxes + outside < 10) {
+        errors.push(GenSetupError::CouldNotSatisfyExpansionCounts);
+    }
+
+    // Each count can be met on its own and still not alongside the others,
+    // e.g. minimums adding up to more than ten cards. Adding the counts one
+    // at a time finds the first that doesn't fit.
+    if errors.is_empty() {
+        let needed = 10usize.saturating_sub(included.len());
+        let mut fitted = HashMap::new();
+        for expansion in expansions_in_order(&counts) {
+            fitted.insert(expansion.clone(), counts[expansion].clone());
+            let picks = count::Picks::new(&fitted, &pickable, false);
+            if picks.ways(0, needed, &picks.tally(&included)) == 0 {
+                errors.push(GenSetupError::ImpossibleExpansionCount(expansion.clone()));
+                break;
+            }
+        }
+    }
+
+    errors
+}
+
+/// The counted expansions, in the order their problems are reported
+fn expansions_in_order(counts: &HashMap<Expansion, ExpansionCount>) -> Vec<&Expansion> {
+    let mut expansions: Vec<&Expansion> = counts.keys().collect();
+    expansions.sort_by_key(|expansion| format!("{:?}", expansion));
+    expansions
+}
+
+/// Does the setup's kingdom meet the config's expansion counts?
+fn expansion_counts_met(config: &SetupConfig, setup: &Setup) -> bool {
+    let tally = expansion_tally(&setup.kingdom_cards);
+    expansion_counts(config)
+        .iter()
+        .all(|(expansion, count)| count.allows(tally.get(expansion).copied().unwrap_or(0)))
+}
+
+/// `needed` of the `ordered` cards to go with the `forced` ones: the first
+/// ones, when no expansion is counted. Otherwise, cards from expansions
+/// still short of their minimum come first, and cards that would take an
+/// expansion past its maximum are skipped. `None` if the counts can't be met
+/// this way.
+fn pick_kingdom_cards(
+    config: &SetupConfig,
+    ordered: &[KC],
+    forced: &[KC],
+    needed: usize,
+) -> Option<Vec<KC>> {
+    let counts = expansion_counts(config);
+    if counts.is_empty() || ordered.len() < needed {
+        return Some(ordered.iter().take(needed).cloned().collect());
+    }
+
+    let mut tally = expansion_tally(forced);
+    let mut picked: Vec<KC> = vec![];
+    let tallied = |tally: &HashMap<Expansion, usize>, expansion: &Expansion| {
+        tally.get(expansion).copied().unwrap_or(0)
+    };
+    let fits = |tally: &HashMap<Expansion, usize>, card: &KC| {
+        card.expansions().iter().all(|expansion| {
+            counts
+                .get(expansion)
+                .and_then(|count| count.max)
+                .is_none_or(|max| tallied(tally, expansion) < max)
+        })
+    };
+    let short = |tally: &HashMap<Expansion, usize>, card: &KC| {
+        card.expansions().iter().any(|expansion| {
+            counts
+                .get(expansion)
+                .is_some_and(|count| tallied(tally, expansion) < count.min)
+        })
+    };
+
+    while picked.len() < needed {
+        let next = ordered
+            .iter()
+            .filter(|card| !picked.contains(card) && fits(&tally, card))
+            .find(|card| short(&tally, card))
+            .or_else(|| {
+                ordered
+                    .iter()
+                    .find(|card| !picked.contains(card) && fits(&tally, card))
+            })?;
+
+        for expansion in next.expansions() {
+            *tally.entry(expansion).or_default() += 1;
+        }
+        picked.push(next.clone());
+    }
+
+    counts
+        .iter()
+        .all(|(expansion, count)| count.allows(tallied(&tally, expansion)))
+        .then_some(picked)
+}
+
+/// Generate a valid setup from options (`SetupConfig`)
+pub fn gen_setup(config: SetupConfig) -> Result<Setup, GenSetupError> {
+    gen_setup_with_rng(config, &[], &mut rand::thread_rng())
+}
+
+/// How many kingdoms `gen_setup` tries before giving up on `require_combo`,
+/// `avoid_swingy`, `max_similarity`, `max_power` and `separate_top_cards`,
+/// and how many orders of the cards it tries for the expansion counts
+pub const CANDIDATE_ATTEMPTS: usize = 500;
+
+/// Generate a valid setup from options (`SetupConfig`), drawing randomness
+/// from `rng` so that a seeded generator gives the same setup every time,
+/// and keeping to the config's `max_similarity` from the `previous` kingdoms
+pub fn gen_setup_with_rng<R: Rng>(
+    config: SetupConfig,
+    previous: &[Setup],
+    rng: &mut R,
+) -> Result<Setup, GenSetupError> {
+    check_weights(&config)?;
+    first_passing(&config, previous, || gen_candidate(&config, rng))
+}
+
+/// The first of `CANDIDATE_ATTEMPTS` candidates that passes the config's
+/// per-kingdom checks
+fn first_passing(
+    config: &SetupConfig,
+    previous: &[Setup],
+    mut candidate: impl FnMut() -> Result<Setup, GenSetupError>,
+) -> Result<Setup, GenSetupError> {
+    let mut rejected = GenSetupError::CouldNotSatisfySynergies;
+    let table = power::table();
+
+    for _ in 0..CANDIDATE_ATTEMPTS {
+        let setup = candidate()?;
+
+        match rejection(config, previous, table, &setup) {
+            None => return Ok(setup),
+            Some(err) => rejected = err,
+        }
+    }
+
+    Err(rejected)
+}
+
+/// Which of the config's per-kingdom checks the setup fails, if any
+fn rejection(
+    config: &SetupConfig,
+    previous: &[Setup],
+    table: &[power::Ranking],
+    setup: &Setup,
+) -> Option<GenSetupError> {
+    if !expansion_counts_met(config, setup) {
+        Some(GenSetupError::CouldNotSatisfyExpansionCounts)
+    } else if !synergy::satisfies(config, setup) {
+        Some(GenSetupError::CouldNotSatisfySynergies)
+    } else if !similarity::satisfies(config, previous, setup) {
+        Some(GenSetupError::CouldNotSatisfyMaxSimilarity)
+    } else if !power::satisfies(config, table, setup) {
+        Some(GenSetupError::CouldNotSatisfyPower)
+    } else {
+        None
+    }
+}
+
+/// A setup that fits the config's cards, expansions and counts
+fn gen_candidate<R: Rng>(config: &SetupConfig, rng: &mut R) -> Result<Setup, GenSetupError> {
+    for _ in 0..CANDIDATE_ATTEMPTS {
+        if let Some(setup) = try_candidate(config, rng)? {
+            return Ok(setup);
+        }
+    }
+
+    Err(GenSetupError::CouldNotSatisfyBaneCard)
+}
+
+/// One go at `gen_candidate`, or `None` if the cards it drew left no bane
+/// but another draw might
+fn try_candidate<R: Rng>(
+    config: &SetupConfig,
+    rng: &mut R,
+) -> Result<Option<Setup>, GenSetupError> {
+    for bans in &config.ban_cards {
+        for includes in &config.include_cards {
+            if !bans.is_disjoint(includes) {
+                let cards_in_common = bans.intersection(&includes);
+                return Err(GenSetupError::IntersectingCardBansAndIncludes(
+                    cards_in_common.cloned().collect(),
+                ));
+            }
+        }
+    }
+
+    let desired_expansions = desired_expansions(config);
+
+    let possible_projects: Vec<Project> = Project::iter()
+        .filter(|p| !expansion_set(p).is_disjoint(&desired_expansions))
+        .collect();
+
+    let banned_cards = config.ban_cards.clone().unwrap_or(HashSet::new());
+
+    let forced_kingdom_cards = config.include_cards.clone().unwrap_or(HashSet::new());
+
+    if forced_kingdom_cards.len() > 10 {
+        return Err(GenSetupError::TooManyCardsIncluded);
+    }
+
+    if let Some(error) = expansion_count_errors(config).into_iter().next() {
+        return Err(error);
+    }
+
+    let mut possible_kingdom_cards: Vec<KC> = KC::iter()
+        .filter(|kc| !expansion_set(kc).is_disjoint(&desired_expansions))
+        .filter(|kc| !banned_cards.contains(kc))
+        .filter(|kc| !forced_kingdom_cards.contains(kc))
+        .collect();
+
+    let project_count = match &config.project_count {
+        Some(desired) => {
+            if possible_projects.len() < desired.count() {
+                return Err(GenSetupError::CouldNotSatisfyProjectsFromExpansions);
+            } else {
+                desired.count()
+            }
+        }
+        None => rng.gen_range(0..3),
+    };
+
+    let project_cards = possible_projects
+        .choose_multiple(rng, project_count)
+        .cloned()
+        .collect();
+
+    let random_needed = 10 - &forced_kingdom_cards.len();
+
+    // Sorted so that a seeded `rng` picks the same custom banes every time
+    let mut forced: Vec<KC> = forced_kingdom_cards.iter().cloned().collect();
+    forced.sort();
+
+    // The order can leave an expansion short, so try a few
+    let mut picked = None;
+    for _ in 0..CANDIDATE_ATTEMPTS {
+        weighted_shuffle(&mut possible_kingdom_cards, config, rng);
+        picked = pick_kingdom_cards(config, &possible_kingdom_cards, &forced, random_needed);
+        if picked.is_some() {
+            break;
+        }
+    }
+    let mut kingdom_cards = picked.ok_or(GenSetupError::CouldNotSatisfyExpansionCounts)?;
+    kingdom_cards.append(&mut forced);
+
+    if kingdom_cards.len() < 10 {
+        return Err(GenSetupError::CouldNotSatisfyKingdomCards);
+    }
+
+    let mut bane_card = None;
+
+    let mut remaining_possible_kingdom_cards = possible_kingdom_cards
+        .iter()
+        .filter(|c| !kingdom_cards.contains(c))
+        .filter(|c| c.base_cost() == 2 || c.base_cost() == 3);
+
+    if kingdom_cards.contains(&KC::YoungWitch) {
+        bane_card = remaining_possible_kingdom_cards.next().cloned();
+
+        // We struck out but making this work is possible. Let's try again.
+        if bane_card.is_none()
+            && kingdom_cards
+                .iter()
+                .any(|kc| kc.base_cost() == 2 || kc.base_cost() == 3)
+        {
+            return Ok(None);
+        }
+        // Not possible.
+        if bane_card.is_none() {
+            return Err(GenSetupError::CouldNotSatisfyBaneCard);
+        }
+    }
+
+    let bane_count = config.bane_count.as_ref().map(|bc| bc.count()).unwrap_or(0);
+
+    let all_banes = BaneCard::iter().collect::<Vec<_>>();
+
+    let bane_cards: HashMap<KC, BaneCard> = kingdom_cards
+        .choose_multiple(rng, bane_count)
+        .cloned()
+        .zip(all_banes.choose_multiple(rng, bane_count).cloned())
+        .collect();
+
+    let mut second_zebra = None;
+
+    if bane_cards
+        .values()
+        .cloned()
+        .collect::<Vec<_>>()
+        .contains(&&BaneCard::Zebra)
+    {
+        second_zebra = remaining_possible_kingdom_cards.next().cloned();
+
+        if second_zebra.is_none() {
+            return Err(GenSetupError::CouldNotSatisfySecondZebra);
+        }
+    }
+
+    Ok(Some(Setup {
+        project_cards,
+        kingdom_cards,
+        bane_card,
+        bane_cards,
+        second_zebra,
+        parent: None,
+    }))
+}
+
+/// Swap out a single slot of an existing setup, keeping everything else.
+///
+/// Replacements come from the config's `include_expansions` without its
+/// `ban_cards`, weighted like `gen_setup` weighs them, and have to pass the
+/// same checks on the whole kingdom: expansion counts, combos and swingy
+/// pairs, and power. Derived pieces are kept consistent: a Young Witch coming
+/// in brings a bane card, a Young Witch going out takes it along, a custom
+/// bane moves onto the replacement card and replacements never collide with
+/// `second_zebra`.
+pub fn reroll(setup: &Setup, slot: Slot, config: SetupConfig) -> Result<Setup, GenSetupError> {
+    reroll_with_previous(setup, slot, config, &[])
+}
+
+/// `reroll`, also keeping to the config's `max_similarity` from the
+/// `previous` kingdoms
+pub fn reroll_with_previous(
+    setup: &Setup,
+    slot: Slot,
+    config: SetupConfig,
+    previous: &[Setup],
+) -> Result<Setup, GenSetupError> {
+    check_weights(&config)?;
+    let mut rng = rand::thread_rng();
+
+    let desired_expansions = desired_expansions(&config);
+    let candidates = replacements(setup, &config, &mut rng);
+    let with = |change: &dyn Fn(&mut Setup)| {
+        let mut result = setup.clone();
+        change(&mut result);
+        Ok(result)
+    };
+
+    // Each replacement in the order to try it, or why it can't go in
+    let (rerolled, mut err): (Vec<Result<Setup, GenSetupError>>, _) = match slot {
+        Slot::KingdomCard(ref old) => {
+            if !setup.kingdom_cards.contains(old) {
+                return Err(GenSetupError::SlotNotInSetup(slot.clone()));
+            }
+
+            (
+                candidates
+                    .iter()
+                    .map(|new| replace_kingdom_card(setup, old, new, &candidates))
+                    .collect(),
+                GenSetupError::CouldNotSatisfyKingdomCards,
+            )
+        }
+        Slot::Project(ref old) => {
+            let position = setup
+                .project_cards
+                .iter()
+                .position(|p| p == old)
+                .ok_or_else(|| GenSetupError::SlotNotInSetup(slot.clone()))?;
+
+            let mut projects: Vec<Project> = Project::iter()
+                .filter(|p| !expansion_set(p).is_disjoint(&desired_expansions))
+                .filter(|p| !setup.project_cards.contains(p))
+                .collect();
+            projects.shuffle(&mut rng);
+
+            (
+                projects
+                    .into_iter()
+                    .map(|project| with(&|result| result.project_cards[position] = project.clone()))
+                    .collect(),
+                GenSetupError::CouldNotSatisfyProjectsFromExpansions,
+            )
+        }
+        Slot::Bane => {
+            if setup.bane_card.is_none() {
+                return Err(GenSetupError::SlotNotInSetup(slot));
+            }
+
+            (
+                candidates
+                    .iter()
+                    .filter(|card| is_bane_cost(card))
+                    .map(|card| with(&|result| result.bane_card = Some(card.clone())))
+                    .collect(),
+                GenSetupError::CouldNotSatisfyBaneCard,
+            )
+        }
+        Slot::SecondZebra => {
+            if setup.second_zebra.is_none() {
+                return Err(GenSetupError::SlotNotInSetup(slot));
+            }
+
+            (
+                candidates
+                    .iter()
+                    .filter(|card| is_bane_cost(card))
+                    .map(|card| with(&|result| result.second_zebra = Some(card.clone())))
+                    .collect(),
+                GenSetupError::CouldNotSatisfySecondZebra,
+            )
+        }
+    };
+
+    let table = power::table();
+    for result in rerolled {
+        match result {
+            Ok(result) => match rejection(&config, previous, table, &result) {
+                None => return Ok(result),
+                Some(rejected) => err = rejected,
+            },
+            Err(e) => err = e,
+        }
+    }
+
+    Err(err)
+}
+
+/// Put `new` in the kingdom in place of `old` like `reroll` would, for a
+/// card picked rather than drawn
+fn swap_in(
+    setup: &Setup,
+    old: &KC,
+    new: &KC,
+    config: &SetupConfig,
+) -> Result<Setup, GenSetupError> {
+    check_weights(config)?;
+    let candidates = replacements(setup, config, &mut rand::thread_rng());
+
+    replace_kingdom_card(setup, old, new, &candidates)
+}
+
+/// The cards that could replace one of `setup`'s, in the order to try them
+fn replacements<R: Rng>(setup: &Setup, config: &SetupConfig, rng: &mut R) -> Vec<KC> {
+    let desired_expansions = desired_expansions(config);
+    let banned_cards = config.ban_cards.clone().unwrap_or_default();
+    let mut taken = setup.cards();
+    taken.extend(setup.second_zebra.clone());
+
+    let mut candidates: Vec<KC> = KC::iter()
+        .filter(|kc| !expansion_set(kc).is_disjoint(&desired_expansions))
+        .filter(|kc| !banned_cards.contains(kc))
+        .filter(|kc| !taken.contains(kc))
+        .collect();
+    weighted_shuffle(&mut candidates, config, rng);
+    candidates
+}
+
+/// `setup` with `new` in `old`'s kingdom slot, its custom bane moved along
+/// and a bane card from `candidates` if `new` is a Young Witch
+fn replace_kingdom_card(
+    setup: &Setup,
+    old: &KC,
+    new: &KC,
+    candidates: &[KC],
+) -> Result<Setup, GenSetupError> {
+    let position = setup
+        .kingdom_cards
+        .iter()
+        .position(|kc| kc == old)
+        .ok_or_else(|| GenSetupError::SlotNotInSetup(Slot::KingdomCard(old.clone())))?;
+
+    let mut result = setup.clone();
+    result.kingdom_cards[position] = new.clone();
+
+    if let Some(bane) = result.bane_cards.remove(old) {
+        result.bane_cards.insert(new.clone(), bane);
+    }
+
+    if !result.kingdom_cards.contains(&KC::YoungWitch) {
+        result.bane_card = None;
+    } else if result.bane_card.is_none() {
+        result.bane_card = Some(
+            candidates
+                .iter()
+                .find(|c| *c != new && is_bane_cost(c))
+                .cloned()
+                .ok_or(GenSetupError::CouldNotSatisfyBaneCard)?,
+        );
+    }
+
+    Ok(result)
+}
+
+/// Generate the next kingdom from a previous one ("keep 7, swap 3"), swapping
+/// cards and projects one at a time with `reroll`. Swapped out cards won't come
+/// back. `history` is only consulted for `LowestRated` and `MostPlayed`.
+pub fn evolve(
+    parent: &Setup,
+    evolve: &EvolveConfig,
+    config: SetupConfig,
+    history: &[history::Played],
+) -> Result<Setup, GenSetupError> {
+    let mut rng = rand::thread_rng();
+
+    let mut outgoing = parent.kingdom_cards.clone();
+    outgoing.shuffle(&mut rng);
+
+    match evolve.prefer {
+        SwapPreference::Random => {}
+        SwapPreference::LowestRated => {
+            let ratings = history::card_ratings(history);
+            outgoing.sort_by(|a, b| {
+                let rating = |kc| ratings.get(kc).cloned().unwrap_or(f64::INFINITY);
+                rating(a).total_cmp(&rating(b))
+            });
+        }
+        SwapPreference::MostPlayed => {
+            let plays = history::card_plays(history);
+            outgoing.sort_by_key(|kc| std::cmp::Reverse(plays.get(kc).cloned().unwrap_or(0)));
+        }
+    }
+
+    let mut projects = parent.project_cards.clone();
+    projects.shuffle(&mut rng);
+
+    let slots = outgoing
+        .into_iter()
+        .take(evolve.kingdom_swaps)
+        .map(Slot::KingdomCard)
+        .chain(
+            projects
+                .into_iter()
+                .take(evolve.project_swaps)
+                .map(Slot::Project),
+        );
+
+    let mut config = config;
+    let mut setup = parent.clone();
+
+    // An evolved kingdom is meant to be like the last one, so it's not held
+    // to `max_similarity`
+    for slot in slots {
+        setup = reroll(&setup, slot.clone(), config.clone())?;
+
+        if let Slot::KingdomCard(card) = slot {
+            let mut bans = config.ban_cards.unwrap_or_default();
+            bans.insert(card);
+            config.ban_cards = Some(bans);
+        }
+    }
+
+    setup.parent = Some(pretty::format_setup(parent));
+
+    Ok(setup)
+}
+
+pub mod pretty {
+    use super::hist::Hist;
+    use super::*;
+    use chrono::prelude::*;
+    use std::collections::BTreeMap;
+    use std::fmt::Debug;
+    use std::hash::Hash;
+
+    pub fn code(name: String, setup: &Setup) -> String {
+        let now_local = Local::now();
+
+        format!(
+            "  , Played {{ name = Just \"{} at {}\"
+           , at = Just $ Date {{year={}, month={}, day={}}}
+           , setup = {}
+           , players = Just []
+           , rating = Nothing
+           }}
+",
+            name,
+            now_local,
+            now_local.year(),
+            now_local.month(),
+            now_local.day(),
+            format_setup(setup)
+        )
+    }
+
+    fn format_card(card: &KC, setup: &Setup) -> String {
+        match &setup.bane_cards.get(&card) {
+            Some(BaneCard::Zebra) => format!(
+                " - {:?} (Zebra with {})",
+                card,
+                setup
+                    .second_zebra
+                    .clone()
+                    .map(spaces)
+                    .unwrap_or(String::new())
+            ),
+            Some(b) => format!(" - {} ({})", spaces(card), spaces(b)),
+            None => match &setup.bane_card {
+                Some(c) => format!(
+                    " - {} {}",
+                    spaces(card),
+                    if c == card { "(Bane)" } else { "" }
+                ),
+                None => format!(" - {}", spaces(card)),
+            },
+        }
+    }
+
+    pub(crate) fn spaces<T: Debug>(card: T) -> String {
+        format!("{:?}", card)
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                if i == 0 || !c.is_uppercase() {
+                    vec![c]
+                } else {
+                    vec![' ', c]
+                }
+            })
+            .collect()
+    }
+
+    fn kingdom_card_by_expansion_list(setup: &Setup) -> String {
+        let mut cards_by_expansion: HashMap<String, String> = HashMap::new();
+
+        let mut cards = setup.cards();
+
+        cards.sort();
+
+        for card in cards {
+            let exp = card
+                .expansions()
+                .iter()
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            cards_by_expansion
+                .entry(exp)
+                .and_modify(|s| {
+                    s.push('\n');
+                    s.push_str(&format_card(&card, &setup))
+                })
+                .or_insert(format_card(&card, &setup));
+        }
+
+        let mut kingdom_cards = String::new();
+
+        for (exp, cs) in cards_by_expansion.into_iter() {
+            kingdom_cards.push_str(&exp);
+            kingdom_cards.push('\n');
+            kingdom_cards.push_str(&cs);
+            kingdom_cards.push('\n');
+        }
+
+        format!(
+            "\
+┌───────────────┐
+│ Kingdom Cards │
+└───────────────┘
+
+{}",
+            kingdom_cards
+        )
+    }
+
+    fn project_card_by_expansion_list(project_cards: &Vec<Project>) -> String {
+        if project_cards.is_empty() {
+            return "".to_string();
+        }
+
+        let mut cards_by_expansion: HashMap<String, String> = HashMap::new();
+
+        let mut cards = project_cards.clone();
+
+        cards.sort();
+
+        for card in cards {
+            let exp = card
+                .expansions()
+                .iter()
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+                .join("/");
+
+            cards_by_expansion
+                .entry(exp)
+                .and_modify(|s| {
+                    s.push('\n');
+                    s.push_str(&format!(" - {:?}", card))
+                })
+                .or_insert(format!(" - {:?}", card));
+        }
+
+        let mut project_cards = String::new();
+
+        for (exp, cs) in cards_by_expansion.into_iter() {
+            project_cards.push_str(&exp);
+            project_cards.push('\n');
+            project_cards.push_str(&cs);
+            project_cards.push('\n');
+        }
+
+        format!(
+            "\
+┌───────────────┐
+│ Project Cards │
+└───────────────┘
+
+{}",
+            project_cards
+        )
+    }
+
+    pub fn pretty(setup: &Setup) -> String {
+        format!(
+            "{}\n{}",
+            kingdom_card_by_expansion_list(&setup),
+            project_card_by_expansion_list(&setup.project_cards)
+        )
+    }
+
+    /// A format setups can be rendered to with `render`. Renderers only lay
+    /// out pieces, `render` decides what goes in them.
+    pub trait Renderer {
+        /// The whole document, given its title and rendered sections
+        fn document(&self, title: &str, sections: &[String]) -> String;
+
+        /// A titled section made of rendered parts
+        fn section(&self, title: &str, parts: &[String]) -> String;
+
+        /// A named list, e.g. an expansion's cards
+        fn list(&self, name: &str, items: &[String]) -> String;
+
+        /// A titled table of labels and counts
+        fn table(&self, title: &str, rows: &[(String, usize)]) -> String;
+    }
+
+    /// Markdown, for wikis and chats
+    pub struct Markdown;
+
+    /// A self-contained HTML page
+    pub struct Html;
+
+    impl Renderer for Markdown {
+        fn document(&self, title: &str, sections: &[String]) -> String {
+            format!("# {}\n\n{}", title, sections.join("\n"))
+        }
+
+        fn section(&self, title: &str, parts: &[String]) -> String {
+            format!("## {}\n\n{}", title, parts.join("\n"))
+        }
+
+        fn list(&self, name: &str, items: &[String]) -> String {
+            let items: Vec<_> = items.iter().map(|i| format!("- {}\n", i)).collect();
+            format!("**{}**\n\n{}", name, items.concat())
+        }
+
+        fn table(&self, title: &str, rows: &[(String, usize)]) -> String {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(label, n)| format!("| {} | {} |\n", label, n))
+                .collect();
+            format!("**{}**\n\n| | Count |\n|---|---|\n{}", title, rows.concat())
+        }
+    }
+
+    pub(crate) fn escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    impl Renderer for Html {
+        fn document(&self, title: &str, sections: &[String]) -> String {
+            format!(
+                "\
+<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"UTF-8\">
+<title>{title}</title>
+<style>
+body {{ background-color: #ede4cb; color: #25140a; font-family: sans-serif; }}
+table {{ border-collapse: collapse; margin-bottom: 1rem; }}
+th, td {{ border: 1px solid #8c7474; padding: 2px 8px; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{}</body>
+</html>
+",
+                sections.concat(),
+                title = escape(title)
+            )
+        }
+
+        fn section(&self, title: &str, parts: &[String]) -> String {
+            format!(
+                "<section>\n<h2>{}</h2>\n{}</section>\n",
+                escape(title),
+                parts.concat()
+            )
+        }
+
+        fn list(&self, name: &str, items: &[String]) -> String {
+            let items: Vec<_> = items
+                .iter()
+                .map(|i| format!("<li>{}</li>\n", escape(i)))
+                .collect();
+            format!("<h3>{}</h3>\n<ul>\n{}</ul>\n", escape(name), items.concat())
+        }
+
+        fn table(&self, title: &str, rows: &[(String, usize)]) -> String {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(label, n)| format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(label), n))
+                .collect();
+            format!(
+                "<table>\n<caption>{}</caption>\n<tr><th></th><th>Count</th></tr>\n{}</table>\n",
+                escape(title),
+                rows.concat()
+            )
+        }
+    }
+
+    /// Plain text, for the terminal
+    pub struct Text;
+
+    impl Renderer for Text {
+        fn document(&self, title: &str, sections: &[String]) -> String {
+            format!("== {} ==\n\n{}", title, sections.join("\n"))
+        }
+
+        fn section(&self, title: &str, parts: &[String]) -> String {
+            format!("{}\n\n{}", title.to_uppercase(), parts.join("\n"))
+        }
+
+        fn list(&self, name: &str, items: &[String]) -> String {
+            let items: Vec<_> = items.iter().map(|i| format!("  {}\n", i)).collect();
+            format!("{}:\n{}", name, items.concat())
+        }
+
+        fn table(&self, title: &str, rows: &[(String, usize)]) -> String {
+            let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(label, n)| format!("  {:<width$}  {}\n", label, n, width = width))
+                .collect();
+            format!("{}:\n{}", title, rows.concat())
+        }
+    }
+
+    /// A listing of cards, expansions, projects or banes, e.g.
+    /// `YoungWitch (4, Action, Attack) [Cornucopia]`. Names are left as the
+    /// flags take them.
+    pub fn listing<R: Renderer + ?Sized>(
+        renderer: &R,
+        listing: catalog::Listing,
+        entries: &[catalog::Entry],
+    ) -> String {
+        let title = format!("{:?}", listing);
+        let items: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                let mut about = vec![];
+                if let Some(cost) = entry.cost {
+                    about.push(cost.to_string());
+                }
+                if !entry.types.is_empty() {
+                    about.push(format!("{:?}", entry.types).replace(['[', ']'], ""));
+                }
+                let mut label = entry.name.clone();
+                if !about.is_empty() {
+                    label.push_str(&format!(" ({})", about.join(", ")));
+                }
+                if listing != catalog::Listing::Expansions && !entry.expansions.is_empty() {
+                    label.push_str(&format!(
+                        " [{}]",
+                        entry
+                            .expansions
+                            .iter()
+                            .map(|e| format!("{:?}", e))
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    ));
+                }
+                label
+            })
+            .collect();
+
+        renderer.document(
+            &title,
+            &[renderer.list(&format!("{} found", entries.len()), &items)],
+        )
+    }
+
+    /// A card's details, as `show` prints them
+    pub fn card<R: Renderer + ?Sized>(renderer: &R, details: &catalog::Details) -> String {
+        let names = |values: Vec<String>| {
+            if values.is_empty() {
+                "none".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+
+        let mut facts = vec![
+            format!("Cost: {}", details.cost),
+            format!(
+                "Types: {}",
+                names(details.types.iter().map(|t| format!("{:?}", t)).collect())
+            ),
+            format!(
+                "Expansions: {}",
+                names(
+                    details
+                        .expansions
+                        .iter()
+                        .map(|e| format!("{:?}", e))
+                        .collect()
+                )
+            ),
+            format!(
+                "Roles: {}",
+                names(details.roles.iter().map(|r| format!("{:?}", r)).collect())
+            ),
+        ];
+        if let Some(win_rate) = details.win_rate {
+            facts.push(format!(
+                "With Big Money, beats Big Money: {:.1}%",
+                win_rate * 100.0
+            ));
+        }
+
+        let mut sections = vec![renderer.section("Card", &[renderer.list("Facts", &facts)])];
+        if !details.synergies.is_empty() {
+            let pairs: Vec<String> = details
+                .synergies
+                .iter()
+                .map(|synergy| {
+                    let partner = match &synergy.partner {
+                        synergy::Partner::Card(card) => spaces(card),
+                        synergy::Partner::Project(project) => spaces(project),
+                    };
+                    format!(
+                        "{:?}: {} + {} ({})",
+                        synergy.kind,
+                        spaces(&synergy.card),
+                        partner,
+                        synergy.why
+                    )
+                })
+                .collect();
+            sections.push(renderer.section("Synergies", &[renderer.list("Known pairs", &pairs)]));
+        }
+
+        renderer.document(&spaces(&details.card), &sections)
+    }
+
+    /// How many setups a config allows, as `count` prints it
+    pub fn count<R: Renderer + ?Sized>(
+        renderer: &R,
+        config: &SetupConfig,
+        count: &count::Count,
+    ) -> String {
+        let breakdown = vec![
+            format!("Cards to pick from: {}", count.pool),
+            format!("Sets of ten kingdom cards: {}", thousands(count.kingdoms)),
+            format!(
+                "With Young Witch: {}",
+                thousands(count.young_witch_kingdoms)
+            ),
+            format!(
+                "Counting each Young Witch bane: {}",
+                thousands(count.with_bane)
+            ),
+            format!("Ways to pick the projects: {}", thousands(count.projects)),
+            format!(
+                "Ways to place the custom bane cards: {}",
+                thousands(count.custom_banes)
+            ),
+        ];
+        let mut lists = vec![
+            renderer.list("Total", &[format!("{} setups", thousands(count.total))]),
+            renderer.list("Breakdown", &breakdown),
+        ];
+
+        let checks: Vec<&str> = [
+            (config.require_combo, "--require-combo"),
+            (config.avoid_swingy, "--avoid-swingy"),
+            (config.max_similarity.is_some(), "--max-similarity"),
+            (config.max_power.is_some(), "--max-power"),
+            (config.separate_top_cards, "--separate-top-cards"),
+        ]
+        .into_iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, flag)| flag)
+        .collect();
+        if !checks.is_empty() {
+            lists.push(renderer.list(
+                "Not counted",
+                &[format!(
+                    "Checked on each kingdom, so fewer pass: {}",
+                    checks.join(", ")
+                )],
+            ));
+        }
+
+        renderer.document("Kingdoms", &[renderer.section("Count", &lists)])
+    }
+
+    /// `1234567` as `1,234,567`
+    fn thousands(n: u128) -> String {
+        let digits = n.to_string();
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// What `validate` found wrong with a file, by the part it's in
+    pub fn validation<R: Renderer + ?Sized>(
+        renderer: &R,
+        title: &str,
+        problems: &[(String, Vec<String>)],
+    ) -> String {
+        if problems.iter().all(|(_, found)| found.is_empty()) {
+            return renderer.document(
+                title,
+                &[renderer.list("Valid", &["No problems found.".to_string()])],
+            );
+        }
+
+        let lists: Vec<String> = problems
+            .iter()
+            .filter(|(_, found)| !found.is_empty())
+            .map(|(part, found)| renderer.list(part, found))
+            .collect();
+        renderer.document(title, &[renderer.section("Problems", &lists)])
+    }
+
+    /// A kingdom card's name with what it's a bane for, e.g. `Young Witch`,
+    /// `Chapel (Bane)` or `Smithy (Zebra with Moat)`
+    pub fn card_label(card: &KC, setup: &Setup) -> String {
+        match setup.bane_cards.get(card) {
+            Some(BaneCard::Zebra) => format!(
+                "{} (Zebra with {})",
+                spaces(card),
+                setup.second_zebra.as_ref().map(spaces).unwrap_or_default()
+            ),
+            Some(bane) => format!("{} ({})", spaces(card), spaces(bane)),
+            None if setup.bane_card.as_ref() == Some(card) => format!("{} (Bane)", spaces(card)),
+            None => spaces(card),
+        }
+    }
+
+    fn by_expansion<T: Expansions + Ord + Clone>(
+        things: &[T],
+        label: impl Fn(&T) -> String,
+    ) -> Vec<(String, Vec<String>)> {
+        let mut groups: BTreeMap<String, Vec<T>> = BTreeMap::new();
+
+        for thing in things {
+            let exp = thing
+                .expansions()
+                .iter()
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+                .join("/");
+            groups.entry(exp).or_default().push(thing.clone());
+        }
+
+        groups
+            .into_iter()
+            .map(|(exp, mut things)| {
+                things.sort();
+                (exp, things.iter().map(&label).collect())
+            })
+            .collect()
+    }
+
+    fn rows<T: Ord + Debug + Hash>(hist: &Hist<T>) -> Vec<(String, usize)> {
+        hist.entries()
+            .into_iter()
+            .map(|(k, n)| (format!("{:?}", k), n))
+            .collect()
+    }
+
+    /// Render a setup (kingdom cards by expansion, projects, custom banes and
+    /// histograms) with any `Renderer`
+    ///
+    /// ```
+    /// use dominion::{gen_setup, SetupConfig};
+    /// use dominion::pretty::{render, Markdown};
+    /// let setup = gen_setup(SetupConfig::none()).unwrap();
+    /// assert!(render(&Markdown, "Game", &setup).starts_with("# Game"));
+    /// ```
+    pub fn render<R: Renderer>(renderer: &R, title: &str, setup: &Setup) -> String {
+        let mut sections = vec![];
+
+        let kingdom: Vec<_> = by_expansion(&setup.cards(), |card| card_label(card, setup))
+            .into_iter()
+            .map(|(exp, cards)| renderer.list(&exp, &cards))
+            .collect();
+        sections.push(renderer.section("Kingdom Cards", &kingdom));
+
+        if !setup.project_cards.is_empty() {
+            let projects: Vec<_> = by_expansion(&setup.project_cards, |p| spaces(p))
+                .into_iter()
+                .map(|(exp, projects)| renderer.list(&exp, &projects))
+                .collect();
+            sections.push(renderer.section("Project Cards", &projects));
+        }
+
+        if !setup.bane_cards.is_empty() {
+            let mut banes: Vec<_> = setup.bane_cards.keys().cloned().collect();
+            banes.sort();
+            let banes: Vec<_> = banes.iter().map(|card| card_label(card, setup)).collect();
+            sections.push(renderer.section(
+                "Custom Bane Cards",
+                &[renderer.list("Bane Expansion", &banes)],
+            ));
+        }
+
+        let hists = setup_hists(setup);
+        sections.push(renderer.section(
+            "Histograms",
+            &[
+                renderer.table("Cards' costs", &rows(&hists.costs)),
+                renderer.table("Cards' types", &rows(&hists.types)),
+                renderer.table("Expansions' cards", &rows(&hists.expansions)),
+            ],
+        ));
+
+        renderer.document(title, &sections)
+    }
+
+    /// Histograms of a setup's costs, types and expansions
+    #[derive(Serialize)]
+    pub struct Hists {
+        pub costs: Hist<u8>,
+        pub types: Hist<CardType>,
+        pub expansions: Hist<Expansion>,
+    }
+
+    pub fn setup_hists(setup: &Setup) -> Hists {
+        let cost_zeros = KC::iter()
+            .map(|c| Hist::n(c.base_cost(), 0))
+            .fold(Hist::empty(), |s, c| s + c);
+        let costs = setup
+            .cards()
+            .iter()
+            .fold(cost_zeros, |s, c| s + Hist::one(c.base_cost()));
+
+        let types_zeros = CardType::iter()
+            .map(|t| Hist::n(t, 0))
+            .fold(Hist::empty(), |s, c| s + c);
+        let types = setup.cards().iter().fold(types_zeros, |s, c| {
+            s + c
+                .card_types()
+                .iter()
+                .fold(Hist::empty(), |s, t| s + Hist::one(t.clone()))
+        });
+
+        let expansions = setup.cards().iter().fold(Hist::empty(), |s, c| {
+            s + c
+                .expansions()
+                .iter()
+                .fold(Hist::empty(), |s, e| s + Hist::one(e.clone()))
+        });
+
+        Hists {
+            costs,
+            types,
+            expansions,
+        }
+    }
+
+    pub fn hists(setup: &Setup) -> String {
+        let Hists {
+            costs,
+            types,
+            expansions,
+        } = setup_hists(setup);
+
+        format!(
+            "\
+Cards' Costs:
+-------------
+{}
+
+Cards' types:
+-------------
+{}
+
+Expansions' cards:
+-----------------
+{}
+
+",
+            costs.pretty(),
+            types.pretty(),
+            expansions.pretty()
+        )
+    }
+
+    pub fn analysis(analysis: &analysis::Analysis) -> String {
+        let score = |name: &str, score: u8| {
+            format!(
+                "{:<20}: {} ({}/10)\n",
+                name,
+                "■".repeat(score as usize),
+                score
+            )
+        };
+
+        let mut result = format!(
+            "Label: {:?}\n\n{}{}{}{}\n",
+            analysis.label,
+            score("Engine potential", analysis.engine_potential),
+            score("Attack intensity", analysis.attack_intensity),
+            score("Counterplay", analysis.counterplay),
+            score("Big Money viability", analysis.big_money_viability),
+        );
+        for reason in &analysis.reasons {
+            result.push_str(&format!(" - {}\n", reason));
+        }
+
+        result
+    }
+
+    /// The known combos, non-bos and swingy pairs in a setup
+    pub fn synergies(setup: &Setup) -> String {
+        let mut result = String::new();
+
+        for synergy in synergy::present(setup) {
+            let partner = match &synergy.partner {
+                synergy::Partner::Card(card) => spaces(card),
+                synergy::Partner::Project(project) => format!("{:?}", project),
+            };
+            result.push_str(&format!(
+                "{:?}: {} + {} ({})\n",
+                synergy.kind,
+                spaces(&synergy.card),
+                partner,
+                synergy.why
+            ));
+        }
+
+        result
+    }
+
+    /// Each bot's share of the wins in a simulation
+    pub fn simulation(simulation: &bots::Simulation) -> String {
+        let names: Vec<String> = simulation
+            .standings
+            .iter()
+            .map(|standing| standing.bot.to_string())
+            .collect();
+        let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+
+        let mut result = format!(
+            "Games: {} ({:.1} turns each on average, {:.1}% tied)\n\n",
+            simulation.games,
+            simulation.average_turns,
+            simulation.tie_rate * 100.0
+        );
+        for (name, standing) in names.iter().zip(&simulation.standings) {
+            let share = if simulation.games == 0 {
+                0.0
+            } else {
+                standing.wins / simulation.games as f64
+            };
+            result.push_str(&format!(
+                "{:<width$}: {} {:.1}% ({} wins, {:.1} VP)\n",
+                name,
+                "■".repeat((share * 20.0).round() as usize),
+                share * 100.0,
+                standing.wins,
+                standing.average_score,
+                width = width
+            ));
+        }
+
+        if let Some(standing) = simulation.dominant(bots::DOMINANCE) {
+            result.push_str(&format!("\n{} dominates this kingdom!\n", standing.bot));
+        }
+
+        for (name, standing) in names.iter().zip(&simulation.standings) {
+            // Per game, rounded, to keep the bars short
+            let per_game = standing
+                .buys
+                .entries()
+                .into_iter()
+                .map(|(card, n)| {
+                    let n = n as f64 / simulation.games.max(1) as f64;
+                    Hist::n(card.clone(), n.round() as usize)
+                })
+                .fold(Hist::empty(), |total, hist| total + hist);
+            result.push_str(&format!(
+                "\n{} buys per game:\n{}\n",
+                name,
+                per_game.pretty()
+            ));
+        }
+
+        result
+    }
+
+    /// One line of a replayed game, numbering players from 1
+    pub fn event(event: &engine::Event) -> String {
+        use engine::Event;
+
+        let cards = |cards: &[engine::Card]| {
+            if cards.is_empty() {
+                "nothing".to_string()
+            } else {
+                cards
+                    .iter()
+                    .map(|card| card.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+
+        match event {
+            Event::Shuffle { player, seed } => {
+                format!("Player {} shuffles (seed {})", player + 1, seed)
+            }
+            Event::Turn { player, turn } => {
+                format!("── Turn {}: player {} ──", turn + 1, player + 1)
+            }
+            Event::Decision { decision, choice } => {
+                let source = decision
+                    .source
+                    .as_ref()
+                    .map(|card| format!(" for {}", card))
+                    .unwrap_or_default();
+                format!(
+                    "Player {} picks {} ({:?}{})",
+                    decision.player + 1,
+                    cards(choice),
+                    decision.kind,
+                    source
+                )
+            }
+            Event::Gain { player, card } => format!("Player {} gains {}", player + 1, card),
+            Event::Trash { player, card } => format!("Player {} trashes {}", player + 1, card),
+            Event::Score { player, score } => {
+                format!("Player {} now has {} VP", player + 1, score)
+            }
+            Event::End { scores } => format!(
+                "Game over: {}",
+                scores
+                    .iter()
+                    .enumerate()
+                    .map(|(player, score)| format!("player {} {} VP", player + 1, score))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// The `S.standard [...]`-style code for a setup, readable by
+    /// `setup_from_code`
+    pub fn format_setup(setup: &Setup) -> String {
+        match (&setup.bane_card, setup.project_cards.len()) {
+            (None, 0) => format!("S.standard {:?}", setup.kingdom_cards),
+            (Some(bane), 0) => format!("S.bane {:?} {:?}", bane, setup.kingdom_cards),
+            (None, _) => format!(
+                "S.standardWithProjects {:?} {:?}",
+                setup.project_cards, setup.kingdom_cards
+            ),
+            (Some(bane), _) => format!(
+                "S.baneWithProjects {:?} {:?} {:?}",
+                bane, setup.project_cards, setup.kingdom_cards
+            ),
+        }
+    }
+
+    /// Read a setup back out of the `setup = ...` part of a history code (see
+    /// `code`). Custom bane cards aren't part of the code so they don't come
+    /// back.
+    ///
+    ///```
+    ///use dominion::{pretty, KC, Project};
+    ///let setup = pretty::setup_from_code("S.baneWithProjects Bandit [Fleet] [Chapel, YoungWitch]").unwrap();
+    ///assert_eq!(setup.bane_card, Some(KC::Bandit));
+    ///assert_eq!(setup.project_cards, vec![Project::Fleet]);
+    ///assert_eq!(setup.kingdom_cards, vec![KC::Chapel, KC::YoungWitch]);
+    ///```
+    pub fn setup_from_code(code: &str) -> Option<Setup> {
+        let code = &code[code.find("S.")? + 2..];
+        let (constructor, rest) = code.split_once(' ')?;
+
+        let (bane_card, rest) = if constructor.starts_with("bane") {
+            let (bane, rest) = rest.trim_start().split_once(' ')?;
+            (Some(bane.parse().ok()?), rest)
+        } else {
+            (None, rest)
+        };
+
+        let mut lists = rest.split('[').skip(1).map(|list| {
+            list.split(']')
+                .next()
+                .unwrap_or("")
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+        });
+
+        let project_cards = if constructor.ends_with("WithProjects") {
+            lists
+                .next()?
+                .into_iter()
+                .map(|p| p.parse().ok())
+                .collect::<Option<Vec<_>>>()?
+        } else {
+            vec![]
+        };
+
+        let kingdom_cards = lists
+            .next()?
+            .into_iter()
+            .map(|kc| kc.parse().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Setup::new(
+            kingdom_cards,
+            bane_card,
+            project_cards,
+            HashMap::new(),
+            None,
+        ))
+    }
+
+    pub fn gen_error(err: GenSetupError) -> String {
+        match err {
+            GenSetupError::CouldNotSatisfyProjectsFromExpansions => {
+                "The requested project count could not be satisfied! Ensure you're not specifying expansions which preclude projects.".to_string()
+            }
+
+            GenSetupError::CouldNotSatisfyKingdomCards => "Could not pick 10 kingdom cards! Ensure your filters don't over-limit cards.".to_string(),
+
+            GenSetupError::CouldNotSatisfyBaneCard => "Could not pick a bane card! Ensure your filters don't over-limit cards.".to_string(),
+
+            GenSetupError::CouldNotSatisfySecondZebra => "Could not pick a second zebra card! Ensure your filters don't over-limit cards.".to_string(),
+
+            GenSetupError::IntersectingCardBansAndIncludes(cards) => format!("I can't ban and include cards! The following exist in the ban and include lists: {:?}", cards),
+
+            GenSetupError::TooManyCardsIncluded => "Too many cards were asked to be included! I currently can't generate a kingdom with more than 10 cards.".to_string(),
+
+            GenSetupError::SlotNotInSetup(slot) => format!("Can't reroll {:?}, the setup doesn't have it!", slot),
+
+            GenSetupError::CouldNotSatisfySynergies => "Could not find a kingdom with the requested synergies! Ensure your filters leave room for a known combo, or that included cards don't form a swingy pair.".to_string(),
+
+            GenSetupError::CouldNotSatisfyMaxSimilarity => "Every kingdom I tried was too much like one you've already played! Try raising the maximum similarity.".to_string(),
+
+            GenSetupError::CouldNotSatisfyPower => "Every kingdom I tried was too strong! Try raising the maximum power, or allowing top cards together.".to_string(),
+
+            GenSetupError::ImpossibleExpansionCount(expansion) => format!("{:?} can't give the number of cards asked for! Check its count against the cards it has, the banned cards, the included ones and the other expansions' counts (the minimums have to fit in 10 cards).", expansion),
+
+            GenSetupError::CouldNotSatisfyExpansionCounts => "Could not pick a kingdom meeting every expansion's count! Ensure the minimums fit in 10 cards and the maximums leave room for 10.".to_string(),
+
+            GenSetupError::InvalidWeight => "Weights must be numbers from 0 up! Infinite weights and NaN can't be weighed against the others.".to_string(),
+        }
+    }
+
+    pub fn draft_error(err: draft::DraftError) -> String {
+        match err {
+            draft::DraftError::BadPlayerCount(n) => format!("Can't draft with {} players! Pick-two-of-five needs room for two picks per player.", n),
+
+            draft::DraftError::NotYourTurn { expected, got } => format!("It's player {}'s turn, not player {}'s!", expected + 1, got + 1),
+
+            draft::DraftError::WrongAction(action) => format!("{:?} isn't allowed in this draft format!", action),
+
+            draft::DraftError::NotInHand(card) => format!("{} isn't in your hand!", spaces(card)),
+
+            draft::DraftError::AlreadyFinished => "The draft is already over!".to_string(),
+
+            draft::DraftError::NotFinished => "The draft isn't over yet!".to_string(),
+
+            draft::DraftError::Setup(err) => gen_error(err),
+        }
+    }
+
+    pub fn log_error(err: gamelog::LogError) -> String {
+        match err {
+            gamelog::LogError::Empty => "The log is empty!".to_string(),
+
+            gamelog::LogError::Parse { line, message } => {
+                format!("Line {} of the log isn't valid: {}", line, message)
+            }
+
+            gamelog::LogError::Version(version) => format!(
+                "The log is version {}, I can only read version {}!",
+                version,
+                gamelog::LOG_VERSION
+            ),
+
+            gamelog::LogError::InvalidSetup(problems) => format!(
+                "The log's setup breaks the rules of setting up!\n\n{}",
+                problems
+                    .into_iter()
+                    .map(setup_error)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+
+            gamelog::LogError::Engine(err) => engine_error(err),
+
+            gamelog::LogError::Mismatch {
+                line,
+                expected,
+                got,
+            } => {
+                let show = |event: Option<Box<engine::Event>>| {
+                    event
+                        .map(|e| self::event(&e))
+                        .unwrap_or_else(|| "the end of the game".to_string())
+                };
+                format!("The game went differently at line {} of the log! Expected \"{}\" but got \"{}\".", line, show(expected), show(got))
+            }
+        }
+    }
+
+    pub fn engine_error(err: engine::EngineError) -> String {
+        match err {
+            engine::EngineError::UnsupportedCard(card) => format!(
+                "I can't play {} yet! Only Base2 kingdom cards are supported.",
+                spaces(&card)
+            ),
+
+            engine::EngineError::UnsupportedSetup => {
+                "I can't play projects or custom bane cards yet!".to_string()
+            }
+
+            engine::EngineError::BadPlayerCount(n) => format!(
+                "Can't play with {} players! Games are for 1 to 4 players.",
+                n
+            ),
+
+            engine::EngineError::NotInSupply(card) => format!(
+                "{} isn't in the kingdom, so no bot can buy it!",
+                spaces(&card)
+            ),
+
+            engine::EngineError::Suspended => "The game is waiting on a decision.".to_string(),
+
+            engine::EngineError::NotYourDecision(seat) => {
+                format!("It's not player {}'s decision!", seat + 1)
+            }
+
+            engine::EngineError::InvalidChoice { decision, choice } => format!(
+                "{:?} isn't a valid answer to {:?} (pick {} to {} of {:?})!",
+                choice, decision.kind, decision.min, decision.max, decision.options
+            ),
+
+            engine::EngineError::GameOver => "The game is already over!".to_string(),
+        }
+    }
+
+    pub fn setup_error(err: SetupError) -> String {
+        match err {
+            SetupError::BaneCost(card) => format!(
+                "{} can't be the bane! Young Witch's bane costs 2 or 3.",
+                spaces(&card)
+            ),
+
+            SetupError::BaneCardNotInKingdom(card) => format!(
+                "{} has a bane card but isn't in the kingdom!",
+                spaces(&card)
+            ),
+
+            SetupError::KingdomCardCount(count) => {
+                format!("A kingdom has 10 cards, not {}!", count)
+            }
+
+            SetupError::DuplicateCard(card) => {
+                format!("{} is in the kingdom more than once!", spaces(&card))
+            }
+
+            SetupError::MissingBane => {
+                "Young Witch is in the kingdom, but there's no bane!".to_string()
+            }
+
+            SetupError::UnneededBane(card) => {
+                format!("{} is the bane, but there's no Young Witch!", spaces(&card))
+            }
+
+            SetupError::MissingSecondZebra => {
+                "There's a Zebra bane card, but no second Zebra card!".to_string()
+            }
+
+            SetupError::UnneededSecondZebra(card) => format!(
+                "{} is the second Zebra card, but there's no Zebra bane card!",
+                spaces(&card)
+            ),
+
+            SetupError::SecondZebraInKingdom(card) => format!(
+                "{} can't be the second Zebra card, it's already in the kingdom!",
+                spaces(&card)
+            ),
+
+            SetupError::TooManyProjects(count) => {
+                format!("A kingdom has at most 2 projects, not {}!", count)
+            }
+        }
+    }
+
+    pub fn config_error(err: config::ConfigError) -> String {
+        match err {
+            config::ConfigError::Parse(message) => format!("That's not a config!\n\n{}", message),
+
+            config::ConfigError::UnknownPreset { name, known } => format!(
+                "There's no preset called {}! Try one of: {}",
+                name,
+                known.join(", ")
+            ),
+        }
+    }
+
+    /// The players' totals, highest first, with what scored
+    pub fn scores(scores: &[scoring::Score]) -> String {
+        let mut ranked: Vec<&scoring::Score> = scores.iter().collect();
+        ranked.sort_by_key(|score| -score.total);
+        let width = scores
+            .iter()
+            .map(|score| score.name.len())
+            .max()
+            .unwrap_or(0);
+
+        ranked
+            .into_iter()
+            .map(|score| {
+                let mut parts: Vec<String> = score
+                    .cards
+                    .iter()
+                    .map(|(card, points)| format!("{} {}", spaces(card), points))
+                    .collect();
+                if score.vp_tokens != 0 {
+                    parts.push(format!("VP tokens {}", score.vp_tokens));
+                }
+                format!(
+                    "{:<width$}  {:>3}  ({})\n",
+                    score.name,
+                    score.total,
+                    parts.join(", "),
+                    width = width
+                )
+            })
+            .collect()
+    }
+
+    pub fn rating_error(err: history::RatingError) -> String {
+        match err {
+            history::RatingError::OutOfRange(rating) => format!(
+                "Can't rate a kingdom {}! Ratings go from 1 to {}.",
+                rating,
+                history::MAX_RATING
+            ),
+            history::RatingError::NotPlayed(Some(name)) => {
+                format!("No kingdom called \"{}\" in the history log!", name)
+            }
+            history::RatingError::NotPlayed(None) => {
+                "The history log has no kingdoms to rate yet!".to_string()
+            }
+        }
+    }
+
+    pub fn scoring_error(err: scoring::ScoringError) -> String {
+        match err {
+            scoring::ScoringError::NotInKingdom(card) => format!(
+                "{} isn't in the kingdom, so nobody can have it!",
+                spaces(&card)
+            ),
+        }
+    }
+}
+
+pub mod hist {
+    use serde::{Serialize, Serializer};
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::hash::Hash;
+    use std::ops::Add;
+
+    /// A histogram for counting instances
+    #[derive(Debug, Clone)]
+    pub struct Hist<T> {
+        hist: HashMap<T, usize>,
+    }
+
+    impl<T> Hist<T> {
+        /// No values counted, the empty `Hist`
+        ///
+        /// ```
+        /// let c: dominion::hist::Hist<&str> = dominion::hist::Hist::empty();
+        /// assert_eq!(c.count(&"hi"), 0);
+        /// ```
+        pub fn empty() -> Self {
+            Hist {
+                hist: HashMap::new(),
+            }
+        }
+    }
+
+    impl<T: Ord + Debug + Hash> Hist<T> {
+        /// Values and their counts, ordered by value
+        ///
+        /// ```
+        /// use dominion::hist::Hist;
+        /// let c = Hist::one("car") + Hist::n("bus", 2);
+        /// assert_eq!(c.entries(), vec![(&"bus", 2), (&"car", 1)]);
+        /// ```
+        pub fn entries(&self) -> Vec<(&T, usize)> {
+            let mut entries: Vec<_> = self.hist.iter().map(|(k, n)| (k, *n)).collect();
+            entries.sort();
+            entries
+        }
+
+        pub fn pretty(self: &Self) -> String {
+            let mut keys: Vec<_> = self.hist.keys().collect();
+            keys.sort();
+            let displayed_keys = keys.iter().map(|k| format!("{:?}", k));
+            displayed_keys
+                .max_by_key(|k| k.len())
+                .map(|k| k.len())
+                .map(|key_length| {
+                    keys.iter()
+                        .map(|k| {
+                            let n = self.count(k);
+                            let k = format!("{:?}", k);
+                            format!("{:key_length$}: {:■<n$} ({})", k, "", n)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or(String::new())
+        }
+    }
+
+    impl<T: Eq + Hash> Hist<T> {
+        /// Count a single instance
+        ///
+        /// ```
+        /// let c = dominion::hist::Hist::one("car".to_string());
+        /// assert_eq!(c.count(&"car".to_string()), 1);
+        /// ```
+        pub fn one(v: T) -> Self {
+            Hist::n(v, 1)
+        }
+
+        /// Count `n` instances
+        ///
+        /// ```
+        /// let c = dominion::hist::Hist::n("car".to_string(), 5);
+        /// assert_eq!(c.count(&"car".to_string()), 5);
+        /// ```
+        pub fn n(v: T, n: usize) -> Self {
+            Hist {
+                hist: HashMap::from([(v, n)]),
+            }
+        }
+
+        /// Get element count
+        pub fn count(self: &Self, v: &T) -> usize {
+            self.hist.get(&v).unwrap_or(&0).clone()
+        }
+    }
+
+    impl<T: Eq + Hash> PartialEq for Hist<T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.hist == other.hist
+        }
+    }
+
+    impl<T: Ord + Serialize> Serialize for Hist<T> {
+        /// Serialize as a map from value to count, ordered by value
+        ///
+        /// ```
+        /// use dominion::hist::Hist;
+        /// let c = Hist::one("car") + Hist::n("bus", 2);
+        /// assert_eq!(serde_json::to_string(&c).unwrap(), r#"{"bus":2,"car":1}"#);
+        /// ```
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.hist.iter().collect::<BTreeMap<_, _>>())
+        }
+    }
+
+    impl<T: Clone + Eq + Hash> Add<Hist<T>> for Hist<T> {
+        type Output = Hist<T>;
+
+        /// Merge counts of elements
+        ///
+        /// ```
+        /// use dominion::hist::Hist;
+        /// let c = Hist::one("car".to_string()) + Hist::one("truck".to_string()) + Hist::one("car".to_string());
+        /// assert_eq!(c.count(&"car".to_string()), 2);
+        /// assert_eq!(c.count(&"truck".to_string()), 1);
+        /// assert_eq!(c.count(&"bus".to_string()), 0);
+        /// ```
+        fn add(self: Hist<T>, other: Hist<T>) -> Hist<T> {
+            let mut hist = self.hist.clone();
+
+            for (v, count) in other.hist {
+                hist.entry(v).and_modify(|c| *c += count).or_insert(count);
+            }
+
+            Hist { hist }
+        }
+    }
+}
+
+pub mod game_name {
+    use super::*;
+    use rand::seq::SliceRandom;
+
+    /// E.g. "The Witch's Bane"
+    fn random_the_cards_blank(card: &KC) -> String {
+        format!("The {:?} of the {}", card, random_end())
+    }
+
+    /// E.g. "The Bane of the Witch"
+    fn random_the_blank_of_the_card(card: &KC) -> String {
+        format!("The {} of the {:?}", random_end(), card)
+    }
+
+    /// E.g. "The Witch and the Village"
+    fn random_the_card_and_the_card(card1: &KC, card2: &KC) -> String {
+        format!("The {:?} and the {:?}", card1, card2)
+    }
+
+    /// E.g. "The Witch's Village"
+    fn random_the_cards_card(card1: &KC, card2: &KC) -> String {
+        format!("The {:?}'s {:?}", card1, card2)
+    }
+
+    /// E.g. "The Witch of the Forest"
+    fn random_the_card_of_the_place(card: &KC) -> String {
+        format!("The {:?} of the {}", card, random_place())
+    }
+
+    /// E.g. "The End of the Forest"
+    fn random_the_blank_of_the_place() -> String {
+        format!("The {} of the {}", random_end(), random_place())
+    }
+
+    fn random_end() -> String {
+        let ends = vec![
+            "Adventure",
+            "Apprentice",
+            "Bane",
+            "Banishment",
+            "Captivity",
+            "Cleansing",
+            "Coffers",
+            "Coins",
+            "Copper",
+            "Crown",
+            "Curse",
+            "Deceit",
+            "Defeat",
+            "Demise",
+            "Destiny",
+            "Dismissal",
+            "End",
+            "Enigma",
+            "Entry",
+            "Err",
+            "Execution",
+            "Exit",
+            "Failing",
+            "Fate",
+            "Favor",
+            "Fettering",
+            "Flight",
+            "Foresight",
+            "Fortune",
+            "Game",
+            "Gauntlet",
+            "Help",
+            "Incident",
+            "Journey",
+            "Killing",
+            "Kinship",
+            "Loss",
+            "Love",
+            "Mystery",
+            "Nocturne",
+            "Overreach",
+            "Peace",
+            "Plight",
+            "Poverty",
+            "Prudence",
+            "Punishment",
+            "Quickening",
+            "Relief",
+            "Repose",
+            "Sacking",
+            "Screams",
+            "Surrender",
+            "Tell",
+            "Termination",
+            "Treasure",
+            "Triumph",
+            "Turn",
+            "Turning",
+            "Unfettering",
+            "Victory",
+            "Wealth",
+            "Winning",
+            "Yearning",
+            "Yells",
+            "Zeal",
+        ];
+
+        ends.choose(&mut rand::thread_rng()).unwrap().to_string()
+    }
+
+    fn random_place() -> String {
+        let places = vec![
+            "Battlefield",
+            "Battlement",
+            "Castle",
+            "Dungeon",
+            "Field",
+            "Forest",
+            "Kingdom",
+            "Mountain",
+            "Palace",
+            "Pit",
+            "Sea",
+            "Sky",
+            "Tower",
+            "Town",
+            "Village",
+            "Waste",
+            "Woods",
+        ];
+
+        places.choose(&mut rand::thread_rng()).unwrap().to_string()
+    }
+
+    pub fn random(setup: &Setup) -> String {
+        let all_cards = setup.cards();
+        let cards: Vec<_> = all_cards
+            .choose_multiple(&mut rand::thread_rng(), 2)
+            .collect();
+
+        let card1 = cards.get(0).unwrap();
+        let card2 = cards.get(1).unwrap();
+
+        let names = vec![
+            random_the_cards_blank(card1),
+            random_the_blank_of_the_card(card1),
+            random_the_card_and_the_card(card1, card2),
+            random_the_cards_card(card1, card2),
+            random_the_card_of_the_place(card1),
+            random_the_blank_of_the_place(),
+        ];
+
+        names.choose(&mut rand::thread_rng()).unwrap().to_string()
+    }
+}
+
+pub mod history {
+    use super::*;
+    use chrono::prelude::*;
+
+    /// A kingdom that was played (or at least set up), one per line of the
+    /// history log
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct Played {
+        pub name: String,
+        pub at: String,
+        pub setup: Setup,
+        #[serde(default)]
+        pub rating: Option<u8>,
+
+        /// Everyone's final tally, once the game was scored
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub scores: Vec<scoring::Score>,
+    }
+
+    impl Played {
+        /// A kingdom played right now
+        pub fn now(name: String, setup: Setup) -> Self {
+            Played {
+                name,
+                at: Local::now().to_rfc3339(),
+                setup,
+                rating: None,
+                scores: vec![],
+            }
+        }
+    }
+
+    /// Format a played kingdom as a single history log line
+    pub fn line(played: &Played) -> String {
+        format!("{}\n", serde_json::to_string(played).unwrap())
+    }
+
+    /// How many kingdoms in the history each card appeared in
+    pub fn card_plays(history: &[Played]) -> HashMap<KC, usize> {
+        let mut plays = HashMap::new();
+
+        for played in history {
+            for card in played.setup.cards() {
+                *plays.entry(card).or_insert(0) += 1;
+            }
+        }
+
+        plays
+    }
+
+    /// The average rating of the rated kingdoms each card appeared in
+    pub fn card_ratings(history: &[Played]) -> HashMap<KC, f64> {
+        let mut totals: HashMap<KC, (f64, usize)> = HashMap::new();
+
+        for played in history {
+            if let Some(rating) = played.rating {
+                for card in played.setup.cards() {
+                    let total = totals.entry(card).or_insert((0.0, 0));
+                    total.0 += rating as f64;
+                    total.1 += 1;
+                }
+            }
+        }
+
+        totals
+            .into_iter()
+            .map(|(card, (sum, count))| (card, sum / count as f64))
+            .collect()
+    }
+
+    /// Read a whole history log, skipping blank lines
+    ///
+    /// ```
+    /// let log = "\n{\"name\":\"G\",\"at\":\"today\",\"setup\":{\"kingdom_cards\":[\"Smithy\"],\"bane_card\":null,\"project_cards\":[],\"bane_cards\":{},\"second_zebra\":null}}\n";
+    /// let played = dominion::history::parse(log).unwrap();
+    /// assert_eq!(played.len(), 1);
+    /// assert_eq!(played[0].rating, None);
+    /// ```
+    pub fn parse(log: &str) -> Result<Vec<Played>, serde_json::Error> {
+        log.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect()
+    }
+
+    /// The best rating a kingdom can get; the worst is 1
+    pub const MAX_RATING: u8 = 5;
+
+    /// Things that can go wrong rating a kingdom
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum RatingError {
+        /// Ratings go from 1 to `MAX_RATING`
+        OutOfRange(u8),
+
+        /// No kingdom in the history has the name, or with no name, the
+        /// history is empty
+        NotPlayed(Option<String>),
+    }
+
+    /// Rate the last kingdom in the history named `name`, or the last one
+    /// of all without a name, returning its name
+    ///
+    /// ```
+    /// use dominion::history::{rate, Played};
+    /// use dominion::{Setup, KC};
+    /// use std::collections::HashMap;
+    /// let setup = Setup::new(vec![KC::Smithy], None, vec![], HashMap::new(), None);
+    /// let mut history = vec![
+    ///     Played::now("Monday".to_string(), setup.clone()),
+    ///     Played::now("Friday".to_string(), setup),
+    /// ];
+    /// assert_eq!(rate(&mut history, None, 4), Ok("Friday".to_string()));
+    /// assert_eq!(rate(&mut history, Some("Monday"), 2), Ok("Monday".to_string()));
+    /// assert_eq!((history[0].rating, history[1].rating), (Some(2), Some(4)));
+    /// ```
+    pub fn rate(
+        history: &mut [Played],
+        name: Option<&str>,
+        rating: u8,
+    ) -> Result<String, RatingError> {
+        if !(1..=MAX_RATING).contains(&rating) {
+            return Err(RatingError::OutOfRange(rating));
+        }
+
+        let played = history
+            .iter_mut()
+            .rev()
+            .find(|played| name.is_none_or(|name| played.name == name))
+            .ok_or_else(|| RatingError::NotPlayed(name.map(String::from)))?;
+        played.rating = Some(rating);
+
+        Ok(played.name.clone())
+    }
+}
+
+pub mod interactive {
+    use super::*;
+
+    /// Something to do to the kingdom being built
+    #[derive(Debug, PartialEq, Clone)]
+    pub enum Command {
+        /// Swap out a single slot
+        Reroll(Slot),
+        /// Keep a card through regenerating (swapped in for an unlocked card
+        /// if it isn't there yet)
+        Lock(KC),
+        /// Let a card go again
+        Unlock(KC),
+        /// Never use a card, rerolling it if it's in the kingdom
+        Ban(KC),
+        /// Add or remove an expansion from the pool, rerolling the unlocked
+        /// cards and projects only a removed expansion had
+        ToggleExpansion(Expansion),
+        /// Change the number of projects and regenerate
+        Projects(ProjectCount),
+        /// Change the number of custom bane cards and regenerate
+        Banes(BaneCount),
+        /// Regenerate everything that isn't locked
+        Regenerate,
+        /// Write the kingdom to the history log
+        Save,
+        /// Print the history code
+        Code,
+        Help,
+        Quit,
+    }
+
+    impl FromStr for Command {
+        type Err = String;
+
+        /// Parse a command from a line like `r Smithy` or `q`
+        ///
+        ///```
+        ///use dominion::interactive::Command;
+        ///use dominion::{Slot, KC};
+        ///assert_eq!("r Smithy".parse(), Ok(Command::Reroll(Slot::KingdomCard(KC::Smithy))));
+        ///assert_eq!("q".parse(), Ok(Command::Quit));
+        ///```
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let mut words = s.split_whitespace();
+            let key = words.next().unwrap_or("");
+            let arg = words.next().unwrap_or("");
+            let bad = |what: &str| format!("{} is not a {}", arg, what);
+
+            match key {
+                "r" => arg.parse().map(Command::Reroll),
+                "l" => arg.parse().map(Command::Lock).map_err(|_| bad("card")),
+                "u" => arg.parse().map(Command::Unlock).map_err(|_| bad("card")),
+                "b" => arg.parse().map(Command::Ban).map_err(|_| bad("card")),
+                "e" => arg
+                    .parse()
+                    .map(Command::ToggleExpansion)
+                    .map_err(|_| bad("expansion")),
+                "p" => arg
+                    .parse()
+                    .map(Command::Projects)
+                    .map_err(|_| bad("project count")),
+                "n" => arg
+                    .parse()
+                    .map(Command::Banes)
+                    .map_err(|_| bad("bane count")),
+                "g" => Ok(Command::Regenerate),
+                "s" => Ok(Command::Save),
+                "c" => Ok(Command::Code),
+                "?" | "h" => Ok(Command::Help),
+                "q" => Ok(Command::Quit),
+                _ => Err(format!("Unknown command {:?}, try ?", key)),
+            }
+        }
+    }
+
+    pub const HELP: &str = "\
+r <card|project|Bane|SecondZebra>  reroll one slot
+l <card>                           lock a card (kept when regenerating)
+u <card>                           unlock a card
+b <card>                           ban a card
+e <expansion>                      toggle an expansion
+p <0-2>                            set the project count and regenerate
+n <0-3>                            set the bane count and regenerate
+g                                  regenerate everything not locked
+s                                  save to the history log
+c                                  print the history code
+q                                  quit";
+
+    /// A kingdom being built up interactively. Locked cards live in the
+    /// config's `include_cards` so regenerating keeps them.
+    pub struct Session {
+        pub config: SetupConfig,
+        pub setup: Setup,
+    }
+
+    impl Session {
+        pub fn new(config: SetupConfig) -> Result<Self, GenSetupError> {
+            let setup = gen_setup(config.clone())?;
+            Ok(Session { config, setup })
+        }
+
+        pub fn locked(&self) -> HashSet<KC> {
+            self.config.include_cards.clone().unwrap_or_default()
+        }
+
+        /// Apply a command that changes the kingdom. The IO ones (`Save`,
+        /// `Code`, `Help` and `Quit`) are left for the caller and do nothing
+        /// here. On error the session is left as it was.
+        pub fn apply(&mut self, command: Command) -> Result<(), GenSetupError> {
+            let mut config = self.config.clone();
+            let mut locked = self.locked();
+            let mut banned = config.ban_cards.clone().unwrap_or_default();
+
+            let setup = match command {
+                Command::Reroll(slot) => {
+                    if let Slot::KingdomCard(card) = &slot {
+                        locked.remove(card);
+                    }
+                    config.include_cards = Some(locked);
+                    reroll(&self.setup, slot, config.clone())?
+                }
+                Command::Lock(card) => {
+                    banned.remove(&card);
+                    locked.insert(card.clone());
+                    config.ban_cards = Some(banned);
+                    config.include_cards = Some(locked.clone());
+                    if self.setup.cards().contains(&card) {
+                        self.setup.clone()
+                    } else {
+                        let unlocked: Vec<&KC> = self
+                            .setup
+                            .kingdom_cards
+                            .iter()
+                            .filter(|kc| !locked.contains(kc))
+                            .collect();
+                        let old = unlocked
+                            .choose(&mut rand::thread_rng())
+                            .ok_or(GenSetupError::TooManyCardsIncluded)?;
+                        swap_in(&self.setup, old, &card, &config)?
+                    }
+                }
+                Command::Unlock(card) => {
+                    locked.remove(&card);
+                    config.include_cards = Some(locked);
+                    self.setup.clone()
+                }
+                Command::Ban(card) => {
+                    locked.remove(&card);
+                    banned.insert(card.clone());
+                    config.ban_cards = Some(banned);
+                    config.include_cards = Some(locked);
+                    let slot = if self.setup.bane_card.as_ref() == Some(&card) {
+                        Some(Slot::Bane)
+                    } else if self.setup.second_zebra.as_ref() == Some(&card) {
+                        Some(Slot::SecondZebra)
+                    } else if self.setup.kingdom_cards.contains(&card) {
+                        Some(Slot::KingdomCard(card))
+                    } else {
+                        None
+                    };
+                    match slot {
+                        Some(slot) => reroll(&self.setup, slot, config.clone())?,
+                        None => self.setup.clone(),
+                    }
+                }
+                Command::ToggleExpansion(expansion) => {
+                    let mut expansions = desired_expansions(&config);
+                    if !expansions.remove(&expansion) {
+                        expansions.insert(expansion);
+                    }
+                    config.include_expansions = Some(expansions.clone());
+
+                    let gone = |kc: &KC| {
+                        expansion_set(kc).is_disjoint(&expansions) && !locked.contains(kc)
+                    };
+                    let mut setup = self.setup.clone();
+                    let leaving: Vec<KC> = setup
+                        .kingdom_cards
+                        .iter()
+                        .filter(|kc| gone(kc))
+                        .cloned()
+                        .collect();
+                    for card in leaving {
+                        setup = reroll(&setup, Slot::KingdomCard(card), config.clone())?;
+                    }
+                    if setup.bane_card.as_ref().is_some_and(gone) {
+                        setup = reroll(&setup, Slot::Bane, config.clone())?;
+                    }
+                    if setup.second_zebra.as_ref().is_some_and(gone) {
+                        setup = reroll(&setup, Slot::SecondZebra, config.clone())?;
+                    }
+                    let leaving: Vec<Project> = setup
+                        .project_cards
+                        .iter()
+                        .filter(|project| expansion_set(*project).is_disjoint(&expansions))
+                        .cloned()
+                        .collect();
+                    for project in leaving {
+                        setup = reroll(&setup, Slot::Project(project), config.clone())?;
+                    }
+                    setup
+                }
+                Command::Projects(count) => {
+                    config.project_count = Some(count);
+                    gen_setup(config.clone())?
+                }
+                Command::Banes(count) => {
+                    config.bane_count = Some(count);
+                    gen_setup(config.clone())?
+                }
+                Command::Regenerate => gen_setup(config.clone())?,
+                Command::Save | Command::Code | Command::Help | Command::Quit => self.setup.clone(),
+            };
+
+            self.config = config;
+            self.setup = setup;
+
+            Ok(())
+        }
+
+        /// The kingdom, what's locked, the pool and live histograms
+        pub fn render(&self) -> String {
+            let mut locked: Vec<_> = self.locked().into_iter().collect();
+            locked.sort();
+            let mut expansions: Vec<_> = desired_expansions(&self.config).into_iter().collect();
+            expansions.sort();
+
+            format!(
+                "{}\nLocked: {:?}\nExpansions: {:?}\nProjects: {}, Banes: {}\n\n{}",
+                pretty::pretty(&self.setup),
+                locked,
+                expansions,
+                self.config
+                    .project_count
+                    .as_ref()
+                    .map(|c| c.count().to_string())
+                    .unwrap_or_else(|| "random".to_string()),
+                self.config
+                    .bane_count
+                    .as_ref()
+                    .map(|c| c.count())
+                    .unwrap_or(0),
+                pretty::hists(&self.setup)
+            )
+        }
+    }
+}
+
+pub mod draft {
+    use super::*;
+
+    /// How many candidates each player is dealt
+    pub const HAND_SIZE: usize = 5;
+
+    /// How players shape the kingdom together
+    #[derive(Debug, PartialEq, Clone, EnumString, EnumIter, Serialize, Deserialize)]
+    pub enum DraftFormat {
+        /// All hands form one pool and players pick one card at a time, the
+        /// order reversing every round, until the kingdom is full
+        Snake,
+
+        /// Each player vetoes one card from their hand, everything else goes
+        /// into the kingdom
+        VetoOne,
+
+        /// Each player picks two cards from their hand of five
+        PickTwoOfFive,
+    }
+
+    /// A single move in a draft
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum Action {
+        Pick(KC),
+        Veto(KC),
+    }
+
+    impl FromStr for Action {
+        type Err = String;
+
+        /// Parse an action from a line like `pick Smithy` or `veto Witch`
+        ///
+        ///```
+        ///use dominion::draft::Action;
+        ///use dominion::KC;
+        ///assert_eq!("pick Smithy".parse(), Ok(Action::Pick(KC::Smithy)));
+        ///assert_eq!("veto Witch".parse(), Ok(Action::Veto(KC::Witch)));
+        ///```
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (action, card) = s
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| format!("Expected `pick CARD` or `veto CARD`, got {:?}", s))?;
+            let card = card
+                .trim()
+                .parse()
+                .map_err(|_| format!("{} is not a kingdom card", card.trim()))?;
+
+            match action {
+                "pick" => Ok(Action::Pick(card)),
+                "veto" => Ok(Action::Veto(card)),
+                _ => Err(format!("Expected pick or veto, got {}", action)),
+            }
+        }
+    }
+
+    /// Things that can go wrong while drafting
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum DraftError {
+        /// Needs at least one player, and pick-two-of-five can't seat more
+        /// players than the kingdom has room for
+        BadPlayerCount(usize),
+
+        /// Someone acted out of turn
+        NotYourTurn { expected: usize, got: usize },
+
+        /// Picked in a veto draft or vetoed in a pick draft
+        WrongAction(Action),
+
+        /// The card isn't in the acting player's hand
+        NotInHand(KC),
+
+        /// Everyone has already had their say
+        AlreadyFinished,
+
+        /// Asked for the setup before the draft was over
+        NotFinished,
+
+        /// The drafted cards and the config don't make a valid setup
+        Setup(GenSetupError),
+    }
+
+    /// A draft in progress. It's plain data so it can be handed back and forth
+    /// (e.g. to the site) between moves.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Draft {
+        pub format: DraftFormat,
+        pub config: SetupConfig,
+        pub hands: Vec<Vec<KC>>,
+        pub picked: Vec<KC>,
+        pub vetoed: Vec<KC>,
+    }
+
+    impl Draft {
+        /// Deal hands from the cards `config` allows
+        pub fn new(
+            config: SetupConfig,
+            players: usize,
+            format: DraftFormat,
+        ) -> Result<Self, DraftError> {
+            let forced = config.include_cards.clone().unwrap_or_default();
+
+            if forced.len() > 10 {
+                return Err(DraftError::Setup(GenSetupError::TooManyCardsIncluded));
+            }
+            if players == 0
+                || (format == DraftFormat::PickTwoOfFive && forced.len() + 2 * players > 10)
+            {
+                return Err(DraftError::BadPlayerCount(players));
+            }
+
+            let desired_expansions = desired_expansions(&config);
+            let banned_cards = config.ban_cards.clone().unwrap_or_default();
+
+            let mut candidates: Vec<KC> = KC::iter()
+                .filter(|kc| !expansion_set(kc).is_disjoint(&desired_expansions))
+                .filter(|kc| !banned_cards.contains(kc))
+                .filter(|kc| !forced.contains(kc))
+                .collect();
+
+            if candidates.len() < players * HAND_SIZE {
+                return Err(DraftError::Setup(
+                    GenSetupError::CouldNotSatisfyKingdomCards,
+                ));
+            }
+
+            candidates.shuffle(&mut rand::thread_rng());
+
+            Ok(Draft {
+                format,
+                config,
+                hands: candidates
+                    .chunks(HAND_SIZE)
+                    .take(players)
+                    .map(|hand| hand.to_vec())
+                    .collect(),
+                picked: vec![],
+                vetoed: vec![],
+            })
+        }
+
+        pub fn players(&self) -> usize {
+            self.hands.len()
+        }
+
+        fn open_slots(&self) -> usize {
+            10usize.saturating_sub(self.config.include_cards.as_ref().map_or(0, |c| c.len()))
+        }
+
+        /// Whose turn it is, or `None` once the draft is over
+        pub fn to_act(&self) -> Option<usize> {
+            let players = self.players();
+
+            match self.format {
+                DraftFormat::Snake => {
+                    let pool =
+                        self.hands.iter().map(|h| h.len()).sum::<usize>() + self.picked.len();
+                    let picks = self.picked.len();
+
+                    if picks >= self.open_slots().min(pool) {
+                        return None;
+                    }
+
+                    let (round, seat) = (picks / players, picks % players);
+                    Some(if round % 2 == 0 {
+                        seat
+                    } else {
+                        players - 1 - seat
+                    })
+                }
+                DraftFormat::VetoOne => Some(self.vetoed.len()).filter(|&p| p < players),
+                DraftFormat::PickTwoOfFive => Some(self.picked.len())
+                    .filter(|&p| p < 2 * players)
+                    .map(|p| p % players),
+            }
+        }
+
+        /// The cards the given player may choose from
+        pub fn hand(&self, player: usize) -> Vec<KC> {
+            match self.format {
+                DraftFormat::Snake => self.hands.concat(),
+                _ => self.hands.get(player).cloned().unwrap_or_default(),
+            }
+        }
+
+        /// Make a move on behalf of `player`
+        pub fn act(&mut self, player: usize, action: Action) -> Result<(), DraftError> {
+            let expected = self.to_act().ok_or(DraftError::AlreadyFinished)?;
+
+            if player != expected {
+                return Err(DraftError::NotYourTurn {
+                    expected,
+                    got: player,
+                });
+            }
+
+            let card = match (&self.format, &action) {
+                (DraftFormat::VetoOne, Action::Veto(card)) => card.clone(),
+                (DraftFormat::Snake | DraftFormat::PickTwoOfFive, Action::Pick(card)) => {
+                    card.clone()
+                }
+                _ => return Err(DraftError::WrongAction(action)),
+            };
+
+            let hand = self
+                .hands
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| self.format == DraftFormat::Snake || *i == player)
+                .map(|(_, hand)| hand)
+                .find(|hand| hand.contains(&card))
+                .ok_or_else(|| DraftError::NotInHand(card.clone()))?;
+            hand.retain(|c| c != &card);
+
+            match action {
+                Action::Pick(_) => self.picked.push(card),
+                Action::Veto(_) => self.vetoed.push(card),
+            }
+
+            Ok(())
+        }
+
+        /// Turn the finished draft into a setup: picks (and, when vetoing,
+        /// whatever survived) are included, vetoes are banned and the rest
+        /// (filler cards, Young Witch bane, projects) is generated as usual
+        pub fn finish(&self) -> Result<Setup, DraftError> {
+            if self.to_act().is_some() {
+                return Err(DraftError::NotFinished);
+            }
+
+            let mut drafted = self.picked.clone();
+            if self.format == DraftFormat::VetoOne {
+                drafted.extend(self.hands.concat());
+            }
+            drafted.truncate(self.open_slots());
+
+            let mut config = self.config.clone();
+            let mut includes = config.include_cards.unwrap_or_default();
+            includes.extend(drafted);
+            config.include_cards = Some(includes);
+            let mut bans = config.ban_cards.unwrap_or_default();
+            bans.extend(self.vetoed.iter().cloned());
+            config.ban_cards = Some(bans);
+
+            gen_setup(config).map_err(DraftError::Setup)
+        }
+    }
+}
+
+pub mod json {
+    //! The stable, machine-readable output of the CLI's `--json`. Fields are
+    //! only ever added; removing or changing the meaning of one bumps
+    //! `SCHEMA_VERSION`.
+
+    use super::*;
+    use std::collections::BTreeMap;
+
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    /// A generated (or rerolled, drafted, evolved) setup
+    #[derive(Serialize)]
+    pub struct SetupOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        /// The kingdom's name, when one was asked for
+        pub name: Option<String>,
+
+        /// The seed the setup was generated with. Only plain generation is
+        /// seeded, so this is `null` for rerolls, drafts and evolutions.
+        pub seed: Option<u64>,
+
+        /// The config the setup was made with
+        pub config: &'a SetupConfig,
+
+        pub setup: &'a Setup,
+
+        /// Counts of the setup's costs, types and expansions (same as the
+        /// `--hists` output)
+        pub hists: pretty::Hists,
+    }
+
+    /// A setup that couldn't be made, or anything else a subcommand failed at
+    #[derive(Serialize)]
+    pub struct ErrorOutput<E = GenSetupError> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub error: E,
+
+        /// A human readable version of `error`
+        pub message: String,
+    }
+
+    pub fn setup(
+        name: Option<String>,
+        seed: Option<u64>,
+        config: &SetupConfig,
+        setup: &Setup,
+    ) -> String {
+        serde_json::to_string_pretty(&SetupOutput {
+            version: SCHEMA_VERSION,
+            name,
+            seed,
+            config,
+            setup,
+            hists: pretty::setup_hists(setup),
+        })
+        .unwrap()
+    }
+
+    /// Bots played against each other on a setup
+    #[derive(Serialize)]
+    pub struct SimulationOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        /// Game `i` was played with seed `seed + i`
+        pub seed: u64,
+
+        pub setup: &'a Setup,
+        pub simulation: &'a bots::Simulation,
+
+        /// The bot winning more than `bots::DOMINANCE` of the games, if any
+        pub dominant: Option<&'a bots::Bot>,
+    }
+
+    pub fn simulation(seed: u64, setup: &Setup, simulation: &bots::Simulation) -> String {
+        serde_json::to_string_pretty(&SimulationOutput {
+            version: SCHEMA_VERSION,
+            seed,
+            setup,
+            simulation,
+            dominant: simulation
+                .dominant(bots::DOMINANCE)
+                .map(|standing| &standing.bot),
+        })
+        .unwrap()
+    }
+
+    /// A game scored from the players' final decks
+    #[derive(Serialize)]
+    pub struct ScoresOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub setup: &'a Setup,
+
+        /// In the order the decks were given
+        pub scores: &'a [scoring::Score],
+    }
+
+    pub fn scores(setup: &Setup, scores: &[scoring::Score]) -> String {
+        serde_json::to_string_pretty(&ScoresOutput {
+            version: SCHEMA_VERSION,
+            setup,
+            scores,
+        })
+        .unwrap()
+    }
+
+    /// Cards, expansions, projects or banes, from `list`
+    #[derive(Serialize)]
+    pub struct ListingOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub listing: catalog::Listing,
+        pub filter: &'a catalog::Filter,
+        pub entries: &'a [catalog::Entry],
+    }
+
+    pub fn listing(
+        listing: catalog::Listing,
+        filter: &catalog::Filter,
+        entries: &[catalog::Entry],
+    ) -> String {
+        serde_json::to_string_pretty(&ListingOutput {
+            version: SCHEMA_VERSION,
+            listing,
+            filter,
+            entries,
+        })
+        .unwrap()
+    }
+
+    /// One card, from `show`
+    #[derive(Serialize)]
+    pub struct CardOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub card: &'a catalog::Details,
+    }
+
+    pub fn card(details: &catalog::Details) -> String {
+        serde_json::to_string_pretty(&CardOutput {
+            version: SCHEMA_VERSION,
+            card: details,
+        })
+        .unwrap()
+    }
+
+    /// How many setups a config allows, from `count`
+    #[derive(Serialize)]
+    pub struct CountOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub config: &'a SetupConfig,
+        pub count: &'a count::Count,
+    }
+
+    pub fn count(config: &SetupConfig, count: &count::Count) -> String {
+        serde_json::to_string_pretty(&CountOutput {
+            version: SCHEMA_VERSION,
+            config,
+            count,
+        })
+        .unwrap()
+    }
+
+    /// The kingdoms from the history log most like a setup, from `nearest`
+    #[derive(Serialize)]
+    pub struct NearestOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub setup: &'a Setup,
+
+        /// Most alike first
+        pub neighbours: &'a [similarity::Neighbour<'a>],
+    }
+
+    pub fn nearest(setup: &Setup, neighbours: &[similarity::Neighbour]) -> String {
+        serde_json::to_string_pretty(&NearestOutput {
+            version: SCHEMA_VERSION,
+            setup,
+            neighbours,
+        })
+        .unwrap()
+    }
+
+    /// A game log that replays the same, from `replay`
+    #[derive(Serialize)]
+    pub struct ReplayOutput<'a> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub header: &'a gamelog::Header,
+
+        /// The events before the first turn, then each turn's
+        pub turns: Vec<&'a [engine::Event]>,
+    }
+
+    pub fn replay(header: &gamelog::Header, events: &[engine::Event]) -> String {
+        serde_json::to_string_pretty(&ReplayOutput {
+            version: SCHEMA_VERSION,
+            header,
+            turns: gamelog::turns(events),
+        })
+        .unwrap()
+    }
+
+    /// What `validate` found
+    #[derive(Serialize)]
+    pub struct ValidationOutput<'a, E: Serialize> {
+        /// Always `SCHEMA_VERSION`
+        pub version: u32,
+
+        pub valid: bool,
+
+        /// The problems with each part of the file (the setup, or a
+        /// config's defaults and each of its presets), by part
+        pub problems: BTreeMap<&'a str, Vec<ProblemOutput<E>>>,
+    }
+
+    #[derive(Serialize)]
+    pub struct ProblemOutput<E: Serialize> {
+        pub error: E,
+
+        /// A human readable version of `error`
+        pub message: String,
+    }
+
+    pub fn validation<E: Serialize + Clone>(
+        problems: &[(String, Vec<E>)],
+        message: impl Fn(E) -> String,
+    ) -> String {
+        serde_json::to_string_pretty(&ValidationOutput {
+            version: SCHEMA_VERSION,
+            valid: problems.iter().all(|(_, errors)| errors.is_empty()),
+            problems: problems
+                .iter()
+                .map(|(part, errors)| {
+                    (
+                        part.as_str(),
+                        errors
+                            .iter()
+                            .map(|error| ProblemOutput {
+                                message: message(error.clone()),
+                                error: error.clone(),
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    /// ```
+    /// let json = dominion::json::error(dominion::GenSetupError::TooManyCardsIncluded);
+    /// assert!(json.contains(r#""error": "TooManyCardsIncluded""#));
+    /// ```
+    pub fn error(error: GenSetupError) -> String {
+        failure(error.clone(), pretty::gen_error(error))
+    }
+
+    /// `error` for the other ways a subcommand fails: a `DraftError`,
+    /// `EngineError`, `ScoringError`, `LogError`, `ConfigError`, or a name
+    /// for a file that couldn't be read
+    ///
+    /// ```
+    /// let json = dominion::json::failure("UnreadableFile", "Could not read x".to_string());
+    /// assert!(json.contains(r#""error": "UnreadableFile""#));
+    /// ```
+    pub fn failure<E: Serialize>(error: E, message: String) -> String {
+        serde_json::to_string_pretty(&ErrorOutput {
+            version: SCHEMA_VERSION,
+            error,
+            message,
+        })
+        .unwrap()
+    }
+}
+
+pub mod config {
+    //! Defaults for generation that would otherwise be passed as flags every
+    //! time: a config file (TOML, or JSON when the path ends in `.json`) and
+    //! named presets.
+    //!
+    //! A `SetupConfig` is built up in layers, each only setting what it
+    //! mentions, later layers winning:
+    //!
+    //! 1. nothing set (`SetupConfig::none()`)
+    //! 2. the config file's top level
+    //! 3. the chosen preset, from the file or built in (`builtin()`); a
+    //!    preset in the file replaces a built-in one of the same name
+    //! 4. the command line flags
+    //!
+    //! Lists (expansions, bans, includes) and weights are replaced by a later
+    //! layer, not added to. The switches (`require_combo`, `avoid_swingy`,
+    //! `separate_top_cards`, `equal_expansions`) can be turned off again by a
+    //! later layer, e.g. `--no-avoid-swingy` under the `casual` preset.
+
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Some of a `SetupConfig`, as one layer of settings. Anything left
+    /// `None` falls through to the layer below.
+    #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+    pub struct Preset {
+        pub include_expansions: Option<HashSet<Expansion>>,
+        pub ban_cards: Option<HashSet<KC>>,
+        pub include_cards: Option<HashSet<KC>>,
+        pub project_count: Option<ProjectCount>,
+        pub bane_count: Option<BaneCount>,
+        pub require_combo: Option<bool>,
+        pub avoid_swingy: Option<bool>,
+        pub max_similarity: Option<f64>,
+        pub max_power: Option<f64>,
+        pub separate_top_cards: Option<bool>,
+        pub expansion_weights: Option<HashMap<Expansion, f64>>,
+        pub card_weights: Option<HashMap<KC, f64>>,
+        pub equal_expansions: Option<bool>,
+        pub expansion_counts: Option<HashMap<Expansion, ExpansionCount>>,
+        pub min_per_expansion: Option<usize>,
+    }
+
+    impl Preset {
+        /// This layer with `over` on top of it
+        pub fn then(self, over: Preset) -> Preset {
+            Preset {
+                include_expansions: over.include_expansions.or(self.include_expansions),
+                ban_cards: over.ban_cards.or(self.ban_cards),
+                include_cards: over.include_cards.or(self.include_cards),
+                project_count: over.project_count.or(self.project_count),
+                bane_count: over.bane_count.or(self.bane_count),
+                require_combo: over.require_combo.or(self.require_combo),
+                avoid_swingy: over.avoid_swingy.or(self.avoid_swingy),
+                max_similarity: over.max_similarity.or(self.max_similarity),
+                max_power: over.max_power.or(self.max_power),
+                separate_top_cards: over.separate_top_cards.or(self.separate_top_cards),
+                expansion_weights: over.expansion_weights.or(self.expansion_weights),
+                card_weights: over.card_weights.or(self.card_weights),
+                equal_expansions: over.equal_expansions.or(self.equal_expansions),
+                expansion_counts: over.expansion_counts.or(self.expansion_counts),
+                min_per_expansion: over.min_per_expansion.or(self.min_per_expansion),
+            }
+        }
+
+        /// The `SetupConfig` with everything unset left as in
+        /// `SetupConfig::none()`
+        pub fn config(self) -> SetupConfig {
+            SetupConfig {
+                include_expansions: self.include_expansions,
+                ban_cards: self.ban_cards,
+                include_cards: self.include_cards,
+                project_count: self.project_count,
+                bane_count: self.bane_count,
+                require_combo: self.require_combo.unwrap_or(false),
+                avoid_swingy: self.avoid_swingy.unwrap_or(false),
+                max_similarity: self.max_similarity,
+                max_power: self.max_power,
+                separate_top_cards: self.separate_top_cards.unwrap_or(false),
+                expansion_weights: self.expansion_weights,
+                card_weights: self.card_weights,
+                equal_expansions: self.equal_expansions.unwrap_or(false),
+                expansion_counts: self.expansion_counts,
+                min_per_expansion: self.min_per_expansion,
+            }
+        }
+    }
+
+    /// The presets that come without a config file
+    pub fn builtin() -> BTreeMap<String, Preset> {
+        BTreeMap::from([
+            (
+                // Nothing too swingy or lopsided, and no projects to explain
+                "casual".to_string(),
+                Preset {
+                    project_count: Some(ProjectCount::NoProjects),
+                    bane_count: Some(BaneCount::NoBanes),
+                    avoid_swingy: Some(true),
+                    separate_top_cards: Some(true),
+                    ..Preset::default()
+                },
+            ),
+            (
+                // For teaching: Base2 only, with a combo to find
+                "intro-friends".to_string(),
+                Preset {
+                    include_expansions: Some(HashSet::from([Expansion::Base2])),
+                    project_count: Some(ProjectCount::NoProjects),
+                    bane_count: Some(BaneCount::NoBanes),
+                    require_combo: Some(true),
+                    avoid_swingy: Some(true),
+                    separate_top_cards: Some(true),
+                    ..Preset::default()
+                },
+            ),
+        ])
+    }
+
+    /// A config file: defaults at the top level, presets under `[presets.NAME]`
+    #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+    pub struct ConfigFile {
+        #[serde(flatten)]
+        pub defaults: Preset,
+
+        #[serde(default)]
+        pub presets: BTreeMap<String, Preset>,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum ConfigError {
+        /// The file isn't a config
+        Parse(String),
+
+        /// No preset has this name
+        UnknownPreset { name: String, known: Vec<String> },
+    }
+
+    impl ConfigFile {
+        /// Parse a TOML config, or a JSON one when `json`
+        ///
+        /// ```
+        /// use dominion::{config::ConfigFile, Expansion};
+        /// let file = ConfigFile::parse("include_expansions = [\"Base2\"]\n[presets.quick]\nproject_count = 0\n", false).unwrap();
+        /// assert_eq!(file.defaults.include_expansions, Some([Expansion::Base2].into_iter().collect()));
+        /// assert!(file.presets.contains_key("quick"));
+        /// ```
+        pub fn parse(contents: &str, json: bool) -> Result<ConfigFile, ConfigError> {
+            let value: serde_json::Value = if json {
+                serde_json::from_str(contents).map_err(|e| ConfigError::Parse(e.to_string()))?
+            } else {
+                toml::from_str(contents).map_err(|e| ConfigError::Parse(e.to_string()))?
+            };
+
+            // Flattening lets unknown keys through, and a misspelt setting
+            // shouldn't be quietly ignored
+            let known = serde_json::to_value(Preset::default()).unwrap();
+            let known = known.as_object().unwrap();
+            let unknown = |table: &serde_json::Value, presets: bool| {
+                table.as_object().and_then(|table| {
+                    table
+                        .keys()
+                        .find(|key| !(known.contains_key(*key) || presets && *key == "presets"))
+                        .cloned()
+                })
+            };
+            let mut tables = vec![(&value, true)];
+            if let Some(presets) = value.get("presets").and_then(|p| p.as_object()) {
+                tables.extend(presets.values().map(|preset| (preset, false)));
+            }
+            if let Some(key) = tables
+                .into_iter()
+                .find_map(|(table, top)| unknown(table, top))
+            {
+                return Err(ConfigError::Parse(format!("unknown setting `{}`", key)));
+            }
+
+            serde_json::from_value(value).map_err(|e| ConfigError::Parse(e.to_string()))
+        }
+
+        /// Every preset, the file's replacing built-in ones
+        pub fn presets(&self) -> BTreeMap<String, Preset> {
+            let mut presets = builtin();
+            presets.extend(self.presets.clone());
+            presets
+        }
+
+        /// Layer the file's defaults, the preset called `preset` and `flags`
+        pub fn resolve(
+            &self,
+            preset: Option<&str>,
+            flags: Preset,
+        ) -> Result<SetupConfig, ConfigError> {
+            let preset = match preset {
+                None => Preset::default(),
+                Some(name) => {
+                    let presets = self.presets();
+                    presets
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| ConfigError::UnknownPreset {
+                            name: name.to_string(),
+                            known: presets.keys().cloned().collect(),
+                        })?
+                }
+            };
+
+            Ok(self.defaults.clone().then(preset).then(flags).config())
+        }
+    }
+}
+
+pub mod svg {
+    //! A printable one-page sheet of a setup: a grid of card tiles with name,
+    //! cost, type colours, expansion and bane/project notes.
+
+    use super::pretty::{escape, spaces as name};
+    use super::*;
+
+    /// Paper to lay the sheet out for. Sizes are in millimetres.
+    #[derive(Debug, PartialEq, Clone, EnumString, EnumIter, Serialize, Deserialize)]
+    pub enum PaperSize {
+        A4,
+        Letter,
+    }
+
+    impl PaperSize {
+        /// Width and height in millimetres (portrait)
+        pub fn size(&self) -> (f64, f64) {
+            match self {
+                PaperSize::A4 => (210.0, 297.0),
+                PaperSize::Letter => (215.9, 279.4),
+            }
+        }
+    }
+
+    const MARGIN: f64 = 12.0;
+    const GAP: f64 = 6.0;
+    const COLUMNS: usize = 3;
+    const TITLE_HEIGHT: f64 = 16.0;
+
+    fn type_colour(card_type: &CardType) -> &'static str {
+        match card_type {
+            CardType::Action => "#e8dcc0",
+            CardType::Attack => "#c8553d",
+            CardType::Reaction => "#3d85c6",
+            CardType::Victory => "#6aa84f",
+            CardType::Treasure => "#f1c232",
+            CardType::Duration => "#e69138",
+        }
+    }
+
+    /// One tile's worth of information
+    struct Tile {
+        name: String,
+        cost: u8,
+        colours: Vec<&'static str>,
+        expansions: String,
+        note: Option<String>,
+    }
+
+    fn expansions_text<T: Expansions>(v: &T) -> String {
+        v.expansions()
+            .iter()
+            .map(|e| format!("{:?}", e))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    fn card_tile(card: &KC, note: Option<String>) -> Tile {
+        Tile {
+            name: name(card),
+            cost: card.base_cost(),
+            colours: card.card_types().iter().map(type_colour).collect(),
+            expansions: expansions_text(card),
+            note,
+        }
+    }
+
+    fn tiles(setup: &Setup) -> Vec<Tile> {
+        let mut cards = setup.cards();
+        cards.sort();
+
+        let mut tiles: Vec<Tile> = cards
+            .iter()
+            .map(|card| {
+                let note = match setup.bane_cards.get(card) {
+                    Some(BaneCard::Zebra) => Some(format!(
+                        "Zebra with {}",
+                        setup.second_zebra.as_ref().map(name).unwrap_or_default()
+                    )),
+                    Some(bane) => Some(name(bane)),
+                    None if setup.bane_card.as_ref() == Some(card) => {
+                        Some("Bane (Young Witch)".to_string())
+                    }
+                    None => None,
+                };
+                card_tile(card, note)
+            })
+            .collect();
+
+        tiles.extend(
+            setup
+                .second_zebra
+                .iter()
+                .map(|card| card_tile(card, Some("Second Zebra".to_string()))),
+        );
+
+        let mut projects = setup.project_cards.clone();
+        projects.sort();
+
+        tiles.extend(projects.iter().map(|project| Tile {
+            name: name(project),
+            cost: project.base_cost(),
+            colours: vec!["#d5a6bd"],
+            expansions: expansions_text(project),
+            note: Some("Project".to_string()),
+        }));
+
+        tiles
+    }
+
+    fn tile_svg(tile: &Tile, x: f64, y: f64, width: f64, height: f64) -> String {
+        let band_height = 6.0;
+        let band_width = width / tile.colours.len().max(1) as f64;
+
+        let bands: String = tile
+            .colours
+            .iter()
+            .enumerate()
+            .map(|(i, colour)| {
+                format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>\n",
+                    x + i as f64 * band_width,
+                    y + height - band_height,
+                    band_width,
+                    band_height,
+                    colour
+                )
+            })
+            .collect();
+
+        let note = tile
+            .note
+            .as_ref()
+            .map(|note| {
+                format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"3.5\" font-style=\"italic\">{}</text>\n",
+                    x + 4.0,
+                    y + height - band_height - 9.0,
+                    escape(note)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            "\
+<g class=\"tile\">
+<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{height:.1}\" rx=\"2\" fill=\"white\" stroke=\"#25140a\" stroke-width=\"0.4\"/>
+{bands}<circle cx=\"{coin_x:.1}\" cy=\"{coin_y:.1}\" r=\"5\" fill=\"#f1c232\" stroke=\"#7f6000\" stroke-width=\"0.4\"/>
+<text x=\"{coin_x:.1}\" y=\"{coin_text_y:.1}\" font-size=\"5\" font-weight=\"bold\" text-anchor=\"middle\">{cost}</text>
+<text x=\"{text_x:.1}\" y=\"{name_y:.1}\" font-size=\"5\" font-weight=\"bold\">{name}</text>
+{note}<text x=\"{text_x:.1}\" y=\"{expansion_y:.1}\" font-size=\"3\" fill=\"#8c7474\">{expansions}</text>
+</g>
+",
+            coin_x = x + 8.0,
+            coin_y = y + 9.0,
+            coin_text_y = y + 10.8,
+            cost = tile.cost,
+            text_x = x + 4.0,
+            name_y = y + 22.0,
+            name = escape(&tile.name),
+            expansion_y = y + height - band_height - 3.0,
+            expansions = escape(&tile.expansions),
+        )
+    }
+
+    /// Lay a setup out as an SVG sheet sized for `paper`
+    ///
+    /// ```
+    /// use dominion::{gen_setup, SetupConfig};
+    /// use dominion::svg::{sheet, PaperSize};
+    /// let setup = gen_setup(SetupConfig::none()).unwrap();
+    /// let svg = sheet(&setup, "Game", &PaperSize::A4);
+    /// assert!(svg.contains("width=\"210mm\" height=\"297mm\""));
+    /// ```
+    pub fn sheet(setup: &Setup, title: &str, paper: &PaperSize) -> String {
+        let (paper_width, paper_height) = paper.size();
+        let tiles = tiles(setup);
+        let rows = tiles.len().div_ceil(COLUMNS);
+
+        let width = (paper_width - 2.0 * MARGIN - (COLUMNS - 1) as f64 * GAP) / COLUMNS as f64;
+        let height =
+            ((paper_height - 2.0 * MARGIN - TITLE_HEIGHT - (rows.max(1) - 1) as f64 * GAP)
+                / rows.max(1) as f64)
+                .min(width * 0.75);
+
+        let tiles: String = tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| {
+                let (row, column) = (i / COLUMNS, i % COLUMNS);
+                tile_svg(
+                    tile,
+                    MARGIN + column as f64 * (width + GAP),
+                    MARGIN + TITLE_HEIGHT + row as f64 * (height + GAP),
+                    width,
+                    height,
+                )
+            })
+            .collect();
+
+        format!(
+            "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" font-family=\"serif\">
+<rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>
+<text x=\"{margin}\" y=\"{title_y}\" font-size=\"8\" font-weight=\"bold\">{title}</text>
+{tiles}</svg>
+",
+            w = paper_width,
+            h = paper_height,
+            margin = MARGIN,
+            title_y = MARGIN + 8.0,
+            title = escape(title),
+            tiles = tiles,
+        )
+    }
+}
+
+pub mod analysis {
+    //! What kind of game a kingdom is likely to be, from its cards' roles,
+    //! costs and types. Every score is out of 10; they're rules of thumb to
+    //! argue over, not a simulation.
+
+    use super::*;
+
+    /// The kind of game a kingdom tends towards
+    #[derive(Debug, PartialEq, Clone, EnumIter, Serialize, Deserialize)]
+    pub enum Label {
+        /// Build up villages, draw and +Buy to play many cards a turn
+        Engine,
+
+        /// Buy treasures and one or two good terminals, then green
+        BigMoney,
+
+        /// Junk flies and can't be trashed quickly, so decks stay bloated
+        Slog,
+
+        /// Nothing dominates, both engines and money can work
+        Mixed,
+    }
+
+    /// How a kingdom sizes up
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Analysis {
+        /// Villages, draw, +Buy and trashing available (out of 10)
+        pub engine_potential: u8,
+
+        /// How much the attacks will hurt (out of 10)
+        pub attack_intensity: u8,
+
+        /// How well the attacks can be blocked or recovered from (out of 10)
+        pub counterplay: u8,
+
+        /// How well Big Money with a terminal or two does (out of 10)
+        pub big_money_viability: u8,
+
+        pub label: Label,
+
+        /// Why the scores and label came out the way they did
+        pub reasons: Vec<String>,
+    }
+
+    /// A card (or project) in the setup, by name, with its cost and roles
+    struct Piece {
+        name: String,
+        cost: u8,
+        roles: Vec<Role>,
+        attack: bool,
+        treasure: bool,
+    }
+
+    fn pieces(setup: &Setup) -> Vec<Piece> {
+        let mut cards = setup.cards();
+        cards.extend(setup.second_zebra.iter().cloned());
+        cards.sort();
+
+        let mut pieces: Vec<Piece> = cards
+            .into_iter()
+            .map(|card| Piece {
+                name: format!("{:?}", card),
+                cost: card.base_cost(),
+                roles: card.roles(),
+                attack: card.card_types().contains(&CardType::Attack),
+                treasure: card.card_types().contains(&CardType::Treasure),
+            })
+            .collect();
+
+        let mut projects = setup.project_cards.clone();
+        projects.sort();
+        pieces.extend(projects.into_iter().map(|project| Piece {
+            name: format!("{:?}", project),
+            cost: project.base_cost(),
+            roles: project.roles(),
+            attack: false,
+            treasure: false,
+        }));
+
+        pieces
+    }
+
+    fn with(pieces: &[Piece], role: Role) -> Vec<&Piece> {
+        pieces.iter().filter(|p| p.roles.contains(&role)).collect()
+    }
+
+    fn names(pieces: &[&Piece]) -> String {
+        pieces
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Score a setup and label it
+    pub fn analyze(setup: &Setup) -> Analysis {
+        let pieces = pieces(setup);
+        let mut reasons = vec![];
+
+        let villages = with(&pieces, Role::Village);
+        let thrones = with(&pieces, Role::Throne);
+        let draw = with(&pieces, Role::Draw);
+        let buys = with(&pieces, Role::PlusBuy);
+        let trashers = with(&pieces, Role::Trasher);
+        let cheap_trashers: Vec<&Piece> =
+            trashers.iter().cloned().filter(|p| p.cost <= 4).collect();
+        let junkers = with(&pieces, Role::Junker);
+        let handsize = with(&pieces, Role::HandsizeAttack);
+        let defenses = with(&pieces, Role::Defense);
+        let enablers = with(&pieces, Role::BigMoney);
+        let attacks: Vec<&Piece> = pieces.iter().filter(|p| p.attack).collect();
+
+        let mut engine = 0;
+        if !villages.is_empty() {
+            engine += 3;
+            reasons.push(format!("Villages: {}", names(&villages)));
+        } else if !thrones.is_empty() {
+            engine += 2;
+            reasons.push(format!(
+                "No villages, but {} can play actions twice",
+                names(&thrones)
+            ));
+        } else {
+            reasons.push("No villages, so terminals will collide".to_string());
+        }
+        if !draw.is_empty() {
+            engine += 3;
+            reasons.push(format!("Draw: {}", names(&draw)));
+        } else {
+            reasons.push("No draw".to_string());
+        }
+        if !buys.is_empty() {
+            engine += 2;
+            reasons.push(format!("+Buy: {}", names(&buys)));
+        } else {
+            reasons.push("No +Buy, one gain a turn".to_string());
+        }
+        if !cheap_trashers.is_empty() {
+            engine += 2;
+            reasons.push(format!("Trashing to open with: {}", names(&cheap_trashers)));
+        } else if !trashers.is_empty() {
+            engine += 1;
+            reasons.push(format!(
+                "Trashing only from pricier cards: {}",
+                names(&trashers)
+            ));
+        } else {
+            reasons.push("No trashing".to_string());
+        }
+
+        let attack_intensity: usize = pieces
+            .iter()
+            .map(|p| {
+                if p.roles.contains(&Role::Junker) {
+                    4
+                } else if p.roles.contains(&Role::HandsizeAttack) {
+                    3
+                } else if p.attack {
+                    1
+                } else {
+                    0
+                }
+            })
+            .sum();
+        if !junkers.is_empty() {
+            reasons.push(format!("Junking: {}", names(&junkers)));
+        }
+        if !handsize.is_empty() {
+            reasons.push(format!("Handsize attacks: {}", names(&handsize)));
+        }
+        let other_attacks: Vec<&Piece> = attacks
+            .iter()
+            .cloned()
+            .filter(|p| {
+                !p.roles.contains(&Role::Junker) && !p.roles.contains(&Role::HandsizeAttack)
+            })
+            .collect();
+        if !other_attacks.is_empty() {
+            reasons.push(format!("Milder attacks: {}", names(&other_attacks)));
+        }
+
+        let mut counterplay = 3 * defenses.len();
+        if !junkers.is_empty() {
+            counterplay += 2 * trashers.len();
+        }
+        if !handsize.is_empty() {
+            counterplay += draw.len();
+        }
+        if !attacks.is_empty() || !junkers.is_empty() {
+            if defenses.is_empty() {
+                reasons.push("Nothing defends against the attacks".to_string());
+            } else {
+                reasons.push(format!("Defense: {}", names(&defenses)));
+            }
+        }
+
+        let mut big_money = 3;
+        if !enablers.is_empty() {
+            big_money += if enablers.len() > 1 { 5 } else { 4 };
+            reasons.push(format!("Big Money enablers: {}", names(&enablers)));
+        } else {
+            reasons.push("No strong Big Money enabler".to_string());
+        }
+        if pieces
+            .iter()
+            .any(|p| p.treasure && p.roles.contains(&Role::Payload))
+        {
+            big_money += 2;
+        }
+
+        let engine_potential = engine.min(10) as u8;
+        let attack_intensity = attack_intensity.min(10) as u8;
+        let counterplay = counterplay.min(10) as u8;
+        let big_money_viability = big_money.min(10) as u8;
+
+        let (label, why) =
+            if !junkers.is_empty() && cheap_trashers.is_empty() && attack_intensity >= 4 {
+                (
+                    Label::Slog,
+                    "Junk with no cheap trashing will bloat every deck".to_string(),
+                )
+            } else if engine_potential >= 7 && engine_potential > big_money_viability {
+                (
+                    Label::Engine,
+                    format!(
+                        "Engine potential ({}) beats Big Money viability ({})",
+                        engine_potential, big_money_viability
+                    ),
+                )
+            } else if big_money_viability >= 7 && big_money_viability > engine_potential {
+                (
+                    Label::BigMoney,
+                    format!(
+                        "Big Money viability ({}) beats engine potential ({})",
+                        big_money_viability, engine_potential
+                    ),
+                )
+            } else {
+                (
+                    Label::Mixed,
+                    format!(
+                        "Neither engines ({}) nor Big Money ({}) stand out",
+                        engine_potential, big_money_viability
+                    ),
+                )
+            };
+        reasons.insert(0, why);
+
+        Analysis {
+            engine_potential,
+            attack_intensity,
+            counterplay,
+            big_money_viability,
+            label,
+            reasons,
+        }
+    }
+}
+
+pub mod synergy {
+    //! Famous pairs of cards (and projects) that play well, badly or wildly
+    //! together. The table is curated by hand; add to `SYNERGIES` to teach
+    //! the generator a new one.
+
+    use super::*;
+
+    /// How a pair plays together
+    #[derive(Debug, PartialEq, Clone, EnumIter, Serialize, Deserialize)]
+    pub enum Kind {
+        /// Better together than apart
+        Combo,
+
+        /// Worse together than apart
+        NonBo,
+
+        /// Games with both tend to be decided by who gets it going first
+        Swingy,
+    }
+
+    /// The second half of a pair
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum Partner {
+        Card(KC),
+        Project(Project),
+    }
+
+    /// A known pair and why it's known
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    pub struct Synergy {
+        pub card: KC,
+        pub partner: Partner,
+        pub kind: Kind,
+        pub why: &'static str,
+    }
+
+    const fn pair(card: KC, partner: KC, kind: Kind, why: &'static str) -> Synergy {
+        Synergy {
+            card,
+            partner: Partner::Card(partner),
+            kind,
+            why,
+        }
+    }
+
+    const fn project(card: KC, partner: Project, kind: Kind, why: &'static str) -> Synergy {
+        Synergy {
+            card,
+            partner: Partner::Project(partner),
+            kind,
+            why,
+        }
+    }
+
+    pub const SYNERGIES: &[Synergy] = &[
+        pair(
+            KC::ThroneRoom,
+            KC::Feast,
+            Kind::Combo,
+            "Throne Room turns one Feast into two $5s",
+        ),
+        pair(
+            KC::Smithy,
+            KC::Festival,
+            Kind::Combo,
+            "Festival's actions and money feed Smithy's draw",
+        ),
+        pair(
+            KC::Masquerade,
+            KC::Lurker,
+            Kind::Combo,
+            "Lurker feeds the trash and Masquerade keeps the hands moving",
+        ),
+        pair(
+            KC::Village,
+            KC::Smithy,
+            Kind::Combo,
+            "The textbook village and draw",
+        ),
+        pair(
+            KC::Library,
+            KC::Festival,
+            Kind::Combo,
+            "Festival empties the hand for Library to refill",
+        ),
+        pair(
+            KC::KingsCourt,
+            KC::Bridge,
+            Kind::Combo,
+            "Three Bridges a play make everything cheap",
+        ),
+        pair(
+            KC::NativeVillage,
+            KC::Bridge,
+            Kind::Combo,
+            "Native Village saves up Bridges for one huge turn",
+        ),
+        pair(
+            KC::Chapel,
+            KC::TreasureMap,
+            Kind::Combo,
+            "A thin deck finds both Maps together",
+        ),
+        pair(
+            KC::Gardens,
+            KC::Workshop,
+            Kind::Combo,
+            "Workshop gains Gardens and the cards that make them count",
+        ),
+        pair(
+            KC::Gardens,
+            KC::Ironworks,
+            Kind::Combo,
+            "Ironworks gains Gardens and fills the deck",
+        ),
+        pair(
+            KC::Ironworks,
+            KC::Mill,
+            Kind::Combo,
+            "Gaining a Mill gives Ironworks' card and action",
+        ),
+        pair(
+            KC::Militia,
+            KC::CouncilRoom,
+            Kind::Combo,
+            "Militia takes back the cards Council Room gives away",
+        ),
+        pair(
+            KC::Outpost,
+            KC::Library,
+            Kind::Combo,
+            "Library refills Outpost's three-card hand",
+        ),
+        pair(
+            KC::Conspirator,
+            KC::Festival,
+            Kind::Combo,
+            "Festival is the cheap action that turns Conspirator on",
+        ),
+        pair(
+            KC::Highway,
+            KC::Workshop,
+            Kind::Combo,
+            "Highways make Workshop gain better cards",
+        ),
+        pair(
+            KC::Hamlet,
+            KC::Library,
+            Kind::Combo,
+            "Hamlet discards so Library draws more",
+        ),
+        pair(
+            KC::Scheme,
+            KC::Laboratory,
+            Kind::Combo,
+            "Scheme keeps the Labs coming back",
+        ),
+        pair(
+            KC::Chapel,
+            KC::Gardens,
+            Kind::NonBo,
+            "Trashing shrinks the deck Gardens counts",
+        ),
+        pair(
+            KC::Chapel,
+            KC::SilkRoad,
+            Kind::NonBo,
+            "Trashing Estates takes away Silk Road's points",
+        ),
+        pair(
+            KC::Chapel,
+            KC::CountingHouse,
+            Kind::NonBo,
+            "Counting House wants the Coppers Chapel trashes",
+        ),
+        pair(
+            KC::Baron,
+            KC::Chapel,
+            Kind::NonBo,
+            "Chapel trashes the Estates Baron wants",
+        ),
+        pair(
+            KC::ThroneRoom,
+            KC::Tactician,
+            Kind::NonBo,
+            "The second Tactician has nothing left to discard",
+        ),
+        pair(
+            KC::Library,
+            KC::Smithy,
+            Kind::NonBo,
+            "Two terminal draws collide",
+        ),
+        pair(
+            KC::Masquerade,
+            KC::Outpost,
+            Kind::Swingy,
+            "Masquerade on Outpost turns can pin a player",
+        ),
+        pair(
+            KC::Masquerade,
+            KC::KingsCourt,
+            Kind::Swingy,
+            "King's Court Masquerade can strip a hand bare",
+        ),
+        pair(
+            KC::KingsCourt,
+            KC::Torturer,
+            Kind::Swingy,
+            "King's Court Torturer buries whoever gets it first",
+        ),
+        pair(
+            KC::ThroneRoom,
+            KC::Witch,
+            Kind::Swingy,
+            "Throne Room Witch turns one good shuffle into four Curses",
+        ),
+        pair(
+            KC::Witch,
+            KC::Mountebank,
+            Kind::Swingy,
+            "Two cursers make the Curse split a coin flip",
+        ),
+        pair(
+            KC::Witch,
+            KC::SeaHag,
+            Kind::Swingy,
+            "Two cursers make the Curse split a coin flip",
+        ),
+        pair(
+            KC::Swindler,
+            KC::KingsCourt,
+            Kind::Swingy,
+            "Swindled King's Courts decide games",
+        ),
+        project(
+            KC::Smithy,
+            Project::Barracks,
+            Kind::Combo,
+            "Barracks' extra action plays a second Smithy",
+        ),
+        project(
+            KC::Bank,
+            Project::Capitalism,
+            Kind::Combo,
+            "Capitalism makes actions count as treasures for Bank",
+        ),
+        project(
+            KC::Gardens,
+            Project::Fair,
+            Kind::Combo,
+            "Fair's +Buy fills the deck for Gardens",
+        ),
+        project(
+            KC::Chapel,
+            Project::Sewers,
+            Kind::Combo,
+            "Sewers trashes again with every Chapel",
+        ),
+        project(
+            KC::Bridge,
+            Project::Canal,
+            Kind::Combo,
+            "Cost reductions stack",
+        ),
+        project(
+            KC::Workshop,
+            Project::Academy,
+            Kind::Combo,
+            "Workshop gains actions, Academy pays Villagers for them",
+        ),
+        project(
+            KC::BorderVillage,
+            Project::Innovation,
+            Kind::Combo,
+            "Innovation plays Border Village and its gain",
+        ),
+        project(
+            KC::Gardens,
+            Project::Cathedral,
+            Kind::NonBo,
+            "Cathedral trashes the deck Gardens counts",
+        ),
+        project(
+            KC::Torturer,
+            Project::Citadel,
+            Kind::Swingy,
+            "Citadel plays Torturer twice every turn",
+        ),
+    ];
+
+    fn in_setup(setup: &Setup, partner: &Partner) -> bool {
+        match partner {
+            Partner::Card(card) => {
+                setup.cards().contains(card) || setup.second_zebra.as_ref() == Some(card)
+            }
+            Partner::Project(project) => setup.project_cards.contains(project),
+        }
+    }
+
+    /// Every known pair in the setup
+    pub fn present(setup: &Setup) -> Vec<&'static Synergy> {
+        SYNERGIES
+            .iter()
+            .filter(|s| {
+                in_setup(setup, &Partner::Card(s.card.clone())) && in_setup(setup, &s.partner)
+            })
+            .collect()
+    }
+
+    /// The combos in the setup
+    pub fn combos(setup: &Setup) -> Vec<&'static Synergy> {
+        present(setup)
+            .into_iter()
+            .filter(|s| s.kind == Kind::Combo)
+            .collect()
+    }
+
+    /// Does the setup meet `require_combo` and `avoid_swingy`?
+    pub fn satisfies(config: &SetupConfig, setup: &Setup) -> bool {
+        let present = present(setup);
+
+        (!config.require_combo || present.iter().any(|s| s.kind == Kind::Combo))
+            && (!config.avoid_swingy || present.iter().all(|s| s.kind != Kind::Swingy))
+    }
+}
+
+pub mod similarity {
+    //! How alike two kingdoms are, from 0 (nothing in common) to 1 (the same
+    //! kingdom). Shared cards count most, then shared roles (a Festival for a
+    //! Village still plays alike), then shared projects.
+
+    use super::*;
+    use std::hash::Hash;
+
+    const CARDS: f64 = 0.6;
+    const ROLES: f64 = 0.25;
+    const PROJECTS: f64 = 0.15;
+
+    fn cards(setup: &Setup) -> HashSet<KC> {
+        let mut cards: HashSet<KC> = setup.cards().into_iter().collect();
+        cards.extend(setup.second_zebra.iter().cloned());
+        cards
+    }
+
+    fn roles(setup: &Setup) -> HashMap<Role, usize> {
+        let mut roles = HashMap::new();
+        let card_roles = cards(setup).into_iter().flat_map(|card| card.roles());
+        let project_roles = setup.project_cards.iter().flat_map(|p| p.roles());
+
+        for role in card_roles.chain(project_roles) {
+            *roles.entry(role).or_insert(0) += 1;
+        }
+
+        roles
+    }
+
+    fn jaccard<T: Eq + Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+        let union = a.union(b).count();
+        if union == 0 {
+            1.0
+        } else {
+            a.intersection(b).count() as f64 / union as f64
+        }
+    }
+
+    /// Jaccard over counts: shared roles over all roles, counting repeats
+    fn weighted_jaccard<T: Eq + Hash>(a: &HashMap<T, usize>, b: &HashMap<T, usize>) -> f64 {
+        let (mut shared, mut total) = (0, 0);
+
+        for key in a.keys().chain(b.keys().filter(|k| !a.contains_key(k))) {
+            let (x, y) = (*a.get(key).unwrap_or(&0), *b.get(key).unwrap_or(&0));
+            shared += x.min(y);
+            total += x.max(y);
+        }
+
+        if total == 0 {
+            1.0
+        } else {
+            shared as f64 / total as f64
+        }
+    }
+
+    /// How alike two kingdoms are, from 0 to 1. When neither has projects,
+    /// projects are left out rather than counted as shared.
+    ///
+    ///```
+    ///use dominion::{Setup, KC};
+    ///let setup = Setup::bane(KC::Bandit, vec![KC::Chapel, KC::YoungWitch]);
+    ///assert_eq!(dominion::similarity::similarity(&setup, &setup), 1.0);
+    ///```
+    pub fn similarity(a: &Setup, b: &Setup) -> f64 {
+        let cards = jaccard(&cards(a), &cards(b));
+        let roles = weighted_jaccard(&roles(a), &roles(b));
+
+        if a.project_cards.is_empty() && b.project_cards.is_empty() {
+            (CARDS * cards + ROLES * roles) / (CARDS + ROLES)
+        } else {
+            let projects = jaccard(
+                &a.project_cards.iter().collect(),
+                &b.project_cards.iter().collect(),
+            );
+            CARDS * cards + ROLES * roles + PROJECTS * projects
+        }
+    }
+
+    /// A kingdom from the history log and how alike it is to another one
+    #[derive(Debug, Serialize)]
+    pub struct Neighbour<'a> {
+        pub similarity: f64,
+        pub played: &'a history::Played,
+    }
+
+    /// The `count` kingdoms from `history` most like `setup`, most alike first
+    pub fn nearest<'a>(
+        setup: &Setup,
+        history: &'a [history::Played],
+        count: usize,
+    ) -> Vec<Neighbour<'a>> {
+        let mut neighbours: Vec<Neighbour> = history
+            .iter()
+            .map(|played| Neighbour {
+                similarity: similarity(setup, &played.setup),
+                played,
+            })
+            .collect();
+
+        neighbours.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        neighbours.truncate(count);
+        neighbours
+    }
+
+    /// Is the setup different enough from the `previous` kingdoms for the
+    /// config's `max_similarity`?
+    pub fn satisfies(config: &SetupConfig, previous: &[Setup], setup: &Setup) -> bool {
+        match config.max_similarity {
+            Some(max) => previous.iter().all(|p| similarity(setup, p) <= max),
+            None => true,
+        }
+    }
+}
+
+pub mod engine {
+    //! A rules engine for the Base2 kingdom cards, the basic treasures and the
+    //! basic victory cards.
+    //!
+    //! A `Game` is plain data. Anything a player has to choose is asked of a
+    //! `Decider` as a `Decision`: pick between `min` and `max` of `options`.
+    //! Shuffles are seeded from the game's seed and a shuffle counter, so a
+    //! game replays exactly from its seed and the choices made. A decider may
+    //! decline to answer (e.g. while waiting on a person), which stops the game
+    //! with `EngineError::Suspended`; the turn in progress is then half played,
+    //! so pick the game back up by replaying it from the start (see
+    //! `Scripted`).
+    //!
+    //! Treasures are played automatically, all at once, at the start of the
+    //! buy phase.
+
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Any card the engine can play with. Shown and serialized by name
+    /// (`"Copper"`, `"Smithy"`).
+    #[derive(PartialEq, Eq, Hash, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+    #[serde(into = "String", try_from = "String")]
+    pub enum Card {
+        Copper,
+        Silver,
+        Gold,
+        Estate,
+        Duchy,
+        Province,
+        Curse,
+        Kingdom(KC),
+    }
+
+    impl std::fmt::Display for Card {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Card::Copper => write!(f, "Copper"),
+                Card::Silver => write!(f, "Silver"),
+                Card::Gold => write!(f, "Gold"),
+                Card::Estate => write!(f, "Estate"),
+                Card::Duchy => write!(f, "Duchy"),
+                Card::Province => write!(f, "Province"),
+                Card::Curse => write!(f, "Curse"),
+                Card::Kingdom(card) => write!(f, "{:?}", card),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for Card {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self)
+        }
+    }
+
+    impl FromStr for Card {
+        type Err = String;
+
+        /// Parse a basic card or a kingdom card
+        ///
+        ///```
+        ///use dominion::{engine::Card, KC};
+        ///assert_eq!("Gold".parse(), Ok(Card::Gold));
+        ///assert_eq!("Smithy".parse(), Ok(Card::Kingdom(KC::Smithy)));
+        ///```
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "Copper" => Ok(Card::Copper),
+                "Silver" => Ok(Card::Silver),
+                "Gold" => Ok(Card::Gold),
+                "Estate" => Ok(Card::Estate),
+                "Duchy" => Ok(Card::Duchy),
+                "Province" => Ok(Card::Province),
+                "Curse" => Ok(Card::Curse),
+                _ => s
+                    .parse()
+                    .map(Card::Kingdom)
+                    .map_err(|_| format!("{} is not a card", s)),
+            }
+        }
+    }
+
+    impl From<Card> for String {
+        fn from(card: Card) -> Self {
+            card.to_string()
+        }
+    }
+
+    impl TryFrom<String> for Card {
+        type Error = String;
+
+        fn try_from(s: String) -> Result<Self, Self::Error> {
+            s.parse()
+        }
+    }
+
+    impl Card {
+        pub fn cost(&self) -> u8 {
+            match self {
+                Card::Copper => 0,
+                Card::Silver => 3,
+                Card::Gold => 6,
+                Card::Estate => 2,
+                Card::Duchy => 5,
+                Card::Province => 8,
+                Card::Curse => 0,
+                Card::Kingdom(card) => card.base_cost(),
+            }
+        }
+
+        pub fn types(&self) -> Vec<CardType> {
+            match self {
+                Card::Copper | Card::Silver | Card::Gold => vec![CardType::Treasure],
+                Card::Estate | Card::Duchy | Card::Province => vec![CardType::Victory],
+                Card::Curse => vec![],
+                Card::Kingdom(card) => card.card_types(),
+            }
+        }
+
+        pub fn is(&self, card_type: CardType) -> bool {
+            self.types().contains(&card_type)
+        }
+
+        /// Coins it makes when played
+        fn value(&self) -> usize {
+            match self {
+                Card::Copper => 1,
+                Card::Silver => 2,
+                Card::Gold => 3,
+                _ => 0,
+            }
+        }
+    }
+
+    /// Where a gained card goes
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Destination {
+        Discard,
+        Hand,
+        Deck,
+    }
+
+    /// A player's cards. The top of the deck is the end of `deck`.
+    #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+    pub struct Player {
+        pub deck: Vec<Card>,
+        pub hand: Vec<Card>,
+        pub discard: Vec<Card>,
+        pub in_play: Vec<Card>,
+    }
+
+    impl Player {
+        /// Every card the player owns
+        pub fn cards(&self) -> impl Iterator<Item = &Card> {
+            self.deck
+                .iter()
+                .chain(&self.hand)
+                .chain(&self.discard)
+                .chain(&self.in_play)
+        }
+    }
+
+    /// A supply pile
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Pile {
+        pub card: Card,
+        pub count: usize,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, EnumIter)]
+    pub enum Phase {
+        Action,
+        Buy,
+        Cleanup,
+    }
+
+    /// What a decision is about
+    #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, EnumIter)]
+    pub enum DecisionKind {
+        /// An action from hand to play, none ends the action phase
+        PlayAction,
+
+        /// A card to buy, none ends the buy phase
+        Buy,
+
+        /// Cards from hand to discard
+        Discard,
+
+        /// Cards to trash
+        Trash,
+
+        /// A card to gain
+        Gain,
+
+        /// Cards to put onto the deck, the last chosen ends up on top
+        TopDeck,
+
+        /// Actions drawn by Library to set aside
+        SetAside,
+
+        /// Whether to play the card (Vassal)
+        Play,
+
+        /// Whether to reveal a reaction (Moat)
+        Reveal,
+    }
+
+    /// A choice a player has to make: between `min` and `max` of `options`
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Decision {
+        pub player: usize,
+        pub kind: DecisionKind,
+
+        /// The card asking, if any
+        pub source: Option<Card>,
+
+        pub options: Vec<Card>,
+        pub min: usize,
+        pub max: usize,
+    }
+
+    /// Makes the players' choices
+    pub trait Decider {
+        /// Pick between `decision.min` and `decision.max` of
+        /// `decision.options`, or `None` to suspend the game
+        fn decide(&mut self, game: &Game, decision: &Decision) -> Option<Vec<Card>>;
+    }
+
+    impl<F: FnMut(&Game, &Decision) -> Option<Vec<Card>>> Decider for F {
+        fn decide(&mut self, game: &Game, decision: &Decision) -> Option<Vec<Card>> {
+            self(game, decision)
+        }
+    }
+
+    /// Replays choices made before, in order, and suspends when it runs out
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct Scripted {
+        pub choices: Vec<Vec<Card>>,
+        pub next: usize,
+    }
+
+    impl Scripted {
+        pub fn new(choices: Vec<Vec<Card>>) -> Self {
+            Self { choices, next: 0 }
+        }
+    }
+
+    impl Decider for Scripted {
+        fn decide(&mut self, _game: &Game, _decision: &Decision) -> Option<Vec<Card>> {
+            let choice = self.choices.get(self.next).cloned();
+            self.next += choice.is_some() as usize;
+            choice
+        }
+    }
+
+    /// Things that stop a game
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum EngineError {
+        /// The engine only knows Base2 kingdom cards
+        UnsupportedCard(KC),
+
+        /// Projects, the Young Witch bane and custom banes aren't modelled
+        UnsupportedSetup,
+
+        /// Games are for 1 to 4 players
+        BadPlayerCount(usize),
+
+        /// A bot wants a card that isn't in the supply
+        NotInSupply(KC),
+
+        /// The decider had no answer yet
+        Suspended,
+
+        /// Someone answered another player's decision
+        NotYourDecision(usize),
+
+        /// The decider's choice doesn't fit the decision
+        InvalidChoice {
+            decision: Decision,
+            choice: Vec<Card>,
+        },
+
+        /// The game has already ended
+        GameOver,
+    }
+
+    /// Something that happened in a game, kept in `Game::events`
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum Event {
+        /// A player's discard pile was shuffled into their deck
+        Shuffle {
+            player: usize,
+            seed: u64,
+        },
+
+        /// A player's turn started
+        Turn {
+            player: usize,
+            turn: usize,
+        },
+
+        /// A player answered a decision
+        Decision {
+            decision: Decision,
+            choice: Vec<Card>,
+        },
+
+        Gain {
+            player: usize,
+            card: Card,
+        },
+        Trash {
+            player: usize,
+            card: Card,
+        },
+
+        /// A player's victory points changed
+        Score {
+            player: usize,
+            score: i32,
+        },
+
+        /// The game ended with these scores
+        End {
+            scores: Vec<i32>,
+        },
+    }
+
+    /// A game in progress
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Game {
+        pub players: Vec<Player>,
+        pub supply: Vec<Pile>,
+        pub trash: Vec<Card>,
+
+        /// Whose turn it is
+        pub current: usize,
+
+        /// Turns taken so far, by all players
+        pub turn: usize,
+
+        pub phase: Phase,
+        pub actions: usize,
+        pub buys: usize,
+        pub coins: usize,
+
+        /// Merchants played this turn
+        pub merchants: usize,
+
+        pub seed: u64,
+
+        /// Shuffles so far, each seeded by `shuffle_seed`
+        pub shuffles: u64,
+
+        /// Each player's victory points, as of the last `Event::Score`
+        pub scores: Vec<i32>,
+
+        /// Everything that happened so far, in order
+        pub events: Vec<Event>,
+    }
+
+    impl Game {
+        /// Set up a game of `setup` for `players` players
+        pub fn new(setup: &Setup, players: usize, seed: u64) -> Result<Self, EngineError> {
+            if !(1..=4).contains(&players) {
+                return Err(EngineError::BadPlayerCount(players));
+            }
+            if setup.bane_card.is_some()
+                || !setup.project_cards.is_empty()
+                || !setup.bane_cards.is_empty()
+                || setup.second_zebra.is_some()
+            {
+                return Err(EngineError::UnsupportedSetup);
+            }
+
+            let mut kingdom = setup.kingdom_cards.clone();
+            kingdom.sort();
+            if let Some(card) = kingdom
+                .iter()
+                .find(|card| !card.expansions().contains(&Expansion::Base2))
+            {
+                return Err(EngineError::UnsupportedCard(card.clone()));
+            }
+
+            let victory = if players <= 2 { 8 } else { 12 };
+            let mut supply = vec![
+                Pile {
+                    card: Card::Copper,
+                    count: 60 - 7 * players,
+                },
+                Pile {
+                    card: Card::Silver,
+                    count: 40,
+                },
+                Pile {
+                    card: Card::Gold,
+                    count: 30,
+                },
+                Pile {
+                    card: Card::Estate,
+                    count: victory,
+                },
+                Pile {
+                    card: Card::Duchy,
+                    count: victory,
+                },
+                Pile {
+                    card: Card::Province,
+                    count: victory,
+                },
+                Pile {
+                    card: Card::Curse,
+                    count: 10 * players.saturating_sub(1).max(1),
+                },
+            ];
+            supply.extend(kingdom.into_iter().map(|card| {
+                let count = if card.card_types().contains(&CardType::Victory) {
+                    victory
+                } else {
+                    10
+                };
+                Pile {
+                    card: Card::Kingdom(card),
+                    count,
+                }
+            }));
+
+            let mut game = Game {
+                players: vec![],
+                supply,
+                trash: vec![],
+                current: 0,
+                turn: 0,
+                phase: Phase::Action,
+                actions: 1,
+                buys: 1,
+                coins: 0,
+                merchants: 0,
+                seed,
+                shuffles: 0,
+                scores: vec![],
+                events: vec![],
+            };
+
+            for player in 0..players {
+                let mut starting = vec![Card::Copper; 7];
+                starting.extend(vec![Card::Estate; 3]);
+                game.players.push(Player {
+                    discard: starting,
+                    ..Player::default()
+                });
+                game.draw(player, 5);
+                game.scores.push(game.score(player));
+            }
+
+            Ok(game)
+        }
+
+        /// The seed of the `n`th shuffle (counting from 0)
+        pub fn shuffle_seed(&self, n: u64) -> u64 {
+            self.seed ^ (n + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        }
+
+        /// The game ends when the Provinces or any three piles run out
+        pub fn is_over(&self) -> bool {
+            let empty = self.supply.iter().filter(|p| p.count == 0).count();
+            let provinces = self.pile(&Card::Province).map(|p| p.count).unwrap_or(0);
+
+            provinces == 0 || empty >= 3
+        }
+
+        pub fn pile(&self, card: &Card) -> Option<&Pile> {
+            self.supply.iter().find(|p| &p.card == card)
+        }
+
+        /// A player's victory points
+        pub fn score(&self, player: usize) -> i32 {
+            let mut cards = std::collections::BTreeMap::new();
+            for card in self.players[player].cards() {
+                *cards.entry(scoring::Card::from(card.clone())).or_insert(0) += 1;
+            }
+
+            scoring::points(&cards).values().sum()
+        }
+
+        /// Play until the game is over
+        pub fn run<D: Decider>(&mut self, decider: &mut D) -> Result<(), EngineError> {
+            while !self.is_over() {
+                self.take_turn(decider)?;
+            }
+            Ok(())
+        }
+
+        /// Play the current player's turn: actions, buys, then cleanup
+        pub fn take_turn<D: Decider>(&mut self, decider: &mut D) -> Result<(), EngineError> {
+            if self.is_over() {
+                return Err(EngineError::GameOver);
+            }
+            let player = self.current;
+            self.events.push(Event::Turn {
+                player,
+                turn: self.turn,
+            });
+
+            self.phase = Phase::Action;
+            self.actions = 1;
+            self.buys = 1;
+            self.coins = 0;
+            self.merchants = 0;
+
+            while self.actions > 0 {
+                let options = self.in_hand(player, |c| c.is(CardType::Action));
+                let choice = self.decide(
+                    decider,
+                    player,
+                    DecisionKind::PlayAction,
+                    None,
+                    options,
+                    0,
+                    1,
+                )?;
+                match choice.first() {
+                    Some(card) => {
+                        self.actions -= 1;
+                        self.play_from_hand(decider, player, card)?;
+                        self.note_scores();
+                    }
+                    None => break,
+                }
+            }
+
+            self.phase = Phase::Buy;
+            let treasures = self.in_hand(player, |c| c.is(CardType::Treasure));
+            let mut silver_played = false;
+            for treasure in treasures {
+                self.move_card(player, &treasure, Zone::Hand, Zone::InPlay);
+                self.coins += treasure.value();
+                if treasure == Card::Silver && !silver_played {
+                    silver_played = true;
+                    self.coins += self.merchants;
+                }
+            }
+
+            while self.buys > 0 {
+                let options: Vec<Card> = self
+                    .supply
+                    .iter()
+                    .filter(|p| p.count > 0 && p.card.cost() as usize <= self.coins)
+                    .map(|p| p.card.clone())
+                    .collect();
+                let choice =
+                    self.decide(decider, player, DecisionKind::Buy, None, options, 0, 1)?;
+                match choice.first() {
+                    Some(card) => {
+                        self.buys -= 1;
+                        self.coins -= card.cost() as usize;
+                        self.gain(player, card, Destination::Discard);
+                        self.note_scores();
+                    }
+                    None => break,
+                }
+            }
+
+            self.phase = Phase::Cleanup;
+            let p = &mut self.players[player];
+            p.discard.append(&mut p.in_play);
+            p.discard.append(&mut p.hand);
+            self.draw(player, 5);
+
+            self.current = (self.current + 1) % self.players.len();
+            self.turn += 1;
+            self.phase = Phase::Action;
+
+            if self.is_over() {
+                self.events.push(Event::End {
+                    scores: self.scores.clone(),
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Record the players' victory points that changed
+        fn note_scores(&mut self) {
+            for player in 0..self.players.len() {
+                let score = self.score(player);
+                if score != self.scores[player] {
+                    self.scores[player] = score;
+                    self.events.push(Event::Score { player, score });
+                }
+            }
+        }
+
+        /// Ask the decider, unless there's nothing to choose, and check the
+        /// answer
+        #[allow(clippy::too_many_arguments)]
+        fn decide<D: Decider>(
+            &mut self,
+            decider: &mut D,
+            player: usize,
+            kind: DecisionKind,
+            source: Option<Card>,
+            options: Vec<Card>,
+            min: usize,
+            max: usize,
+        ) -> Result<Vec<Card>, EngineError> {
+            let min = min.min(options.len());
+            let max = max.min(options.len());
+
+            if options.is_empty() {
+                return Ok(vec![]);
+            }
+            if min == options.len() && options.iter().all(|c| c == &options[0]) {
+                return Ok(options);
+            }
+
+            let decision = Decision {
+                player,
+                kind,
+                source,
+                options,
+                min,
+                max,
+            };
+            let choice = decider
+                .decide(self, &decision)
+                .ok_or(EngineError::Suspended)?;
+
+            let mut left = decision.options.clone();
+            let fits = choice.len() >= min
+                && choice.len() <= max
+                && choice
+                    .iter()
+                    .all(|card| match left.iter().position(|c| c == card) {
+                        Some(i) => {
+                            left.remove(i);
+                            true
+                        }
+                        None => false,
+                    });
+
+            if fits {
+                self.events.push(Event::Decision {
+                    decision,
+                    choice: choice.clone(),
+                });
+                Ok(choice)
+            } else {
+                Err(EngineError::InvalidChoice { decision, choice })
+            }
+        }
+
+        fn in_hand(&self, player: usize, keep: impl Fn(&Card) -> bool) -> Vec<Card> {
+            let mut cards: Vec<Card> = self.players[player]
+                .hand
+                .iter()
+                .filter(|c| keep(c))
+                .cloned()
+                .collect();
+            cards.sort();
+            cards
+        }
+
+        fn supply_up_to(&self, cost: u8, keep: impl Fn(&Card) -> bool) -> Vec<Card> {
+            self.supply
+                .iter()
+                .filter(|p| p.count > 0 && p.card.cost() <= cost && keep(&p.card))
+                .map(|p| p.card.clone())
+                .collect()
+        }
+
+        /// Put a player's discard pile under their deck, shuffled
+        fn shuffle(&mut self, player: usize) {
+            let seed = self.shuffle_seed(self.shuffles);
+            let mut rng = StdRng::seed_from_u64(seed);
+            self.shuffles += 1;
+            self.events.push(Event::Shuffle { player, seed });
+
+            let p = &mut self.players[player];
+            let mut cards = std::mem::take(&mut p.discard);
+            cards.shuffle(&mut rng);
+            cards.append(&mut p.deck);
+            p.deck = cards;
+        }
+
+        /// Take the top card of a player's deck, shuffling if it's empty
+        fn reveal(&mut self, player: usize) -> Option<Card> {
+            if self.players[player].deck.is_empty() && !self.players[player].discard.is_empty() {
+                self.shuffle(player);
+            }
+            self.players[player].deck.pop()
+        }
+
+        fn draw(&mut self, player: usize, count: usize) {
+            for _ in 0..count {
+                match self.reveal(player) {
+                    Some(card) => self.players[player].hand.push(card),
+                    None => break,
+                }
+            }
+        }
+
+        fn gain(&mut self, player: usize, card: &Card, to: Destination) -> bool {
+            let pile = self.supply.iter_mut().find(|p| &p.card == card);
+            match pile {
+                Some(pile) if pile.count > 0 => {
+                    pile.count -= 1;
+                    self.events.push(Event::Gain {
+                        player,
+                        card: card.clone(),
+                    });
+                    let p = &mut self.players[player];
+                    match to {
+                        Destination::Discard => p.discard.push(card.clone()),
+                        Destination::Hand => p.hand.push(card.clone()),
+                        Destination::Deck => p.deck.push(card.clone()),
+                    }
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn zone(&mut self, player: usize, zone: Zone) -> &mut Vec<Card> {
+            let p = &mut self.players[player];
+            match zone {
+                Zone::Deck => &mut p.deck,
+                Zone::Hand => &mut p.hand,
+                Zone::Discard => &mut p.discard,
+                Zone::InPlay => &mut p.in_play,
+            }
+        }
+
+        fn take(&mut self, player: usize, card: &Card, from: Zone) -> Card {
+            let cards = self.zone(player, from);
+            let i = cards.iter().rposition(|c| c == card).unwrap();
+            cards.remove(i)
+        }
+
+        fn move_card(&mut self, player: usize, card: &Card, from: Zone, to: Zone) {
+            let card = self.take(player, card, from);
+            self.zone(player, to).push(card);
+        }
+
+        fn trash_from(&mut self, player: usize, card: &Card, from: Zone) {
+            let card = self.take(player, card, from);
+            self.trash_card(player, card);
+        }
+
+        /// Trash one of a player's cards that's already out of their zones
+        fn trash_card(&mut self, player: usize, card: Card) {
+            self.events.push(Event::Trash {
+                player,
+                card: card.clone(),
+            });
+            self.trash.push(card);
+        }
+
+        fn play_from_hand<D: Decider>(
+            &mut self,
+            decider: &mut D,
+            player: usize,
+            card: &Card,
+        ) -> Result<(), EngineError> {
+            self.move_card(player, card, Zone::Hand, Zone::InPlay);
+            self.resolve(decider, player, card)
+        }
+
+        /// The other players, in turn order, that an attack hits (Moat
+        /// blocks)
+        fn victims<D: Decider>(
+            &mut self,
+            decider: &mut D,
+            player: usize,
+        ) -> Result<Vec<usize>, EngineError> {
+            let mut victims = vec![];
+            let count = self.players.len();
+
+            for victim in (1..count).map(|i| (player + i) % count) {
+                let moats = self.in_hand(victim, |c| c == &Card::Kingdom(KC::Moat));
+                let reveal = self.decide(
+                    decider,
+                    victim,
+                    DecisionKind::Reveal,
+                    Some(Card::Kingdom(KC::Moat)),
+                    moats.into_iter().take(1).collect(),
+                    0,
+                    1,
+                )?;
+                if reveal.is_empty() {
+                    victims.push(victim);
+                }
+            }
+
+            Ok(victims)
+        }
+
+        /// Carry out an action's effect (the card is already in play)
+        fn resolve<D: Decider>(
+            &mut self,
+            decider: &mut D,
+            player: usize,
+            card: &Card,
+        ) -> Result<(), EngineError> {
+            let source = Some(card.clone());
+            let kc = match card {
+                Card::Kingdom(kc) => kc,
+                _ => return Ok(()),
+            };
+
+            match kc {
+                KC::Artisan => {
+                    let options = self.supply_up_to(5, |_| true);
+                    let gained = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Gain,
+                        source.clone(),
+                        options,
+                        1,
+                        1,
+                    )?;
+                    for card in &gained {
+                        self.gain(player, card, Destination::Hand);
+                    }
+                    let options = self.in_hand(player, |_| true);
+                    let topdeck = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::TopDeck,
+                        source,
+                        options,
+                        1,
+                        1,
+                    )?;
+                    for card in &topdeck {
+                        self.move_card(player, card, Zone::Hand, Zone::Deck);
+                    }
+                }
+                KC::Bandit => {
+                    self.gain(player, &Card::Gold, Destination::Discard);
+                    for victim in self.victims(decider, player)? {
+                        let revealed: Vec<Card> =
+                            (0..2).filter_map(|_| self.reveal(victim)).collect();
+                        let mut options: Vec<Card> = revealed
+                            .iter()
+                            .filter(|c| c.is(CardType::Treasure) && c != &&Card::Copper)
+                            .cloned()
+                            .collect();
+                        options.sort();
+                        let trashed = self.decide(
+                            decider,
+                            victim,
+                            DecisionKind::Trash,
+                            source.clone(),
+                            options,
+                            1,
+                            1,
+                        )?;
+
+                        let mut rest = revealed;
+                        for card in &trashed {
+                            let i = rest.iter().position(|c| c == card).unwrap();
+                            self.trash_card(victim, rest.remove(i));
+                        }
+                        self.players[victim].discard.append(&mut rest);
+                    }
+                }
+                KC::Bureaucrat => {
+                    self.gain(player, &Card::Silver, Destination::Deck);
+                    for victim in self.victims(decider, player)? {
+                        let options = self.in_hand(victim, |c| c.is(CardType::Victory));
+                        let topdeck = self.decide(
+                            decider,
+                            victim,
+                            DecisionKind::TopDeck,
+                            source.clone(),
+                            options,
+                            1,
+                            1,
+                        )?;
+                        for card in &topdeck {
+                            self.move_card(victim, card, Zone::Hand, Zone::Deck);
+                        }
+                    }
+                }
+                KC::Cellar => {
+                    self.actions += 1;
+                    let options = self.in_hand(player, |_| true);
+                    let max = options.len();
+                    let discarded = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Discard,
+                        source,
+                        options,
+                        0,
+                        max,
+                    )?;
+                    for card in &discarded {
+                        self.move_card(player, card, Zone::Hand, Zone::Discard);
+                    }
+                    self.draw(player, discarded.len());
+                }
+                KC::Chapel => {
+                    let options = self.in_hand(player, |_| true);
+                    let trashed =
+                        self.decide(decider, player, DecisionKind::Trash, source, options, 0, 4)?;
+                    for card in &trashed {
+                        self.trash_from(player, card, Zone::Hand);
+                    }
+                }
+                KC::CouncilRoom => {
+                    self.draw(player, 4);
+                    self.buys += 1;
+                    let count = self.players.len();
+                    for other in (1..count).map(|i| (player + i) % count) {
+                        self.draw(other, 1);
+                    }
+                }
+                KC::Festival => {
+                    self.actions += 2;
+                    self.buys += 1;
+                    self.coins += 2;
+                }
+                KC::Harbinger => {
+                    self.draw(player, 1);
+                    self.actions += 1;
+                    let mut options = self.players[player].discard.clone();
+                    options.sort();
+                    options.dedup();
+                    let topdeck = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::TopDeck,
+                        source,
+                        options,
+                        0,
+                        1,
+                    )?;
+                    for card in &topdeck {
+                        self.move_card(player, card, Zone::Discard, Zone::Deck);
+                    }
+                }
+                KC::Laboratory => {
+                    self.draw(player, 2);
+                    self.actions += 1;
+                }
+                KC::Library => {
+                    let mut set_aside = vec![];
+                    while self.players[player].hand.len() < 7 {
+                        let drawn = match self.reveal(player) {
+                            Some(card) => card,
+                            None => break,
+                        };
+                        let options = if drawn.is(CardType::Action) {
+                            vec![drawn.clone()]
+                        } else {
+                            vec![]
+                        };
+                        let aside = self.decide(
+                            decider,
+                            player,
+                            DecisionKind::SetAside,
+                            source.clone(),
+                            options,
+                            0,
+                            1,
+                        )?;
+                        if aside.is_empty() {
+                            self.players[player].hand.push(drawn);
+                        } else {
+                            set_aside.push(drawn);
+                        }
+                    }
+                    self.players[player].discard.append(&mut set_aside);
+                }
+                KC::Market => {
+                    self.draw(player, 1);
+                    self.actions += 1;
+                    self.buys += 1;
+                    self.coins += 1;
+                }
+                KC::Merchant => {
+                    self.draw(player, 1);
+                    self.actions += 1;
+                    self.merchants += 1;
+                }
+                KC::Militia => {
+                    self.coins += 2;
+                    for victim in self.victims(decider, player)? {
+                        let options = self.in_hand(victim, |_| true);
+                        let extra = options.len().saturating_sub(3);
+                        let discarded = self.decide(
+                            decider,
+                            victim,
+                            DecisionKind::Discard,
+                            source.clone(),
+                            options,
+                            extra,
+                            extra,
+                        )?;
+                        for card in &discarded {
+                            self.move_card(victim, card, Zone::Hand, Zone::Discard);
+                        }
+                    }
+                }
+                KC::Mine => {
+                    let options = self.in_hand(player, |c| c.is(CardType::Treasure));
+                    let trashed = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Trash,
+                        source.clone(),
+                        options,
+                        0,
+                        1,
+                    )?;
+                    for card in &trashed {
+                        self.trash_from(player, card, Zone::Hand);
+                        let options =
+                            self.supply_up_to(card.cost() + 3, |c| c.is(CardType::Treasure));
+                        let gained = self.decide(
+                            decider,
+                            player,
+                            DecisionKind::Gain,
+                            source.clone(),
+                            options,
+                            1,
+                            1,
+                        )?;
+                        for card in &gained {
+                            self.gain(player, card, Destination::Hand);
+                        }
+                    }
+                }
+                KC::Moat => self.draw(player, 2),
+                KC::Moneylender => {
+                    let options = self.in_hand(player, |c| c == &Card::Copper);
+                    let trashed = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Trash,
+                        source,
+                        options.into_iter().take(1).collect(),
+                        0,
+                        1,
+                    )?;
+                    for card in &trashed {
+                        self.trash_from(player, card, Zone::Hand);
+                        self.coins += 3;
+                    }
+                }
+                KC::Poacher => {
+                    self.draw(player, 1);
+                    self.actions += 1;
+                    self.coins += 1;
+                    let empty = self.supply.iter().filter(|p| p.count == 0).count();
+                    let options = self.in_hand(player, |_| true);
+                    let discarded = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Discard,
+                        source,
+                        options,
+                        empty,
+                        empty,
+                    )?;
+                    for card in &discarded {
+                        self.move_card(player, card, Zone::Hand, Zone::Discard);
+                    }
+                }
+                KC::Remodel => {
+                    let options = self.in_hand(player, |_| true);
+                    let trashed = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Trash,
+                        source.clone(),
+                        options,
+                        1,
+                        1,
+                    )?;
+                    for card in &trashed {
+                        self.trash_from(player, card, Zone::Hand);
+                        let options = self.supply_up_to(card.cost() + 2, |_| true);
+                        let gained = self.decide(
+                            decider,
+                            player,
+                            DecisionKind::Gain,
+                            source.clone(),
+                            options,
+                            1,
+                            1,
+                        )?;
+                        for card in &gained {
+                            self.gain(player, card, Destination::Discard);
+                        }
+                    }
+                }
+                KC::Sentry => {
+                    self.draw(player, 1);
+                    self.actions += 1;
+                    let mut looked: Vec<Card> =
+                        (0..2).filter_map(|_| self.reveal(player)).collect();
+                    looked.sort();
+
+                    let max = looked.len();
+                    let trashed = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Trash,
+                        source.clone(),
+                        looked.clone(),
+                        0,
+                        max,
+                    )?;
+                    for card in trashed {
+                        let i = looked.iter().position(|c| c == &card).unwrap();
+                        self.trash_card(player, looked.remove(i));
+                    }
+
+                    let max = looked.len();
+                    let discarded = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::Discard,
+                        source.clone(),
+                        looked.clone(),
+                        0,
+                        max,
+                    )?;
+                    for card in discarded {
+                        let i = looked.iter().position(|c| c == &card).unwrap();
+                        self.players[player].discard.push(looked.remove(i));
+                    }
+
+                    let count = looked.len();
+                    let order = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::TopDeck,
+                        source,
+                        looked,
+                        count,
+                        count,
+                    )?;
+                    self.players[player].deck.extend(order);
+                }
+                KC::Smithy => self.draw(player, 3),
+                KC::ThroneRoom => {
+                    let options = self.in_hand(player, |c| c.is(CardType::Action));
+                    let chosen = self.decide(
+                        decider,
+                        player,
+                        DecisionKind::PlayAction,
+                        source,
+                        options,
+                        0,
+                        1,
+                    )?;
+                    for card in &chosen {
+                        self.play_from_hand(decider, player, card)?;
+                        self.resolve(decider, player, card)?;
+                    }
+                }
+                KC::Vassal => {
+                    self.coins += 2;
+                    if let Some(top) = self.reveal(player) {
+                        self.players[player].discard.push(top.clone());
+                        let options = if top.is(CardType::Action) {
+                            vec![top]
+                        } else {
+                            vec![]
+                        };
+                        let played = self.decide(
+                            decider,
+                            player,
+                            DecisionKind::Play,
+                            source,
+                            options,
+                            0,
+                            1,
+                        )?;
+                        for card in &played {
+                            self.move_card(player, card, Zone::Discard, Zone::InPlay);
+                            self.resolve(decider, player, card)?;
+                        }
+                    }
+                }
+                KC::Village => {
+                    self.draw(player, 1);
+                    self.actions += 2;
+                }
+                KC::Witch => {
+                    self.draw(player, 2);
+                    for victim in self.victims(decider, player)? {
+                        self.gain(victim, &Card::Curse, Destination::Discard);
+                    }
+                }
+                KC::Workshop => {
+                    let options = self.supply_up_to(4, |_| true);
+                    let gained =
+                        self.decide(decider, player, DecisionKind::Gain, source, options, 1, 1)?;
+                    for card in &gained {
+                        self.gain(player, card, Destination::Discard);
+                    }
+                }
+                // Gardens only scores, and `Game::new` turns away every
+                // non-Base2 card
+                _ => {}
+            }
+
+            Ok(())
         }
     }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Zone {
+        Deck,
+        Hand,
+        Discard,
+        InPlay,
+    }
 }
 
-/// A project card
-#[derive(
-    EnumIter,
-    Debug,
-    PartialEq,
-    EnumCountMacro,
-    Eq,
-    Hash,
-    Clone,
-    Ord,
-    PartialOrd,
-    Serialize,
-    Deserialize,
-)]
-pub enum Project {
-    Academy,
-    Barracks,
-    Canal,
-    Capitalism,
-    Cathedral,
-    Citadel,
-    CityGate,
-    CropRotation,
-    Exploration,
-    Fair,
-    Fleet,
-    Guildhall,
-    Innovation,
-    Pageant,
-    Piazza,
-    RoadNetwork,
-    Sewers,
-    Silos,
-    SinisterPlot,
-    StarChart,
+pub mod scoring {
+    //! End-of-game tallies from the cards each player ended with, for games
+    //! played at the table as well as by the engine. Counts go by name and
+    //! cover every card a player owns, wherever it is: deck, hand, discard,
+    //! in play or set aside on a mat. VP tokens (Renaissance projects and
+    //! the like) are counted by the players and added on top.
+
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Any card a player can end the game with: the engine's cards, and the
+    /// Prosperity basics it doesn't play with. Shown and serialized by name
+    /// (`"Colony"`, `"Gardens"`).
+    #[derive(PartialEq, Eq, Hash, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+    #[serde(into = "String", try_from = "String")]
+    pub enum Card {
+        Copper,
+        Silver,
+        Gold,
+        Platinum,
+        Estate,
+        Duchy,
+        Province,
+        Colony,
+        Curse,
+        Kingdom(KC),
+    }
+
+    impl std::fmt::Display for Card {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Card::Copper => write!(f, "Copper"),
+                Card::Silver => write!(f, "Silver"),
+                Card::Gold => write!(f, "Gold"),
+                Card::Platinum => write!(f, "Platinum"),
+                Card::Estate => write!(f, "Estate"),
+                Card::Duchy => write!(f, "Duchy"),
+                Card::Province => write!(f, "Province"),
+                Card::Colony => write!(f, "Colony"),
+                Card::Curse => write!(f, "Curse"),
+                Card::Kingdom(card) => write!(f, "{:?}", card),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for Card {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self)
+        }
+    }
+
+    impl From<engine::Card> for Card {
+        fn from(card: engine::Card) -> Self {
+            match card {
+                engine::Card::Copper => Card::Copper,
+                engine::Card::Silver => Card::Silver,
+                engine::Card::Gold => Card::Gold,
+                engine::Card::Estate => Card::Estate,
+                engine::Card::Duchy => Card::Duchy,
+                engine::Card::Province => Card::Province,
+                engine::Card::Curse => Card::Curse,
+                engine::Card::Kingdom(card) => Card::Kingdom(card),
+            }
+        }
+    }
+
+    impl FromStr for Card {
+        type Err = String;
+
+        /// Parse Platinum, Colony or anything the engine plays with
+        ///
+        ///```
+        ///use dominion::{scoring::Card, KC};
+        ///assert_eq!("Colony".parse(), Ok(Card::Colony));
+        ///assert_eq!("Gardens".parse(), Ok(Card::Kingdom(KC::Gardens)));
+        ///```
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "Platinum" => Ok(Card::Platinum),
+                "Colony" => Ok(Card::Colony),
+                _ => s.parse::<engine::Card>().map(Card::from),
+            }
+        }
+    }
+
+    impl From<Card> for String {
+        fn from(card: Card) -> Self {
+            card.to_string()
+        }
+    }
+
+    impl TryFrom<String> for Card {
+        type Error = String;
+
+        fn try_from(s: String) -> Result<Self, Self::Error> {
+            s.parse()
+        }
+    }
+
+    impl Card {
+        fn is_victory(&self) -> bool {
+            match self {
+                Card::Estate | Card::Duchy | Card::Province | Card::Colony => true,
+                Card::Kingdom(card) => card.card_types().contains(&CardType::Victory),
+                _ => false,
+            }
+        }
+    }
+
+    /// What one player ended the game with
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Deck {
+        pub name: String,
+        pub cards: BTreeMap<Card, usize>,
+        #[serde(default)]
+        pub vp_tokens: i32,
+    }
+
+    /// One player's tally
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Score {
+        pub name: String,
+
+        /// What every card that scored was worth, all copies together
+        pub cards: BTreeMap<Card, i32>,
+
+        pub vp_tokens: i32,
+        pub total: i32,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum ScoringError {
+        /// A deck has a kingdom card the setup doesn't
+        NotInKingdom(KC),
+    }
+
+    /// What one copy of `card` is worth at the end of the game in a deck of
+    /// `cards`
+    ///
+    /// ```
+    /// use dominion::{scoring::{self, Card}, KC};
+    /// let cards = [(Card::Copper, 7), (Card::Kingdom(KC::Gardens), 3)].into_iter().collect();
+    /// assert_eq!(scoring::worth(&Card::Kingdom(KC::Gardens), &cards), 1);
+    /// ```
+    pub fn worth(card: &Card, cards: &BTreeMap<Card, usize>) -> i32 {
+        let count = |card: &Card| cards.get(card).copied().unwrap_or(0) as i32;
+
+        match card {
+            Card::Estate => 1,
+            Card::Duchy => 3,
+            Card::Province => 6,
+            Card::Colony => 10,
+            Card::Curse => -1,
+            Card::Kingdom(card) => match card {
+                KC::Mill => 1,
+                KC::Island | KC::Harem | KC::Nobles | KC::Tunnel | KC::Farmland => 2,
+
+                // 1 per 10 cards
+                KC::Gardens => cards.values().sum::<usize>() as i32 / 10,
+
+                // 1 per Duchy
+                KC::Duke => count(&Card::Duchy),
+
+                // 2 per 5 differently named cards
+                KC::Fairgrounds => {
+                    cards.values().filter(|count| **count > 0).count() as i32 / 5 * 2
+                }
+
+                // 1 per 4 victory cards
+                KC::SilkRoad => {
+                    cards
+                        .iter()
+                        .filter(|(card, _)| card.is_victory())
+                        .map(|(_, count)| *count as i32)
+                        .sum::<i32>()
+                        / 4
+                }
+
+                _ => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// What the cards that score are worth, all copies together
+    pub fn points(cards: &BTreeMap<Card, usize>) -> BTreeMap<Card, i32> {
+        cards
+            .iter()
+            .map(|(card, count)| (card.clone(), worth(card, cards) * *count as i32))
+            .filter(|(_, points)| *points != 0)
+            .collect()
+    }
+
+    /// Tally `deck`, which must only hold kingdom cards from `setup`
+    pub fn score(setup: &Setup, deck: &Deck) -> Result<Score, ScoringError> {
+        let kingdom: Vec<KC> = setup
+            .cards()
+            .into_iter()
+            .chain(setup.second_zebra.clone())
+            .collect();
+        for card in deck.cards.keys() {
+            if let Card::Kingdom(card) = card {
+                if !kingdom.contains(card) {
+                    return Err(ScoringError::NotInKingdom(card.clone()));
+                }
+            }
+        }
+
+        let cards = points(&deck.cards);
+        Ok(Score {
+            name: deck.name.clone(),
+            total: cards.values().sum::<i32>() + deck.vp_tokens,
+            cards,
+            vp_tokens: deck.vp_tokens,
+        })
+    }
+
+    /// Tally every player's deck, in order
+    pub fn score_all(setup: &Setup, decks: &[Deck]) -> Result<Vec<Score>, ScoringError> {
+        decks.iter().map(|deck| score(setup, deck)).collect()
+    }
 }
 
-impl Expansions for Project {
-    fn expansions(&self) -> Vec<Expansion> {
-        vec![Expansion::Renaissance]
+pub mod bots {
+    //! Automated players for the rules engine, to see how kingdoms play.
+    //!
+    //! A `Strategy` picks what to buy and which action to play; everything
+    //! else a card asks (discards, trashing, gains, ...) has a reasonable
+    //! default. A `Table` seats one strategy per player and answers the
+    //! engine's decisions for all of them.
+
+    use super::engine::{Card, Decider, Decision, DecisionKind, EngineError, Game};
+    use super::hist::Hist;
+    use super::*;
+
+    /// Games longer than this (in turns, by all players) are called off
+    pub const TURN_LIMIT: usize = 400;
+
+    /// A bot winning more than this share of games dominates the kingdom
+    pub const DOMINANCE: f64 = 0.75;
+
+    pub trait Strategy {
+        fn name(&self) -> String;
+
+        /// The card to buy from `options` (all affordable), or `None` to
+        /// stop buying
+        fn buy(&mut self, game: &Game, player: usize, options: &[Card]) -> Option<Card>;
+
+        /// The action to play from `options`, or `None` to stop playing.
+        /// Plays cards giving +Actions first.
+        fn play(&mut self, _game: &Game, _player: usize, options: &[Card]) -> Option<Card> {
+            options
+                .iter()
+                .max_by_key(|card| match card {
+                    Card::Kingdom(kc) => (kc.roles().contains(&Role::Village), kc.base_cost()),
+                    _ => (false, 0),
+                })
+                .cloned()
+        }
+
+        /// Everything else a card asks for
+        fn choose(&mut self, game: &Game, decision: &Decision) -> Vec<Card> {
+            let mut options = decision.options.clone();
+            let player = decision.player;
+
+            match decision.kind {
+                DecisionKind::PlayAction => self.play(game, player, &options).into_iter().collect(),
+                DecisionKind::Buy => self.buy(game, player, &options).into_iter().collect(),
+                DecisionKind::Gain => {
+                    let options: Vec<Card> = options
+                        .into_iter()
+                        .filter(|card| card != &Card::Curse)
+                        .collect();
+                    self.buy(game, player, &options)
+                        .or_else(|| options.iter().max_by_key(|card| card.cost()).cloned())
+                        .into_iter()
+                        .collect()
+                }
+                DecisionKind::Discard | DecisionKind::Trash => {
+                    options.sort_by_key(worth);
+                    let junk = options
+                        .iter()
+                        .take_while(|card| {
+                            worth(card) < 0
+                                || (decision.kind == DecisionKind::Trash
+                                    && card == &&Card::Copper
+                                    && decision.source != Some(Card::Kingdom(KC::Chapel)))
+                        })
+                        .count();
+                    options.truncate(junk.clamp(decision.min, decision.max));
+                    options
+                }
+                DecisionKind::TopDeck if decision.min < options.len() => {
+                    options.sort_by_key(|card| std::cmp::Reverse(worth(card)));
+                    let wanted = options.iter().filter(|card| worth(card) > 0).count();
+                    options.truncate(wanted.clamp(decision.min, decision.max));
+                    options
+                }
+                DecisionKind::TopDeck => {
+                    // The last card ends up on top, so the best goes last
+                    options.sort_by_key(worth);
+                    options
+                }
+                DecisionKind::SetAside => {
+                    if game.actions == 0 {
+                        options
+                    } else {
+                        vec![]
+                    }
+                }
+                DecisionKind::Play | DecisionKind::Reveal => options,
+            }
+        }
+    }
+
+    /// How much a card is worth keeping: junk is negative
+    fn worth(card: &Card) -> i32 {
+        match card {
+            Card::Curse => -2,
+            card if card.types() == vec![CardType::Victory] => -1,
+            Card::Copper => 0,
+            card => card.cost() as i32,
+        }
+    }
+
+    /// The reference bots
+    #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+    pub enum Bot {
+        /// Only treasures and victory cards
+        BigMoney,
+
+        /// Big Money with Smithies
+        BigMoneySmithy,
+
+        /// Big Money with one kingdom card from the setup
+        SingleCard(KC),
+    }
+
+    impl Bot {
+        /// The kingdom card the bot buys, if any
+        pub fn card(&self) -> Option<KC> {
+            match self {
+                Bot::BigMoney => None,
+                Bot::BigMoneySmithy => Some(KC::Smithy),
+                Bot::SingleCard(card) => Some(card.clone()),
+            }
+        }
+    }
+
+    impl std::fmt::Display for Bot {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Bot::SingleCard(card) => write!(f, "BigMoney+{:?}", card),
+                bot => write!(f, "{:?}", bot),
+            }
+        }
+    }
+
+    impl FromStr for Bot {
+        type Err = String;
+
+        /// Parse a bot name, or a kingdom card for a single card bot
+        ///
+        ///```
+        ///use dominion::{bots::Bot, KC};
+        ///assert_eq!("BigMoney".parse(), Ok(Bot::BigMoney));
+        ///assert_eq!("Witch".parse(), Ok(Bot::SingleCard(KC::Witch)));
+        ///assert_eq!("BigMoney+Witch".parse(), Ok(Bot::SingleCard(KC::Witch)));
+        ///```
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "BigMoney" => Ok(Bot::BigMoney),
+                "BigMoneySmithy" => Ok(Bot::BigMoneySmithy),
+                _ => s
+                    .trim_start_matches("BigMoney+")
+                    .parse()
+                    .map(Bot::SingleCard)
+                    .map_err(|_| {
+                        format!("{} is not BigMoney, BigMoneySmithy or a kingdom card", s)
+                    }),
+            }
+        }
+    }
+
+    impl Strategy for Bot {
+        fn name(&self) -> String {
+            self.to_string()
+        }
+
+        /// Provinces at 8, Gold at 6, the bot's card (one for every 12 cards
+        /// owned) then Silver, greening as the Provinces run low
+        fn buy(&mut self, game: &Game, player: usize, options: &[Card]) -> Option<Card> {
+            let provinces = game.pile(&Card::Province).map(|p| p.count).unwrap_or(0);
+            let owned: Vec<&Card> = game.players[player].cards().collect();
+            let has = |card: &Card| options.contains(card);
+
+            let wanted = self.card().map(Card::Kingdom).filter(|card| {
+                let copies = owned.iter().filter(|c| c == &&card).count();
+                has(card) && copies < 1 + owned.len() / 12
+            });
+
+            let choice = if has(&Card::Province) {
+                Card::Province
+            } else if has(&Card::Duchy) && provinces <= 4 {
+                Card::Duchy
+            } else if has(&Card::Gold) {
+                Card::Gold
+            } else if let Some(card) = wanted {
+                card
+            } else if has(&Card::Estate) && provinces <= 2 {
+                Card::Estate
+            } else if has(&Card::Silver) {
+                Card::Silver
+            } else {
+                return None;
+            };
+
+            Some(choice)
+        }
+    }
+
+    /// One strategy per player, answering the engine's decisions
+    pub struct Table<'a> {
+        pub seats: Vec<&'a mut dyn Strategy>,
+
+        /// What each seat bought
+        pub buys: Vec<Hist<Card>>,
+    }
+
+    impl<'a> Table<'a> {
+        pub fn new(seats: Vec<&'a mut dyn Strategy>) -> Self {
+            let buys = seats.iter().map(|_| Hist::empty()).collect();
+            Table { seats, buys }
+        }
+    }
+
+    impl Decider for Table<'_> {
+        fn decide(&mut self, game: &Game, decision: &Decision) -> Option<Vec<Card>> {
+            let choice = self.seats[decision.player].choose(game, decision);
+            if decision.kind == DecisionKind::Buy {
+                for card in &choice {
+                    let buys = std::mem::replace(&mut self.buys[decision.player], Hist::empty());
+                    self.buys[decision.player] = buys + Hist::one(card.clone());
+                }
+            }
+            Some(choice)
+        }
+    }
+
+    /// How one bot did over a simulation
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    pub struct Standing {
+        pub bot: Bot,
+
+        /// Ties are shared
+        pub wins: f64,
+
+        pub average_score: f64,
+
+        /// Every card the bot bought, over all games
+        pub buys: Hist<Card>,
+    }
+
+    /// The outcome of many games between bots
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    pub struct Simulation {
+        pub games: usize,
+
+        /// How many turns each player took in a game, on average
+        pub average_turns: f64,
+
+        /// The share of games with a shared win
+        pub tie_rate: f64,
+
+        pub standings: Vec<Standing>,
+    }
+
+    impl Simulation {
+        /// The bot winning more than `share` of the games, if any
+        pub fn dominant(&self, share: f64) -> Option<&Standing> {
+            self.standings
+                .iter()
+                .find(|standing| self.games > 0 && standing.wins / self.games as f64 > share)
+        }
+    }
+
+    /// Running totals, per bot
+    struct Tally {
+        wins: Vec<f64>,
+        scores: Vec<i64>,
+        buys: Vec<Hist<Card>>,
+        turns: usize,
+        ties: usize,
+    }
+
+    impl Tally {
+        fn new(bots: usize) -> Self {
+            Tally {
+                wins: vec![0.0; bots],
+                scores: vec![0; bots],
+                buys: vec![Hist::empty(); bots],
+                turns: 0,
+                ties: 0,
+            }
+        }
+
+        fn merge(mut self, other: Tally) -> Tally {
+            for (bot, buys) in other.buys.into_iter().enumerate() {
+                self.wins[bot] += other.wins[bot];
+                self.scores[bot] += other.scores[bot];
+                self.buys[bot] = self.buys[bot].clone() + buys;
+            }
+            self.turns += other.turns;
+            self.ties += other.ties;
+            self
+        }
+    }
+
+    /// Play one game of `setup`, `bots[s]` in seat `s`, returning the finished
+    /// (or called off) game and what each seat bought
+    pub fn play(
+        setup: &Setup,
+        bots: &[Bot],
+        seed: u64,
+    ) -> Result<(Game, Vec<Hist<Card>>), EngineError> {
+        let mut seated = bots.to_vec();
+        let mut table = Table::new(
+            seated
+                .iter_mut()
+                .map(|bot| bot as &mut dyn Strategy)
+                .collect(),
+        );
+
+        let mut game = Game::new(setup, bots.len(), seed)?;
+        while !game.is_over() && game.turn < TURN_LIMIT {
+            game.take_turn(&mut table)?;
+        }
+
+        Ok((game, table.buys))
+    }
+
+    /// Play game `i` of a simulation into `tally`
+    fn play_into(
+        setup: &Setup,
+        bots: &[Bot],
+        i: usize,
+        seed: u64,
+        tally: &mut Tally,
+    ) -> Result<(), EngineError> {
+        // Seat `s` is played by bot `(s + i) % bots.len()`
+        let bot = |seat: usize| (seat + i) % bots.len();
+        let seated: Vec<Bot> = (0..bots.len()).map(|s| bots[bot(s)].clone()).collect();
+        let (game, buys) = play(setup, &seated, seed.wrapping_add(i as u64))?;
+        tally.turns += game.turn;
+
+        let winners = winners(&game);
+        tally.ties += winners.iter().any(|won| *won > 0.0 && *won < 1.0) as usize;
+        for (seat, (won, buys)) in winners.into_iter().zip(buys).enumerate() {
+            tally.wins[bot(seat)] += won;
+            tally.scores[bot(seat)] += game.score(seat) as i64;
+            tally.buys[bot(seat)] = tally.buys[bot(seat)].clone() + buys;
+        }
+
+        Ok(())
+    }
+
+    /// Play `games` games of `setup` between `bots`, rotating who starts,
+    /// spread over the machine's cores. Game `i` uses seed `seed + i`.
+    pub fn simulate(
+        setup: &Setup,
+        bots: &[Bot],
+        games: usize,
+        seed: u64,
+    ) -> Result<Simulation, EngineError> {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        simulate_on(setup, bots, games, seed, cores)
+    }
+
+    /// `simulate` on `threads` threads, which doesn't change the result
+    pub fn simulate_on(
+        setup: &Setup,
+        bots: &[Bot],
+        games: usize,
+        seed: u64,
+        threads: usize,
+    ) -> Result<Simulation, EngineError> {
+        if let Some(card) = bots
+            .iter()
+            .filter_map(Bot::card)
+            .find(|card| !setup.kingdom_cards.contains(card))
+        {
+            return Err(EngineError::NotInSupply(card));
+        }
+
+        let threads = threads.clamp(1, games.max(1));
+        let share = |thread: usize| -> Result<Tally, EngineError> {
+            let mut tally = Tally::new(bots.len());
+            for i in (thread..games).step_by(threads) {
+                play_into(setup, bots, i, seed, &mut tally)?;
+            }
+            Ok(tally)
+        };
+
+        let tallies: Vec<Result<Tally, EngineError>> = if threads == 1 {
+            vec![share(0)]
+        } else {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..threads)
+                    .map(|thread| scope.spawn(move || share(thread)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect()
+            })
+        };
+        let tally = tallies
+            .into_iter()
+            .try_fold(Tally::new(bots.len()), |total, tally| {
+                Ok::<_, EngineError>(total.merge(tally?))
+            })?;
+
+        let per_game = |total: f64| {
+            if games == 0 {
+                0.0
+            } else {
+                total / games as f64
+            }
+        };
+
+        Ok(Simulation {
+            games,
+            // `game.turn` counts every seat's turns
+            average_turns: per_game(tally.turns as f64) / bots.len().max(1) as f64,
+            tie_rate: per_game(tally.ties as f64),
+            standings: bots
+                .iter()
+                .zip(tally.buys)
+                .enumerate()
+                .map(|(i, (bot, buys))| Standing {
+                    bot: bot.clone(),
+                    wins: tally.wins[i],
+                    average_score: per_game(tally.scores[i] as f64),
+                    buys,
+                })
+                .collect(),
+        })
+    }
+
+    /// Each player's share of the win: the most points wins, ties going to
+    /// whoever had fewer turns, and remaining ties are shared
+    pub fn winners(game: &Game) -> Vec<f64> {
+        let players = game.players.len();
+        let rank = |player: usize| {
+            let turns = game.turn / players + (player < game.turn % players) as usize;
+            (game.score(player), std::cmp::Reverse(turns))
+        };
+
+        let best = (0..players).map(rank).max();
+        let tied = (0..players).filter(|p| Some(rank(*p)) == best).count();
+
+        (0..players)
+            .map(|p| {
+                if Some(rank(p)) == best {
+                    1.0 / tied as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect()
     }
 }
 
-/// A game's setup
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Setup {
-    pub kingdom_cards: Vec<KC>,
-    pub bane_card: Option<KC>,
-    pub project_cards: Vec<Project>,
-    pub bane_cards: HashMap<KC, BaneCard>,
-    pub second_zebra: Option<KC>,
-}
+pub mod gamelog {
+    //! Replayable game logs, as JSON lines: a `Header` tying the log to its
+    //! setup, players and seed, then one `engine::Event` per line. The
+    //! decisions in a log are enough to play the game again, and `verify`
+    //! does exactly that, checking every other event along the way.
+
+    use super::engine::{Event, Game, Scripted};
+    use super::*;
+
+    /// Bumped whenever a log written before can't be read the same way
+    pub const LOG_VERSION: u32 = 1;
+
+    /// The first line of a log
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Header {
+        /// Always `LOG_VERSION`
+        pub version: u32,
+
+        pub setup: Setup,
+        pub players: usize,
+        pub seed: u64,
+
+        /// Who sat in each seat, if known
+        #[serde(default)]
+        pub names: Vec<String>,
+    }
+
+    /// Reasons a log doesn't check out
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum LogError {
+        /// There's no header
+        Empty,
+
+        /// A line (counting from 1) isn't a header or an event
+        Parse { line: usize, message: String },
+
+        /// The log was written in a version this can't read
+        Version(u32),
+
+        /// The header's setup breaks the rules of setting up
+        InvalidSetup(Vec<SetupError>),
 
-impl Setup {
-    pub fn new(
-        kingdom_cards: Vec<KC>,
-        bane_card: Option<KC>,
-        project_cards: Vec<Project>,
-        bane_cards: HashMap<KC, BaneCard>,
-        second_zebra: Option<KC>,
-    ) -> Self {
-        Self {
-            kingdom_cards,
-            bane_card,
-            project_cards,
-            bane_cards,
-            second_zebra,
-        }
+        /// The game couldn't be set up or played from the log's decisions
+        Engine(engine::EngineError),
+
+        /// Playing the game again went differently from the log at this line
+        Mismatch {
+            line: usize,
+            expected: Option<Box<Event>>,
+            got: Option<Box<Event>>,
+        },
     }
 
-    pub fn bane(bane: KC, other_kingdom: Vec<KC>) -> Self {
-        Self {
-            kingdom_cards: other_kingdom,
-            bane_card: Some(bane),
-            project_cards: vec![],
-            bane_cards: HashMap::new(),
-            second_zebra: None,
+    /// A log of everything that happened in `game` so far
+    pub fn write(setup: &Setup, names: Vec<String>, game: &Game) -> String {
+        let header = Header {
+            version: LOG_VERSION,
+            setup: setup.clone(),
+            players: game.players.len(),
+            seed: game.seed,
+            names,
+        };
+
+        let mut log = serde_json::to_string(&header).unwrap();
+        for event in &game.events {
+            log.push('\n');
+            log.push_str(&serde_json::to_string(event).unwrap());
         }
+        log.push('\n');
+        log
     }
 
-    pub fn cards(&self) -> Vec<KC> {
-        let mut results = self.kingdom_cards.clone();
-        for bane in &self.bane_card {
-            results.push(bane.clone());
+    pub fn parse(log: &str) -> Result<(Header, Vec<Event>), LogError> {
+        let mut lines = log
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let parse_error = |line: usize, err: serde_json::Error| LogError::Parse {
+            line: line + 1,
+            message: err.to_string(),
+        };
+
+        let (i, first) = lines.next().ok_or(LogError::Empty)?;
+        let header: Header = serde_json::from_str(first).map_err(|err| parse_error(i, err))?;
+        if header.version != LOG_VERSION {
+            return Err(LogError::Version(header.version));
         }
-        results
+        let problems = header.setup.validate();
+        if !problems.is_empty() {
+            return Err(LogError::InvalidSetup(problems));
+        }
+
+        let events = lines
+            .map(|(i, line)| serde_json::from_str(line).map_err(|err| parse_error(i, err)))
+            .collect::<Result<Vec<Event>, LogError>>()?;
+
+        Ok((header, events))
     }
-}
 
-#[wasm_bindgen]
-pub fn setup_kingdom_cards_js(json: &JsValue) -> JsValue {
-    let setup: Setup = json.into_serde().unwrap();
-    JsValue::from_serde(&setup.cards()).unwrap()
-}
+    /// Play the logged game again from its seed and decisions, and check
+    /// that everything else happens just as logged
+    pub fn replay(header: &Header, events: &[Event]) -> Result<Game, LogError> {
+        let mut game =
+            Game::new(&header.setup, header.players, header.seed).map_err(LogError::Engine)?;
+        let mut decider = Scripted::new(
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Decision { choice, .. } => Some(choice.clone()),
+                    _ => None,
+                })
+                .collect(),
+        );
+        let turns = events
+            .iter()
+            .filter(|event| matches!(event, Event::Turn { .. }))
+            .count();
+
+        let mut played = Ok(());
+        for _ in 0..turns {
+            played = game.take_turn(&mut decider);
+            if played.is_err() {
+                break;
+            }
+        }
 
-/// The number of projects allowed in a game
-#[derive(EnumString, Debug, Deserialize_repr, Serialize_repr, EnumIter, Clone)]
-#[repr(u8)]
-pub enum ProjectCount {
-    #[strum(serialize = "0")]
-    NoProjects = 0,
-    #[strum(serialize = "1")]
-    OneProject = 1,
-    #[strum(serialize = "2")]
-    TwoProjects = 2,
-}
+        let mismatch = (0..events.len().max(game.events.len()))
+            .find(|i| events.get(*i) != game.events.get(*i))
+            .map(|i| LogError::Mismatch {
+                // The header is line 1
+                line: i + 2,
+                expected: events.get(i).cloned().map(Box::new),
+                got: game.events.get(i).cloned().map(Box::new),
+            });
+
+        // An event that went differently explains a failed turn better than
+        // the engine's error, which explains the game stopping short better
+        // than the missing events do
+        match (mismatch, played) {
+            (Some(mismatch @ LogError::Mismatch { got: Some(_), .. }), _) => Err(mismatch),
+            (_, Err(err)) => Err(LogError::Engine(err)),
+            (Some(mismatch), Ok(())) => Err(mismatch),
+            (None, Ok(())) => Ok(game),
+        }
+    }
 
-impl ProjectCount {
-    /// Convert enum to actual count
+    /// Parse and replay a log, giving the game it ends with
     ///
-    ///```
-    ///let count = dominion::ProjectCount::OneProject.count();
-    ///assert_eq!(count, 1);
-    ///```
-    pub fn count(&self) -> usize {
-        match self {
-            ProjectCount::NoProjects => 0,
-            ProjectCount::OneProject => 1,
-            ProjectCount::TwoProjects => 2,
+    /// ```
+    /// use dominion::{bots::{self, Bot}, gamelog, KC, Setup};
+    /// use std::collections::HashMap;
+    ///
+    /// let kingdom = vec![
+    ///     KC::Cellar, KC::Chapel, KC::Moat, KC::Village, KC::Workshop,
+    ///     KC::Smithy, KC::Gardens, KC::Militia, KC::Market, KC::Witch,
+    /// ];
+    /// let setup = Setup::new(kingdom, None, vec![], HashMap::new(), None);
+    /// let bots = [Bot::BigMoney, Bot::BigMoneySmithy];
+    /// let (game, _) = bots::play(&setup, &bots, 3).unwrap();
+    ///
+    /// let log = gamelog::write(&setup, vec![], &game);
+    /// assert_eq!(gamelog::verify(&log), Ok(game));
+    /// ```
+    pub fn verify(log: &str) -> Result<Game, LogError> {
+        let (header, events) = parse(log)?;
+        replay(&header, &events)
+    }
+
+    /// The events split into turns. The first group is the setup, before
+    /// anyone's turn.
+    pub fn turns(events: &[Event]) -> Vec<&[Event]> {
+        let mut turns = vec![];
+        let mut start = 0;
+
+        for (i, event) in events.iter().enumerate() {
+            if matches!(event, Event::Turn { .. }) {
+                turns.push(&events[start..i]);
+                start = i;
+            }
         }
+        turns.push(&events[start..]);
+
+        turns
     }
 }
 
-/// The number of custom bane cards allowed in a game
-#[derive(EnumString, Debug, Deserialize_repr, Serialize_repr, EnumIter, Clone)]
-#[repr(u8)]
-pub enum BaneCount {
-    #[strum(serialize = "0")]
-    NoBanes = 0,
-    #[strum(serialize = "1")]
-    OneBane = 1,
-    #[strum(serialize = "2")]
-    TwoBanes = 2,
-    #[strum(serialize = "3")]
-    ThreeBanes = 3,
-}
+pub mod count {
+    //! How many kingdoms a config allows, and a draw from them where every
+    //! one is equally likely. `gen_setup` can't promise that: it picks the
+    //! project count before the projects, and starts over when a Young Witch
+    //! kingdom has no bane left, so some kingdoms come up more than others.
+    //!
+    //! The cards, expansions and counts are counted, expansion counts too:
+    //! kingdoms are counted a tally of the bounded expansions at a time (see
+    //! `Picks`). `require_combo`, `avoid_swingy`, `max_similarity`,
+    //! `max_power` and `separate_top_cards` are checked on each drawn
+    //! kingdom, which keeps the draw uniform over the kingdoms that pass
+    //! them. Weights are left out: a uniform draw has none.
 
-impl BaneCount {
-    /// Convert enum to actual count
-    ///
-    ///```
-    ///let count = dominion::BaneCount::OneBane.count();
-    ///assert_eq!(count, 1);
-    ///```
-    pub fn count(&self) -> usize {
-        match self {
-            BaneCount::NoBanes => 0,
-            BaneCount::OneBane => 1,
-            BaneCount::TwoBanes => 2,
-            BaneCount::ThreeBanes => 3,
+    use super::*;
+    use std::cell::RefCell;
+
+    /// How many distinct setups a config allows, and what they're made of
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Count {
+        /// Cards the kingdom cards that aren't included are picked from
+        pub pool: usize,
+
+        /// Sets of ten kingdom cards
+        pub kingdoms: u128,
+
+        /// Of those, the ones with Young Witch
+        pub young_witch_kingdoms: u128,
+
+        /// Kingdoms with their Young Witch bane, if they need one. A Young
+        /// Witch kingdom with no card left to be its bane isn't counted.
+        pub with_bane: u128,
+
+        /// Ways to pick the projects
+        pub projects: u128,
+
+        /// Ways to put the custom bane cards on a kingdom's cards
+        pub custom_banes: u128,
+
+        /// Distinct setups: every kingdom with its bane, projects, custom
+        /// bane cards and second Zebra card
+        pub total: u128,
+    }
+
+    /// What a config's kingdoms are drawn from
+    struct Pools {
+        forced: Vec<KC>,
+
+        /// `cheap` and `other`, grouped for the expansion counts
+        picks: Picks,
+
+        /// Young Witch is included, rather than in `cheap` or `other`
+        young_witch_forced: bool,
+
+        /// Young Witch can be picked
+        young_witch: bool,
+
+        /// Cards that cost 2 or 3, so could be the bane or the second Zebra
+        cheap: Vec<KC>,
+
+        other: Vec<KC>,
+        projects: Vec<Project>,
+
+        /// The project counts the config allows
+        project_counts: Vec<usize>,
+
+        bane_count: usize,
+    }
+
+    /// Kingdoms alike in what's left for the bane and the second Zebra: the
+    /// ones with (or without) Young Witch picked and `cheap` cheap cards
+    struct Stratum {
+        young_witch: bool,
+        cheap: usize,
+
+        /// Sets of kingdom cards in it
+        kingdoms: u128,
+
+        /// Ways to pick the bane for each
+        banes: u128,
+
+        /// Cards left for a second Zebra once the bane is picked
+        zebras: usize,
+    }
+
+    impl Pools {
+        fn new(config: &SetupConfig) -> Result<Self, GenSetupError> {
+            if let Some(error) = config.validate().into_iter().next() {
+                return Err(error);
+            }
+
+            let desired_expansions = desired_expansions(config);
+            let banned_cards = config.ban_cards.clone().unwrap_or_default();
+            let mut forced: Vec<KC> = config
+                .include_cards
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            forced.sort();
+
+            let pool: Vec<KC> = KC::iter()
+                .filter(|kc| !expansion_set(kc).is_disjoint(&desired_expansions))
+                .filter(|kc| !banned_cards.contains(kc))
+                .filter(|kc| !forced.contains(kc))
+                .collect();
+
+            let projects: Vec<Project> = Project::iter()
+                .filter(|p| !expansion_set(p).is_disjoint(&desired_expansions))
+                .collect();
+            let project_counts = match &config.project_count {
+                Some(desired) => vec![desired.count()],
+                None => (0..3).filter(|count| *count <= projects.len()).collect(),
+            };
+
+            let picked: Vec<KC> = pool
+                .iter()
+                .filter(|kc| **kc != KC::YoungWitch)
+                .cloned()
+                .collect();
+
+            Ok(Pools {
+                picks: Picks::new(&expansion_counts(config), &picked, true),
+                young_witch_forced: forced.contains(&KC::YoungWitch),
+                young_witch: pool.contains(&KC::YoungWitch),
+                cheap: pool
+                    .iter()
+                    .filter(|kc| **kc != KC::YoungWitch && is_bane_cost(kc))
+                    .cloned()
+                    .collect(),
+                other: pool
+                    .iter()
+                    .filter(|kc| **kc != KC::YoungWitch && !is_bane_cost(kc))
+                    .cloned()
+                    .collect(),
+                forced,
+                projects,
+                project_counts,
+                bane_count: config.bane_count.as_ref().map_or(0, |bc| bc.count()),
+            })
+        }
+
+        /// How many kingdom cards are picked rather than included
+        fn picked(&self) -> usize {
+            10 - self.forced.len()
+        }
+
+        /// The bounded expansions' tallies before anything's picked, with or
+        /// without a picked Young Witch
+        fn tally(&self, young_witch: bool) -> Vec<usize> {
+            let young_witch = young_witch.then_some(&KC::YoungWitch);
+            self.picks.tally(self.forced.iter().chain(young_witch))
+        }
+
+        fn strata(&self) -> Vec<Stratum> {
+            let mut strata = vec![];
+
+            for young_witch in [false, true] {
+                if young_witch && !self.young_witch {
+                    continue;
+                }
+
+                for cheap in 0..=self.cheap.len() {
+                    let Some(rest) = self.picked().checked_sub(cheap + young_witch as usize) else {
+                        continue;
+                    };
+                    let kingdoms = self.picks.ways(cheap, rest, &self.tally(young_witch));
+                    let cheap_left = self.cheap.len() - cheap;
+
+                    let (banes, zebras) = if young_witch || self.young_witch_forced {
+                        (cheap_left as u128, cheap_left.saturating_sub(1))
+                    } else {
+                        (1, cheap_left)
+                    };
+
+                    if kingdoms > 0 {
+                        strata.push(Stratum {
+                            young_witch,
+                            cheap,
+                            kingdoms,
+                            banes,
+                            zebras,
+                        });
+                    }
+                }
+            }
+
+            strata
+        }
+
+        fn project_sets(&self, count: usize) -> u128 {
+            choose(self.projects.len(), count)
+        }
+
+        /// Ways to put the custom banes on the cards without a Zebra, and
+        /// with one
+        fn custom_banes(&self) -> (u128, u128) {
+            let banes = BaneCard::iter().count();
+            let cards = choose(10, self.bane_count);
+            let without = arrangements(banes - 1, self.bane_count);
+
+            (
+                cards * without,
+                cards * (arrangements(banes, self.bane_count) - without),
+            )
+        }
+
+        /// Setups in `stratum`, with every bane, custom bane and second Zebra
+        fn setups(&self, stratum: &Stratum) -> u128 {
+            let (without_zebra, with_zebra) = self.custom_banes();
+            stratum.kingdoms * stratum.banes * (without_zebra + with_zebra * stratum.zebras as u128)
         }
     }
-}
 
-/// How to setup a game
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SetupConfig {
-    /// Which specific expansions to include
-    pub include_expansions: Option<HashSet<Expansion>>,
+    /// The cards a kingdom's picks come from, grouped by the bounded
+    /// expansions they count towards (and, when counting, by whether they
+    /// could be a bane), so the picks meeting the expansion counts can be
+    /// counted and drawn a group at a time rather than a card at a time
+    pub(crate) struct Picks {
+        /// The expansions with a minimum or maximum, and their counts
+        bounded: Vec<(Expansion, ExpansionCount)>,
 
-    /// Cards to be sure _not_ to include
-    pub ban_cards: Option<HashSet<KC>>,
+        /// The groups, in order of the expansions they count towards
+        groups: Vec<Group>,
 
-    /// Cards to be sure to include. It is okay for this to be inconsistent with
-    /// `include_expansions` (e.g. value here not member of listed expansions).
-    pub include_cards: Option<HashSet<KC>>,
+        /// The cheap and other cards in each group and those after it
+        cheap_after: Vec<usize>,
+        other_after: Vec<usize>,
 
-    /// How many projects to include (for random of count)
-    /// If expansions are provided and we can't pick enough projects to satisfy
-    /// this count, we'll return an error
-    pub project_count: Option<ProjectCount>,
+        /// `ways` already worked out
+        memo: RefCell<HashMap<WaysFrom, u128>>,
+    }
 
-    /// How many bane cards to include (for random of count)
-    /// The "Bane Expansion" is my custom expansion
-    pub bane_count: Option<BaneCount>,
-}
+    /// The group, cheap and other cards left to pick, and the tally so far
+    type WaysFrom = (usize, usize, usize, Vec<usize>);
 
-impl SetupConfig {
-    /// Give us a totally random game
-    pub fn none() -> SetupConfig {
-        SetupConfig {
-            include_expansions: None,
-            ban_cards: None,
-            include_cards: None,
-            project_count: None,
-            bane_count: None,
+    struct Group {
+        cards: Vec<KC>,
+        cheap: bool,
+
+        /// The indices in `bounded` the cards count towards
+        counted: Vec<usize>,
+
+        /// The indices in `bounded` no later group counts towards, whose
+        /// tallies are settled once this group is picked from
+        closes: Vec<usize>,
+    }
+
+    impl Picks {
+        /// `pool` grouped for `counts`, with the cards costing 2 or 3 apart
+        /// from the others when `split_cheap`
+        pub(crate) fn new(
+            counts: &HashMap<Expansion, ExpansionCount>,
+            pool: &[KC],
+            split_cheap: bool,
+        ) -> Self {
+            let mut bounded: Vec<(Expansion, ExpansionCount)> = counts
+                .iter()
+                .filter(|(_, count)| count.min > 0 || count.max.is_some())
+                .map(|(expansion, count)| (expansion.clone(), count.clone()))
+                .collect();
+            bounded.sort_by_key(|(expansion, _)| format!("{:?}", expansion));
+
+            let mut groups: Vec<(bool, Vec<usize>, Vec<KC>)> = vec![];
+            for card in pool {
+                let cheap = split_cheap && is_bane_cost(card);
+                let counted = Self::counted(&bounded, card);
+                match groups
+                    .iter_mut()
+                    .find(|(c, expansions, _)| *c == cheap && *expansions == counted)
+                {
+                    Some((_, _, cards)) => cards.push(card.clone()),
+                    None => groups.push((cheap, counted, vec![card.clone()])),
+                }
+            }
+            // Keeping the groups of an expansion together lets its tally be
+            // settled early, which keeps down the tallies to remember
+            groups.sort_by_key(|(cheap, expansions, _)| (expansions.clone(), !cheap));
+
+            let mut groups: Vec<Group> = groups
+                .into_iter()
+                .map(|(cheap, counted, cards)| Group {
+                    cards,
+                    cheap,
+                    counted,
+                    closes: vec![],
+                })
+                .collect();
+            for i in 0..bounded.len() {
+                if let Some(last) = groups.iter().rposition(|group| group.counted.contains(&i)) {
+                    groups[last].closes.push(i);
+                }
+            }
+
+            let after = |cheap: bool| -> Vec<usize> {
+                let mut after = vec![0; groups.len() + 1];
+                for (i, group) in groups.iter().enumerate().rev() {
+                    let here = if group.cheap == cheap {
+                        group.cards.len()
+                    } else {
+                        0
+                    };
+                    after[i] = after[i + 1] + here;
+                }
+                after
+            };
+
+            Picks {
+                cheap_after: after(true),
+                other_after: after(false),
+                groups,
+                bounded,
+                memo: RefCell::new(HashMap::new()),
+            }
+        }
+
+        /// The indices in `bounded` that `card` counts towards
+        fn counted(bounded: &[(Expansion, ExpansionCount)], card: &KC) -> Vec<usize> {
+            let expansions = card.expansions();
+            bounded
+                .iter()
+                .enumerate()
+                .filter(|(_, (expansion, _))| expansions.contains(expansion))
+                .map(|(i, _)| i)
+                .collect()
+        }
+
+        /// The bounded expansions' tallies of `cards` (already in the
+        /// kingdom), for `ways` and `draw`
+        pub(crate) fn tally<'a>(&self, cards: impl IntoIterator<Item = &'a KC>) -> Vec<usize> {
+            let mut tally = vec![0; self.bounded.len()];
+            for card in cards {
+                for i in Self::counted(&self.bounded, card) {
+                    tally[i] += 1;
+                }
+            }
+            self.settle(tally)
+        }
+
+        /// `tally` with counts past a minimum and no maximum taken back to
+        /// the minimum, as more makes no difference
+        fn settle(&self, mut tally: Vec<usize>) -> Vec<usize> {
+            for (n, (_, count)) in tally.iter_mut().zip(&self.bounded) {
+                if count.max.is_none() {
+                    *n = (*n).min(count.min);
+                }
+            }
+            tally
+        }
+
+        /// Ways to pick `cheap` cards from the cheap groups and `other`
+        /// from the rest so that, with the `tally` so far, every count is met
+        pub(crate) fn ways(&self, cheap: usize, other: usize, tally: &[usize]) -> u128 {
+            self.ways_from(0, cheap, other, tally)
+        }
+
+        fn ways_from(&self, group: usize, cheap: usize, other: usize, tally: &[usize]) -> u128 {
+            if group == self.groups.len() {
+                let met = self
+                    .bounded
+                    .iter()
+                    .zip(tally)
+                    .all(|((_, count), n)| count.allows(*n));
+                return (cheap == 0 && other == 0 && met) as u128;
+            }
+            if cheap > self.cheap_after[group] || other > self.other_after[group] {
+                return 0;
+            }
+
+            let key = (group, cheap, other, tally.to_vec());
+            if let Some(ways) = self.memo.borrow().get(&key) {
+                return *ways;
+            }
+            let ways = self
+                .choices(group, cheap, other, tally)
+                .iter()
+                .map(|(_, ways)| ways)
+                .sum();
+            self.memo.borrow_mut().insert(key, ways);
+            ways
+        }
+
+        /// Each number of `group`'s cards that could be picked, with the
+        /// ways to pick them and the rest
+        fn choices(
+            &self,
+            group: usize,
+            cheap: usize,
+            other: usize,
+            tally: &[usize],
+        ) -> Vec<(usize, u128)> {
+            let cards = &self.groups[group].cards;
+            let is_cheap = self.groups[group].cheap;
+            let left = if is_cheap { cheap } else { other };
+
+            (0..=left.min(cards.len()))
+                .map(|k| {
+                    let Some(next) = self.advance(group, tally, k) else {
+                        return (k, 0);
+                    };
+
+                    let (cheap, other) = if is_cheap {
+                        (cheap - k, other)
+                    } else {
+                        (cheap, other - k)
+                    };
+                    let rest = self.ways_from(group + 1, cheap, other, &next);
+                    (k, choose(cards.len(), k) * rest)
+                })
+                .collect()
+        }
+
+        /// `tally` after picking `k` of `group`'s cards, or `None` if that
+        /// breaks a count. The counts no later group affects are checked
+        /// here and their tallies set to the minimum, so tallies that can
+        /// no longer make a difference are remembered as one.
+        fn advance(&self, group: usize, tally: &[usize], k: usize) -> Option<Vec<usize>> {
+            let group = &self.groups[group];
+            let mut next = tally.to_vec();
+            for i in &group.counted {
+                next[*i] += k;
+                if self.bounded[*i].1.max.is_some_and(|max| next[*i] > max) {
+                    return None;
+                }
+            }
+            for i in &group.closes {
+                let count = &self.bounded[*i].1;
+                if !count.allows(next[*i]) {
+                    return None;
+                }
+                next[*i] = count.min;
+            }
+
+            Some(self.settle(next))
+        }
+
+        /// One of the `ways`, every one equally likely: the cards picked,
+        /// and the cheap cards left in random order
+        fn draw<R: Rng>(
+            &self,
+            mut cheap: usize,
+            mut other: usize,
+            tally: &[usize],
+            rng: &mut R,
+        ) -> (Vec<KC>, Vec<KC>) {
+            let mut tally = tally.to_vec();
+            let mut counts = vec![];
+            for group in 0..self.groups.len() {
+                let choices = self.choices(group, cheap, other, &tally);
+                let possible: Vec<&(usize, u128)> =
+                    choices.iter().filter(|(_, ways)| *ways > 0).collect();
+                // Only spend randomness on a real choice, so configs without
+                // expansion counts draw what they always have
+                let k = match possible.as_slice() {
+                    [(k, _)] => *k,
+                    _ => {
+                        let mut pick = rng.gen_range(0..possible.iter().map(|(_, w)| w).sum());
+                        possible
+                            .iter()
+                            .find(|(_, ways)| {
+                                if pick < *ways {
+                                    return true;
+                                }
+                                pick -= ways;
+                                false
+                            })
+                            .expect("the picks add up to the ways")
+                            .0
+                    }
+                };
+
+                tally = self
+                    .advance(group, &tally, k)
+                    .expect("only possible picks are drawn");
+                if self.groups[group].cheap {
+                    cheap -= k;
+                } else {
+                    other -= k;
+                }
+                counts.push(k);
+            }
+
+            let mut picked = vec![];
+            let mut cheap_left = vec![];
+            for (group, k) in self.groups.iter().zip(counts) {
+                let mut cards = group.cards.clone();
+                cards.shuffle(rng);
+                picked.extend(cards.drain(..k));
+                if group.cheap {
+                    cheap_left.extend(cards);
+                }
+            }
+            if self.groups.iter().filter(|group| group.cheap).count() > 1 {
+                cheap_left.shuffle(rng);
+            }
+
+            (picked, cheap_left)
         }
     }
 
-    /// Only include these expansions
-    pub fn including_expansions(expansions: HashSet<Expansion>) -> SetupConfig {
-        SetupConfig {
-            include_expansions: Some(expansions),
-            ban_cards: None,
-            include_cards: None,
-            project_count: None,
-            bane_count: None,
+    /// Ways to pick `k` of `n` things
+    fn choose(n: usize, k: usize) -> u128 {
+        if k > n {
+            return 0;
+        }
+
+        (0..k).fold(1, |ways, i| ways * (n - i) as u128 / (i + 1) as u128)
+    }
+
+    /// Ways to pick `k` of `n` things in order
+    fn arrangements(n: usize, k: usize) -> u128 {
+        if k > n {
+            return 0;
         }
+
+        (0..k).map(|i| (n - i) as u128).product()
+    }
+
+    /// How many setups the config's cards, expansions and counts allow
+    ///
+    /// ```
+    /// use dominion::{count::count, Expansion, SetupConfig};
+    /// use std::collections::HashSet;
+    /// let config = SetupConfig::including_expansions(HashSet::from([Expansion::Base2]));
+    /// // 26 Base2 cards, no projects and no banes
+    /// assert_eq!(count(&config).unwrap().total, 5_311_735);
+    /// ```
+    pub fn count(config: &SetupConfig) -> Result<Count, GenSetupError> {
+        let pools = Pools::new(config)?;
+        let strata = pools.strata();
+        let projects: u128 = pools
+            .project_counts
+            .iter()
+            .map(|count| pools.project_sets(*count))
+            .sum();
+        let (without_zebra, with_zebra) = pools.custom_banes();
+
+        Ok(Count {
+            pool: pools.cheap.len() + pools.other.len() + pools.young_witch as usize,
+            kingdoms: strata.iter().map(|stratum| stratum.kingdoms).sum(),
+            young_witch_kingdoms: strata
+                .iter()
+                .filter(|stratum| stratum.young_witch || pools.young_witch_forced)
+                .map(|stratum| stratum.kingdoms)
+                .sum(),
+            with_bane: strata
+                .iter()
+                .map(|stratum| stratum.kingdoms * stratum.banes)
+                .sum(),
+            projects,
+            custom_banes: without_zebra + with_zebra,
+            total: strata
+                .iter()
+                .map(|stratum| pools.setups(stratum))
+                .sum::<u128>()
+                * projects,
+        })
+    }
+
+    /// A setup drawn uniformly from the ones `count` counts, and passing the
+    /// config's other checks
+    pub fn sample(config: SetupConfig) -> Result<Setup, GenSetupError> {
+        sample_with_rng(config, &[], &mut rand::thread_rng())
+    }
+
+    /// `sample`, drawing randomness from `rng`
+    ///
+    /// A stratum is picked by how many setups it has, then every piece of
+    /// the setup is picked uniformly from what's left. The pieces left never
+    /// depend on more than the stratum, so each setup is drawn with
+    /// probability 1 / `total`. Rejecting the ones that fail the other
+    /// checks (`max_similarity` against the `previous` kingdoms) leaves the
+    /// rest equally likely.
+    pub fn sample_with_rng<R: Rng>(
+        config: SetupConfig,
+        previous: &[Setup],
+        rng: &mut R,
+    ) -> Result<Setup, GenSetupError> {
+        let pools = Pools::new(&config)?;
+        first_passing(&config, previous, || draw(&pools, rng))
     }
 
-    /// Be sure to include (at least) these cards
-    pub fn including_cards(cards: HashSet<KC>) -> SetupConfig {
-        SetupConfig {
-            include_expansions: None,
-            ban_cards: None,
-            include_cards: Some(cards),
-            project_count: None,
-            bane_count: None,
+    fn draw<R: Rng>(pools: &Pools, rng: &mut R) -> Result<Setup, GenSetupError> {
+        let strata = pools.strata();
+        let total: u128 = strata.iter().map(|stratum| pools.setups(stratum)).sum();
+
+        if strata.is_empty() {
+            return Err(GenSetupError::CouldNotSatisfyKingdomCards);
+        } else if total == 0 {
+            return Err(GenSetupError::CouldNotSatisfyBaneCard);
+        }
+
+        let mut pick = rng.gen_range(0..total);
+        let stratum = strata
+            .iter()
+            .find(|stratum| {
+                let setups = pools.setups(stratum);
+                if pick < setups {
+                    return true;
+                }
+                pick -= setups;
+                false
+            })
+            .expect("the picks add up to the total");
+
+        let rest = pools.picked() - stratum.cheap - stratum.young_witch as usize;
+        let (mut kingdom_cards, cheap_left) =
+            pools
+                .picks
+                .draw(stratum.cheap, rest, &pools.tally(stratum.young_witch), rng);
+        if stratum.young_witch {
+            kingdom_cards.push(KC::YoungWitch);
         }
+        kingdom_cards.shuffle(rng);
+        kingdom_cards.extend(pools.forced.iter().cloned());
+
+        // The cheap cards not picked, in random order: the bane, then the
+        // second Zebra
+        let mut cheap_left = cheap_left.into_iter();
+
+        let bane_card = if kingdom_cards.contains(&KC::YoungWitch) {
+            cheap_left.next()
+        } else {
+            None
+        };
+
+        let (without_zebra, with_zebra) = pools.custom_banes();
+        let zebra =
+            rng.gen_range(0..without_zebra + with_zebra * stratum.zebras as u128) >= without_zebra;
+        let mut banes: Vec<BaneCard> = if zebra {
+            // Uniform over the arrangements with a Zebra, by rejecting the
+            // ones without
+            loop {
+                let banes: Vec<BaneCard> = BaneCard::iter()
+                    .collect::<Vec<_>>()
+                    .choose_multiple(rng, pools.bane_count)
+                    .cloned()
+                    .collect();
+                if banes.contains(&BaneCard::Zebra) {
+                    break banes;
+                }
+            }
+        } else {
+            BaneCard::iter()
+                .filter(|bane| *bane != BaneCard::Zebra)
+                .collect::<Vec<_>>()
+                .choose_multiple(rng, pools.bane_count)
+                .cloned()
+                .collect()
+        };
+        banes.shuffle(rng);
+        let bane_cards = kingdom_cards
+            .choose_multiple(rng, pools.bane_count)
+            .cloned()
+            .zip(banes)
+            .collect();
+
+        let project_count = *pools
+            .project_counts
+            .choose_weighted(rng, |count| pools.project_sets(*count))
+            .map_err(|_| GenSetupError::CouldNotSatisfyProjectsFromExpansions)?;
+
+        Ok(Setup {
+            kingdom_cards,
+            bane_card,
+            project_cards: pools
+                .projects
+                .choose_multiple(rng, project_count)
+                .cloned()
+                .collect(),
+            bane_cards,
+            second_zebra: if zebra { cheap_left.next() } else { None },
+            parent: None,
+        })
     }
 }
 
-/// Errors we may encounter when generating a setup. These are mostly due to
-/// incoherent configurations.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-pub enum GenSetupError {
-    /// Asked for some number of projects but didn't supply enough expansions to
-    /// choose them.
-    CouldNotSatisfyProjectsFromExpansions,
+pub mod power {
+    //! How strong each kingdom card is on its own: the share of games "the
+    //! card + Big Money" wins against plain Big Money. The table in
+    //! `src/power.json` is made by `rank` (the CLI's `rank` command) and only
+    //! covers cards the engine can play; other cards have no power.
 
-    /// Filtered in such a way as to not allow us to pick enough kingdom cards.
-    CouldNotSatisfyKingdomCards,
+    use super::bots::{self, Bot};
+    use super::*;
+    use std::sync::OnceLock;
 
-    /// Filtered in such a way as to not allow us to pick a bane card.
-    CouldNotSatisfyBaneCard,
+    const TABLE: &str = include_str!("power.json");
 
-    /// Filtered in such a way as to not allow us to pick a second zebra card.
-    CouldNotSatisfySecondZebra,
+    /// One card's result, in the table
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Ranking {
+        pub card: KC,
+        pub win_rate: f64,
+    }
 
-    /// Asked to ban and include one or more cards.
-    IntersectingCardBansAndIncludes(Vec<KC>),
+    /// The stored table, strongest card first. It's parsed the first time
+    /// it's asked for.
+    pub fn table() -> &'static [Ranking] {
+        static RANKINGS: OnceLock<Vec<Ranking>> = OnceLock::new();
+        RANKINGS.get_or_init(|| {
+            serde_json::from_str(TABLE).expect("src/power.json is a list of rankings")
+        })
+    }
 
-    /// Asked to include more cards than the kingdom can handle. Right now this
-    /// just errors if given more than 10. Technically we should be able to
-    /// handle an 11th if `KC::YoungWitch` is one of them.
-    TooManyCardsIncluded,
-}
+    /// Rank every card the engine can play, playing `games` games of the card
+    /// + Big Money against Big Money on a kingdom of just that card
+    pub fn rank(games: usize, seed: u64) -> Vec<Ranking> {
+        let mut rankings: Vec<Ranking> = KC::iter()
+            .filter(|card| card.expansions().contains(&Expansion::Base2))
+            .map(|card| {
+                let setup = Setup::new(vec![card.clone()], None, vec![], HashMap::new(), None);
+                let simulation = bots::simulate(
+                    &setup,
+                    &[Bot::SingleCard(card.clone()), Bot::BigMoney],
+                    games,
+                    seed,
+                )
+                .expect("Base2 cards are playable");
+
+                Ranking {
+                    card,
+                    win_rate: simulation.standings[0].wins / games.max(1) as f64,
+                }
+            })
+            .collect();
 
-fn expansion_set<T: Expansions>(v: &T) -> HashSet<Expansion> {
-    v.expansions().into_iter().collect()
-}
+        rankings.sort_by(|a, b| b.win_rate.total_cmp(&a.win_rate));
+        rankings
+    }
 
-#[wasm_bindgen]
-pub fn gen_setup_js(config: &JsValue) -> Result<JsValue, JsValue> {
-    let config = config.into_serde().unwrap();
-    let setup = gen_setup(config);
-    match setup {
-        Ok(setup) => Ok(JsValue::from_serde(&setup).unwrap()),
-        Err(err) => Err(JsValue::from_serde(&err).unwrap()),
+    /// The cards in the top tenth of the table (rounding up)
+    pub fn top_decile(table: &[Ranking]) -> HashSet<KC> {
+        table
+            .iter()
+            .take(table.len().div_ceil(10))
+            .map(|ranking| ranking.card.clone())
+            .collect()
     }
-}
 
-#[wasm_bindgen]
-pub fn kingdom_cards_js() -> JsValue {
-    JsValue::from_serde(&KC::iter().collect::<Vec<_>>()).unwrap()
-}
+    /// The summed win rates of the setup's kingdom cards that are in the
+    /// table
+    pub fn power(table: &[Ranking], setup: &Setup) -> f64 {
+        table
+            .iter()
+            .filter(|ranking| setup.kingdom_cards.contains(&ranking.card))
+            .map(|ranking| ranking.win_rate)
+            .sum()
+    }
+
+    /// Does the setup meet `max_power` and `separate_top_cards`, going by
+    /// `table`?
+    pub fn satisfies(config: &SetupConfig, table: &[Ranking], setup: &Setup) -> bool {
+        if config.max_power.is_none() && !config.separate_top_cards {
+            return true;
+        }
 
-#[wasm_bindgen]
-pub fn expansions_js() -> JsValue {
-    JsValue::from_serde(&Expansion::iter().collect::<Vec<_>>()).unwrap()
+        let top = top_decile(table);
+        let top_cards = setup
+            .kingdom_cards
+            .iter()
+            .filter(|card| top.contains(card))
+            .count();
+
+        config
+            .max_power
+            .is_none_or(|max| power(table, setup) <= max)
+            && (!config.separate_top_cards || top_cards <= 1)
+    }
 }
 
-#[wasm_bindgen]
-pub fn expansion_cards_js() -> JsValue {
-    let mut results: HashMap<Expansion, Vec<KC>> = HashMap::new();
+pub mod catalog {
+    //! Everything the generator knows about: cards, expansions, projects and
+    //! custom bane cards, for listing and looking up.
 
-    for card in KC::iter() {
-        for expansion in card.expansions() {
-            match results.entry(expansion) {
-                Entry::Occupied(mut entry) => {
-                    entry.get_mut().push(card.clone());
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![card.clone()]);
-                }
-            }
-        }
+    use super::*;
+
+    /// What to list
+    #[derive(Debug, PartialEq, Clone, Copy, EnumString, EnumIter, Serialize, Deserialize)]
+    #[strum(serialize_all = "lowercase")]
+    #[serde(rename_all = "lowercase")]
+    pub enum Listing {
+        Cards,
+        Expansions,
+        Projects,
+        Banes,
     }
 
-    JsValue::from_serde(&results).unwrap()
-}
+    /// What to narrow a listing to. Anything without the attribute (e.g.
+    /// the cost of an expansion) is left out once it's filtered on.
+    #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+    pub struct Filter {
+        /// In any of these expansions
+        pub expansions: Option<HashSet<Expansion>>,
 
-#[wasm_bindgen]
-pub fn project_counts_js() -> JsValue {
-    JsValue::from_serde(&ProjectCount::iter().collect::<Vec<_>>()).unwrap()
-}
+        /// Of any of these types
+        pub types: Option<HashSet<CardType>>,
 
-#[wasm_bindgen]
-pub fn bane_counts_js() -> JsValue {
-    JsValue::from_serde(&BaneCount::iter().collect::<Vec<_>>()).unwrap()
-}
+        /// Costing any of these
+        pub costs: Option<HashSet<u8>>,
+    }
 
-/// Generate a valid setup from options (`SetupConfig`)
-pub fn gen_setup(config: SetupConfig) -> Result<Setup, GenSetupError> {
-    let mut rng = rand::thread_rng();
+    /// One line of a listing
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Entry {
+        pub name: String,
+        pub cost: Option<u8>,
+        pub types: Vec<CardType>,
+        pub expansions: Vec<Expansion>,
+    }
 
-    for bans in &config.ban_cards {
-        for includes in &config.include_cards {
-            if !bans.is_disjoint(includes) {
-                let cards_in_common = bans.intersection(&includes);
-                return Err(GenSetupError::IntersectingCardBansAndIncludes(
-                    cards_in_common.cloned().collect(),
-                ));
-            }
+    impl Filter {
+        fn keeps(&self, entry: &Entry) -> bool {
+            let expansions = self.expansions.as_ref().is_none_or(|expansions| {
+                entry
+                    .expansions
+                    .iter()
+                    .any(|expansion| expansions.contains(expansion))
+            });
+            let types = self
+                .types
+                .as_ref()
+                .is_none_or(|types| entry.types.iter().any(|t| types.contains(t)));
+            let costs = self
+                .costs
+                .as_ref()
+                .is_none_or(|costs| entry.cost.is_some_and(|cost| costs.contains(&cost)));
+
+            expansions && types && costs
         }
     }
 
-    let desired_expansions = config
-        .clone()
-        .include_expansions
-        .unwrap_or(Expansion::iter().collect());
+    /// Everything of a kind that passes `filter`, by name
+    ///
+    /// ```
+    /// use dominion::catalog::{list, Filter, Listing};
+    /// let filter = Filter { costs: Some([2].into_iter().collect()), ..Filter::default() };
+    /// assert!(list(Listing::Cards, &filter).iter().any(|entry| entry.name == "Cellar"));
+    /// ```
+    pub fn list(listing: Listing, filter: &Filter) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = match listing {
+            Listing::Cards => KC::iter()
+                .map(|card| Entry {
+                    name: format!("{:?}", card),
+                    cost: Some(card.base_cost()),
+                    types: card.card_types(),
+                    expansions: card.expansions(),
+                })
+                .collect(),
+            Listing::Expansions => Expansion::iter()
+                .map(|expansion| Entry {
+                    name: format!("{:?}", expansion),
+                    cost: None,
+                    types: vec![],
+                    expansions: vec![expansion],
+                })
+                .collect(),
+            Listing::Projects => Project::iter()
+                .map(|project| Entry {
+                    name: format!("{:?}", project),
+                    cost: Some(project.base_cost()),
+                    types: vec![],
+                    expansions: project.expansions(),
+                })
+                .collect(),
+            Listing::Banes => BaneCard::iter()
+                .map(|bane| Entry {
+                    name: format!("{:?}", bane),
+                    cost: None,
+                    types: vec![],
+                    expansions: vec![],
+                })
+                .collect(),
+        };
 
-    let possible_projects: Vec<Project> = Project::iter()
-        .filter(|p| !expansion_set(p).is_disjoint(&desired_expansions))
-        .collect();
+        entries.retain(|entry| filter.keeps(entry));
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
 
-    let banned_cards = config.ban_cards.clone().unwrap_or(HashSet::new());
+    /// All there is to know about one card
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    pub struct Details {
+        pub card: KC,
+        pub cost: u8,
+        pub types: Vec<CardType>,
+        pub expansions: Vec<Expansion>,
+        pub roles: Vec<Role>,
+
+        /// How often Big Money with it beats Big Money, when the engine can
+        /// play it (see `power`)
+        pub win_rate: Option<f64>,
+
+        /// The known pairs it's in
+        pub synergies: Vec<synergy::Synergy>,
+    }
 
-    let forced_kingdom_cards = config.include_cards.clone().unwrap_or(HashSet::new());
+    pub fn details(card: KC) -> Details {
+        Details {
+            cost: card.base_cost(),
+            types: card.card_types(),
+            expansions: card.expansions(),
+            roles: card.roles(),
+            win_rate: power::table()
+                .iter()
+                .find(|ranking| ranking.card == card)
+                .map(|ranking| ranking.win_rate),
+            synergies: synergy::SYNERGIES
+                .iter()
+                .filter(|synergy| {
+                    synergy.card == card || synergy.partner == synergy::Partner::Card(card.clone())
+                })
+                .cloned()
+                .collect(),
+            card,
+        }
+    }
+}
 
-    if forced_kingdom_cards.len() > 10 {
-        return Err(GenSetupError::TooManyCardsIncluded);
+pub mod session {
+    //! A game with people and bots in the seats, played one answer at a time.
+    //! Bots answer as soon as they're asked; a person's decision waits in
+    //! `pending` until they `choose`. Underneath, each answer replays the
+    //! current turn from its start with every answer given so far, so a
+    //! session is plain data that can be kept anywhere.
+
+    use super::bots::{Bot, Strategy};
+    use super::engine::{Card, Decider, Decision, EngineError, Event, Game, Phase, Pile, Scripted};
+    use super::*;
+
+    /// Who plays a seat
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub enum Seat {
+        Human,
+        Bot(Bot),
     }
 
-    let mut possible_kingdom_cards: Vec<KC> = KC::iter()
-        .filter(|kc| !expansion_set(kc).is_disjoint(&desired_expansions))
-        .filter(|kc| !banned_cards.contains(kc))
-        .filter(|kc| !forced_kingdom_cards.contains(kc))
-        .collect();
+    /// Another player, without their hidden cards
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct PlayerView {
+        pub name: String,
+        pub deck: usize,
+        pub hand: usize,
+        pub discard: usize,
 
-    let project_count = match &config.project_count {
-        Some(desired) => {
-            if possible_projects.len() < desired.count() {
-                return Err(GenSetupError::CouldNotSatisfyProjectsFromExpansions);
-            } else {
-                desired.count()
-            }
-        }
-        None => rng.gen_range(0..3),
-    };
+        /// The face up card on top of the discard pile
+        pub discard_top: Option<Card>,
 
-    let project_cards = possible_projects
-        .choose_multiple(&mut rng, project_count)
-        .cloned()
-        .collect();
+        pub in_play: Vec<Card>,
+        pub score: i32,
+    }
 
-    possible_kingdom_cards.shuffle(&mut rng);
+    /// One player's view of the game
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct View {
+        pub seat: usize,
+        pub hand: Vec<Card>,
+        pub players: Vec<PlayerView>,
+        pub supply: Vec<Pile>,
+        pub trash: Vec<Card>,
+        pub turn: usize,
+        pub current: usize,
+        pub phase: Phase,
+        pub actions: usize,
+        pub buys: usize,
+        pub coins: usize,
+
+        /// Your decision to make, if it's yours
+        pub decision: Option<Decision>,
+
+        /// Whose decision the game is waiting on
+        pub waiting_on: Option<usize>,
+
+        pub over: bool,
+    }
 
-    let random_needed = 10 - &forced_kingdom_cards.len();
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct Session {
+        pub seats: Vec<Seat>,
 
-    let mut kingdom_cards: Vec<KC> = possible_kingdom_cards
-        .iter()
-        .take(random_needed)
-        .cloned()
-        .collect();
+        /// The game as it was when the current turn started
+        turn_start: Game,
 
-    kingdom_cards.append(&mut forced_kingdom_cards.iter().cloned().collect());
+        /// Every answer given this turn, bots' too
+        answers: Vec<Vec<Card>>,
 
-    if kingdom_cards.len() < 10 {
-        return Err(GenSetupError::CouldNotSatisfyKingdomCards);
-    }
+        /// The game with `answers` played
+        game: Game,
 
-    let mut bane_card = None;
+        /// The decision the game waits on
+        pending: Option<Decision>,
+    }
 
-    let mut remaining_possible_kingdom_cards = possible_kingdom_cards
-        .iter()
-        .skip(random_needed)
-        .filter(|c| c.base_cost() == 2 || c.base_cost() == 3);
+    impl Session {
+        /// Set up a game of `setup`, one player per seat, and play until a
+        /// person has to decide something
+        pub fn new(setup: &Setup, seats: Vec<Seat>, seed: u64) -> Result<Self, EngineError> {
+            let game = Game::new(setup, seats.len(), seed)?;
+            let mut session = Session {
+                seats,
+                turn_start: game.clone(),
+                answers: vec![],
+                game,
+                pending: None,
+            };
+            session.advance()?;
+            Ok(session)
+        }
 
-    if kingdom_cards.contains(&KC::YoungWitch) {
-        bane_card = remaining_possible_kingdom_cards.next().cloned();
+        pub fn game(&self) -> &Game {
+            &self.game
+        }
 
-        // We struck out but making this work is possible. Let's try again.
-        if bane_card.is_none()
-            && kingdom_cards
-                .iter()
-                .any(|kc| kc.base_cost() == 2 || kc.base_cost() == 3)
-        {
-            return gen_setup(config.clone());
+        pub fn pending(&self) -> Option<&Decision> {
+            self.pending.as_ref()
         }
-        // Not possible.
-        if bane_card.is_none() {
-            return Err(GenSetupError::CouldNotSatisfyBaneCard);
+
+        /// The pending decision if it's `seat`'s, as nobody may see what
+        /// another player is deciding between
+        pub fn decision(&self, seat: usize) -> Option<&Decision> {
+            self.pending().filter(|decision| decision.player == seat)
         }
-    }
 
-    let bane_count = config.bane_count.map(|bc| bc.count()).unwrap_or(0);
+        /// Answer the pending decision as `seat`. A bad answer leaves the
+        /// session as it was.
+        pub fn choose(&mut self, seat: usize, choice: Vec<Card>) -> Result<(), EngineError> {
+            match &self.pending {
+                Some(decision) if decision.player == seat => {}
+                Some(_) => return Err(EngineError::NotYourDecision(seat)),
+                None => return Err(EngineError::GameOver),
+            }
 
-    let all_banes = BaneCard::iter().collect::<Vec<_>>();
+            self.answers.push(choice);
+            let advanced = self.advance();
+            if advanced.is_err() {
+                self.answers.pop();
+            }
+            advanced
+        }
 
-    let bane_cards: HashMap<KC, BaneCard> = kingdom_cards
-        .choose_multiple(&mut rng, bane_count)
-        .cloned()
-        .zip(all_banes.choose_multiple(&mut rng, bane_count).cloned())
-        .collect();
+        /// Play turns until a person has to decide something, the game ends
+        /// or it runs past `bots::TURN_LIMIT`
+        fn advance(&mut self) -> Result<(), EngineError> {
+            while !self.turn_start.is_over() && self.turn_start.turn < bots::TURN_LIMIT {
+                let mut game = self.turn_start.clone();
+                let mut answers = Scripted::new(self.answers.clone());
+                let mut bot_answers = vec![];
+                let mut pending = None;
+                let seats = &self.seats;
+
+                let mut decider = |game: &Game, decision: &Decision| {
+                    if let Some(answer) = answers.decide(game, decision) {
+                        return Some(answer);
+                    }
+                    match &seats[decision.player] {
+                        Seat::Bot(bot) => {
+                            let answer = bot.clone().choose(game, decision);
+                            bot_answers.push(answer.clone());
+                            Some(answer)
+                        }
+                        Seat::Human => {
+                            pending = Some(decision.clone());
+                            None
+                        }
+                    }
+                };
+
+                match game.take_turn(&mut decider) {
+                    Ok(()) => {
+                        self.turn_start = game.clone();
+                        self.answers.clear();
+                        self.game = game;
+                        self.pending = None;
+                    }
+                    Err(EngineError::Suspended) => {
+                        self.answers.extend(bot_answers);
+                        self.game = game;
+                        self.pending = pending;
+                        return Ok(());
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            Ok(())
+        }
 
-    let mut second_zebra = None;
+        /// The game as `seat` can see it
+        pub fn view(&self, seat: usize) -> View {
+            let game = &self.game;
+
+            View {
+                seat,
+                hand: game.players[seat].hand.clone(),
+                players: game
+                    .players
+                    .iter()
+                    .enumerate()
+                    .map(|(i, player)| PlayerView {
+                        name: self.name(i),
+                        deck: player.deck.len(),
+                        hand: player.hand.len(),
+                        discard: player.discard.len(),
+                        discard_top: player.discard.last().cloned(),
+                        in_play: player.in_play.clone(),
+                        score: game.score(i),
+                    })
+                    .collect(),
+                supply: game.supply.clone(),
+                trash: game.trash.clone(),
+                turn: game.turn,
+                current: game.current,
+                phase: game.phase,
+                actions: game.actions,
+                buys: game.buys,
+                coins: game.coins,
+                decision: self.decision(seat).cloned(),
+                waiting_on: self.pending.as_ref().map(|decision| decision.player),
+                over: game.is_over(),
+            }
+        }
 
-    if bane_cards
-        .values()
-        .cloned()
-        .collect::<Vec<_>>()
-        .contains(&&BaneCard::Zebra)
-    {
-        second_zebra = remaining_possible_kingdom_cards.next().cloned();
+        /// "Player 2", or the bot's name
+        pub fn name(&self, seat: usize) -> String {
+            match &self.seats[seat] {
+                Seat::Human => format!("Player {}", seat + 1),
+                Seat::Bot(bot) => bot.to_string(),
+            }
+        }
 
-        if second_zebra.is_none() {
-            return Err(GenSetupError::CouldNotSatisfySecondZebra);
+        /// The events so far that `seat` may see, from the `since`th on
+        pub fn events(&self, seat: usize, since: usize) -> Vec<Event> {
+            visible(seat, self.game.events.get(since..).unwrap_or_default())
         }
     }
 
-    Ok(Setup {
-        project_cards,
-        kingdom_cards,
-        bane_card,
-        bane_cards,
-        second_zebra,
-    })
+    /// The events `seat` may see: no shuffle seeds (they give away deck
+    /// order) and only their own decisions
+    pub fn visible(seat: usize, events: &[Event]) -> Vec<Event> {
+        events
+            .iter()
+            .filter(|event| match event {
+                Event::Shuffle { .. } => false,
+                Event::Decision { decision, .. } => decision.player == seat,
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
 }
 
-pub mod pretty {
-    use super::hist::Hist;
+pub mod server {
+    //! A room hosting one game for players over a network. The room only
+    //! speaks `ClientMessage`s and `ServerMessage`s (JSON, tagged by `type`)
+    //! and leaves the sockets to the caller: `handle` and `disconnect` return
+    //! the messages to send, addressed by connection.
+    //!
+    //! Players `Join` the lobby and get the next free seat and a token to
+    //! `Rejoin` with after a dropped connection. Seat 0 hosts and `Start`s the
+    //! game. Each player only ever sees their own hand, deck order stays
+    //! secret (no shuffle seeds), and other players' decisions are left out.
+
+    use super::engine::{Card, Event, Game};
+    use super::session::{Session, View};
     use super::*;
-    use chrono::prelude::*;
-    use std::fmt::Debug;
 
-    pub fn code(name: String, setup: &Setup) -> String {
-        let now_local = Local::now();
+    /// Messages from a player
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    pub enum ClientMessage {
+        /// Take the next free seat
+        Join { name: String },
 
-        format!(
-            "  , Played {{ name = Just \"{} at {}\"
-           , at = Just $ Date {{year={}, month={}, day={}}}
-           , setup = {}
-           , players = Just []
-           , rating = Nothing
-           }}
-",
-            name,
-            now_local,
-            now_local.year(),
-            now_local.month(),
-            now_local.day(),
-            format_setup(setup)
-        )
+        /// Take back a seat after a dropped connection
+        Rejoin { token: String },
+
+        /// Start the game (the host, seat 0, only)
+        Start,
+
+        /// Answer the decision in your `View`
+        Choose { choice: Vec<Card> },
     }
 
-    fn format_card(card: &KC, setup: &Setup) -> String {
-        match &setup.bane_cards.get(&card) {
-            Some(BaneCard::Zebra) => format!(
-                " - {:?} (Zebra with {})",
-                card,
-                setup
-                    .second_zebra
-                    .clone()
-                    .map(spaces)
-                    .unwrap_or(String::new())
-            ),
-            Some(b) => format!(" - {} ({})", spaces(card), spaces(b)),
-            None => match &setup.bane_card {
-                Some(c) => format!(
-                    " - {} {}",
-                    spaces(card),
-                    if c == card { "(Bane)" } else { "" }
-                ),
-                None => format!(" - {}", spaces(card)),
-            },
-        }
+    /// Messages to a player
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    pub enum ServerMessage {
+        /// You have a seat, `token` gets it back after a dropped connection
+        Welcome {
+            seat: usize,
+            token: String,
+        },
+
+        /// Who's sitting where
+        Lobby {
+            setup: Setup,
+            seats: Vec<SeatView>,
+            started: bool,
+        },
+
+        /// The game as you can see it
+        State {
+            view: Box<View>,
+        },
+
+        /// What happened since the last `Events`, as far as you can see
+        Events {
+            events: Vec<Event>,
+        },
+
+        Error {
+            message: String,
+        },
     }
 
-    fn spaces<T: Debug>(card: T) -> String {
-        format!("{:?}", card)
-            .chars()
-            .enumerate()
-            .flat_map(|(i, c)| {
-                if i == 0 || !c.is_uppercase() {
-                    vec![c]
-                } else {
-                    vec![' ', c]
-                }
-            })
-            .collect()
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    pub struct SeatView {
+        pub name: String,
+        pub connected: bool,
     }
 
-    fn kingdom_card_by_expansion_list(setup: &Setup) -> String {
-        let mut cards_by_expansion: HashMap<String, String> = HashMap::new();
+    /// Where a message goes
+    pub type Outbox = Vec<(usize, ServerMessage)>;
+
+    struct Seat {
+        name: String,
+        token: String,
+        connection: Option<usize>,
+    }
+
+    pub struct Room {
+        setup: Setup,
+        max_players: usize,
+        seed: u64,
+        seats: Vec<Seat>,
+        session: Option<Session>,
 
-        let mut cards = setup.cards();
+        /// How many of the game's events have been sent out
+        sent: usize,
+    }
 
-        cards.sort();
+    impl Room {
+        pub fn new(setup: Setup, max_players: usize, seed: u64) -> Self {
+            Room {
+                setup,
+                max_players: max_players.clamp(1, 4),
+                seed,
+                seats: vec![],
+                session: None,
+                sent: 0,
+            }
+        }
 
-        for card in cards {
-            let exp = card
-                .expansions()
+        /// The game, once started
+        pub fn game(&self) -> Option<&Game> {
+            self.session.as_ref().map(Session::game)
+        }
+
+        fn seat_of(&self, connection: usize) -> Option<usize> {
+            self.seats
                 .iter()
-                .map(|e| format!("{:?}", e))
-                .collect::<Vec<_>>()
-                .join("/");
+                .position(|seat| seat.connection == Some(connection))
+        }
 
-            cards_by_expansion
-                .entry(exp)
-                .and_modify(|s| {
-                    s.push('\n');
-                    s.push_str(&format_card(&card, &setup))
-                })
-                .or_insert(format_card(&card, &setup));
+        fn error(connection: usize, message: &str) -> Outbox {
+            vec![(
+                connection,
+                ServerMessage::Error {
+                    message: message.to_string(),
+                },
+            )]
         }
 
-        let mut kingdom_cards = String::new();
+        /// Act on a message from `connection`
+        pub fn handle(&mut self, connection: usize, message: ClientMessage) -> Outbox {
+            match message {
+                ClientMessage::Join { name } => self.join(connection, name),
+                ClientMessage::Rejoin { token } => self.rejoin(connection, &token),
+                ClientMessage::Start => self.start(connection),
+                ClientMessage::Choose { choice } => self.choose(connection, choice),
+            }
+        }
 
-        for (exp, cs) in cards_by_expansion.into_iter() {
-            kingdom_cards.push_str(&exp);
-            kingdom_cards.push('\n');
-            kingdom_cards.push_str(&cs);
-            kingdom_cards.push('\n');
+        /// `connection` went away; its seat waits for a `Rejoin`
+        pub fn disconnect(&mut self, connection: usize) -> Outbox {
+            match self.seat_of(connection) {
+                Some(seat) => {
+                    self.seats[seat].connection = None;
+                    self.broadcast_lobby()
+                }
+                None => vec![],
+            }
         }
 
-        format!(
-            "\
-┌───────────────┐
-│ Kingdom Cards │
-└───────────────┘
+        fn join(&mut self, connection: usize, name: String) -> Outbox {
+            if self.seat_of(connection).is_some() {
+                return Room::error(connection, "You already have a seat!");
+            }
+            if self.session.is_some() {
+                return Room::error(connection, "The game has already started!");
+            }
+            if self.seats.len() >= self.max_players {
+                return Room::error(connection, "The table is full!");
+            }
 
-{}",
-            kingdom_cards
-        )
-    }
+            let token = format!(
+                "{:016x}{:016x}",
+                rand::random::<u64>(),
+                rand::random::<u64>()
+            );
+            self.seats.push(Seat {
+                name,
+                token: token.clone(),
+                connection: Some(connection),
+            });
+
+            let mut outbox = vec![(
+                connection,
+                ServerMessage::Welcome {
+                    seat: self.seats.len() - 1,
+                    token,
+                },
+            )];
+            outbox.extend(self.broadcast_lobby());
+            outbox
+        }
 
-    fn project_card_by_expansion_list(project_cards: &Vec<Project>) -> String {
-        if project_cards.is_empty() {
-            return "".to_string();
+        fn rejoin(&mut self, connection: usize, token: &str) -> Outbox {
+            let seat = match self.seats.iter().position(|seat| seat.token == token) {
+                Some(seat) => seat,
+                None => return Room::error(connection, "That token doesn't hold a seat!"),
+            };
+            if let Some(previous) = self.seat_of(connection) {
+                self.seats[previous].connection = None;
+            }
+            self.seats[seat].connection = Some(connection);
+
+            let mut outbox = vec![(
+                connection,
+                ServerMessage::Welcome {
+                    seat,
+                    token: token.to_string(),
+                },
+            )];
+            outbox.extend(self.broadcast_lobby());
+            if let Some(session) = &self.session {
+                // Catch up on everything missed
+                let mut events = session.events(seat, 0);
+                events.truncate(self.sent);
+                outbox.push((connection, ServerMessage::Events { events }));
+                outbox.push((connection, self.state(seat)));
+            }
+            outbox
         }
 
-        let mut cards_by_expansion: HashMap<String, String> = HashMap::new();
+        fn start(&mut self, connection: usize) -> Outbox {
+            if self.seat_of(connection) != Some(0) {
+                return Room::error(connection, "Only the host (seat 0) can start the game!");
+            }
+            if self.session.is_some() {
+                return Room::error(connection, "The game has already started!");
+            }
 
-        let mut cards = project_cards.clone();
+            let players = vec![session::Seat::Human; self.seats.len()];
+            match Session::new(&self.setup, players, self.seed) {
+                Ok(session) => {
+                    self.session = Some(session);
 
-        cards.sort();
+                    let mut outbox = self.broadcast_lobby();
+                    outbox.extend(self.broadcast_game());
+                    outbox
+                }
+                Err(err) => Room::error(connection, &pretty::engine_error(err)),
+            }
+        }
 
-        for card in cards {
-            let exp = card
-                .expansions()
+        fn choose(&mut self, connection: usize, choice: Vec<Card>) -> Outbox {
+            let seat = self.seat_of(connection);
+            let session = match (&mut self.session, seat) {
+                (Some(session), Some(_)) => session,
+                (None, _) => return Room::error(connection, "The game hasn't started!"),
+                (_, None) => return Room::error(connection, "You don't have a seat!"),
+            };
+
+            match session.choose(seat.unwrap(), choice) {
+                Ok(()) => self.broadcast_game(),
+                Err(err) => Room::error(connection, &pretty::engine_error(err)),
+            }
+        }
+
+        fn broadcast_lobby(&self) -> Outbox {
+            let seats: Vec<SeatView> = self
+                .seats
                 .iter()
-                .map(|e| format!("{:?}", e))
-                .collect::<Vec<_>>()
-                .join("/");
+                .map(|seat| SeatView {
+                    name: seat.name.clone(),
+                    connected: seat.connection.is_some(),
+                })
+                .collect();
 
-            cards_by_expansion
-                .entry(exp)
-                .and_modify(|s| {
-                    s.push('\n');
-                    s.push_str(&format!(" - {:?}", card))
+            self.seats
+                .iter()
+                .filter_map(|seat| seat.connection)
+                .map(|connection| {
+                    (
+                        connection,
+                        ServerMessage::Lobby {
+                            setup: self.setup.clone(),
+                            seats: seats.clone(),
+                            started: self.session.is_some(),
+                        },
+                    )
                 })
-                .or_insert(format!(" - {:?}", card));
+                .collect()
         }
 
-        let mut project_cards = String::new();
+        /// New events and the state, to every connected player
+        fn broadcast_game(&mut self) -> Outbox {
+            let session = match &self.session {
+                Some(session) => session,
+                None => return vec![],
+            };
+
+            let mut outbox = vec![];
+            for (seat, connection) in self.seats.iter().enumerate() {
+                if let Some(connection) = connection.connection {
+                    let events = session.events(seat, self.sent);
+                    outbox.push((connection, ServerMessage::Events { events }));
+                    outbox.push((connection, self.state(seat)));
+                }
+            }
 
-        for (exp, cs) in cards_by_expansion.into_iter() {
-            project_cards.push_str(&exp);
-            project_cards.push('\n');
-            project_cards.push_str(&cs);
-            project_cards.push('\n');
+            self.sent = session.game().events.len();
+            outbox
         }
 
-        format!(
-            "\
-┌───────────────┐
-│ Project Cards │
-└───────────────┘
+        fn state(&self, seat: usize) -> ServerMessage {
+            let session = self.session.as_ref().expect("the game has started");
+            let mut view = session.view(seat);
+            for (player, seat) in view.players.iter_mut().zip(&self.seats) {
+                player.name = seat.name.clone();
+            }
+            ServerMessage::State {
+                view: Box::new(view),
+            }
+        }
+    }
 
-{}",
-            project_cards
-        )
+    /// A room with its clients in the same process, for trying the protocol
+    /// without sockets
+    pub struct Harness {
+        pub room: Room,
+        inboxes: HashMap<usize, Vec<ServerMessage>>,
     }
 
-    pub fn pretty(setup: &Setup) -> String {
-        format!(
-            "{}\n{}",
-            kingdom_card_by_expansion_list(&setup),
-            project_card_by_expansion_list(&setup.project_cards)
-        )
+    impl Harness {
+        pub fn new(room: Room) -> Self {
+            Harness {
+                room,
+                inboxes: HashMap::new(),
+            }
+        }
+
+        fn deliver(&mut self, outbox: Outbox) {
+            for (connection, message) in outbox {
+                self.inboxes.entry(connection).or_default().push(message);
+            }
+        }
+
+        pub fn send(&mut self, connection: usize, message: ClientMessage) {
+            let outbox = self.room.handle(connection, message);
+            self.deliver(outbox);
+        }
+
+        pub fn disconnect(&mut self, connection: usize) {
+            let outbox = self.room.disconnect(connection);
+            self.deliver(outbox);
+        }
+
+        /// Everything `connection` was sent since the last `take`
+        pub fn take(&mut self, connection: usize) -> Vec<ServerMessage> {
+            self.inboxes.remove(&connection).unwrap_or_default()
+        }
+
+        /// The latest view `connection` was sent, without taking anything
+        pub fn view(&self, connection: usize) -> Option<&View> {
+            self.inboxes
+                .get(&connection)?
+                .iter()
+                .rev()
+                .find_map(|message| match message {
+                    ServerMessage::State { view } => Some(view.as_ref()),
+                    _ => None,
+                })
+        }
     }
+}
+
+pub mod wasm {
+    //! Everything the site (or any other JavaScript) can call. Arguments and
+    //! results are typed by the definitions in `types.d.ts`, which are
+    //! generated by `typescript()`. Nothing here panics on bad input: every
+    //! export throws an `ApiError` instead.
+    //!
+    //! Adding an export or an optional field is backwards compatible; renaming
+    //! or removing either, or changing what a value means, bumps
+    //! `API_VERSION`.
+
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use std::fmt::Debug;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+
+    /// Version 1 was the untyped API that passed `JsValue`s around
+    pub const API_VERSION: u32 = 2;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TYPES: &'static str = include_str!("types.d.ts");
 
     #[wasm_bindgen]
-    pub fn hists_js(json: &JsValue) -> String {
-        let setup = json.into_serde().unwrap();
-        hists(&setup)
-    }
+    extern "C" {
+        #[wasm_bindgen(typescript_type = "SetupConfig")]
+        pub type JsSetupConfig;
 
-    pub fn hists(setup: &Setup) -> String {
-        let cost_zeros = KC::iter()
-            .map(|c| Hist::n(c.base_cost(), 0))
-            .fold(Hist::empty(), |s, c| s + c);
-        let costs = setup
-            .cards()
-            .iter()
-            .fold(cost_zeros, |s, c| s + Hist::one(c.base_cost()));
+        #[wasm_bindgen(typescript_type = "Setup")]
+        pub type JsSetup;
 
-        let types_zeros = CardType::iter()
-            .map(|t| Hist::n(t, 0))
-            .fold(Hist::empty(), |s, c| s + c);
-        let types = setup.cards().iter().fold(types_zeros, |s, c| {
-            s + c
-                .card_types()
-                .iter()
-                .fold(Hist::empty(), |s, t| s + Hist::one(t.clone()))
-        });
+        #[wasm_bindgen(typescript_type = "Slot")]
+        pub type JsSlot;
 
-        let expansions = setup.cards().iter().fold(Hist::empty(), |s, c| {
-            s + c
-                .expansions()
-                .iter()
-                .fold(Hist::empty(), |s, e| s + Hist::one(e.clone()))
-        });
+        #[wasm_bindgen(typescript_type = "EvolveConfig")]
+        pub type JsEvolveConfig;
 
-        format!(
-            "\
-Cards' Costs:
--------------
-{}
+        #[wasm_bindgen(typescript_type = "Played[]")]
+        pub type JsHistory;
 
-Cards' types:
--------------
-{}
+        #[wasm_bindgen(typescript_type = "GenSetupError")]
+        pub type JsGenSetupError;
 
-Expansions' cards:
------------------
-{}
+        #[wasm_bindgen(typescript_type = "Draft")]
+        pub type JsDraft;
 
-",
-            costs.pretty(),
-            types.pretty(),
-            expansions.pretty()
-        )
-    }
+        #[wasm_bindgen(typescript_type = "DraftFormat")]
+        pub type JsDraftFormat;
 
-    fn format_setup(setup: &Setup) -> String {
-        match (&setup.bane_card, setup.project_cards.len()) {
-            (None, 0) => format!("S.standard {:?}", setup.kingdom_cards),
-            (Some(bane), 0) => format!("S.bane {:?} {:?}", bane, setup.kingdom_cards),
-            (None, _) => format!(
-                "S.standardWithProjects {:?} {:?}",
-                setup.project_cards, setup.kingdom_cards
-            ),
-            (Some(bane), _) => format!(
-                "S.baneWithProjects {:?} {:?} {:?}",
-                bane, setup.project_cards, setup.kingdom_cards
-            ),
-        }
-    }
+        #[wasm_bindgen(typescript_type = "Action")]
+        pub type JsAction;
 
-    #[wasm_bindgen]
-    pub fn gen_error_js(json: &JsValue) -> String {
-        let err = json.into_serde().unwrap();
-        gen_error(err)
-    }
+        #[wasm_bindgen(typescript_type = "DraftError")]
+        pub type JsDraftError;
 
-    pub fn gen_error(err: GenSetupError) -> String {
-        match err {
-            GenSetupError::CouldNotSatisfyProjectsFromExpansions => {
-                "The requested project count could not be satisfied! Ensure you're not specifying expansions which preclude projects.".to_string()
-            }
+        #[wasm_bindgen(typescript_type = "PaperSize")]
+        pub type JsPaperSize;
 
-            GenSetupError::CouldNotSatisfyKingdomCards => "Could not pick 10 kingdom cards! Ensure your filters don't over-limit cards.".to_string(),
+        #[wasm_bindgen(typescript_type = "Analysis")]
+        pub type JsAnalysis;
 
-            GenSetupError::CouldNotSatisfyBaneCard => "Could not pick a bane card! Ensure your filters don't over-limit cards.".to_string(),
+        #[wasm_bindgen(typescript_type = "Synergy[]")]
+        pub type JsSynergies;
 
-            GenSetupError::CouldNotSatisfySecondZebra => "Could not pick a second zebra card! Ensure your filters don't over-limit cards.".to_string(),
+        #[wasm_bindgen(typescript_type = "Neighbour[]")]
+        pub type JsNeighbours;
 
-            GenSetupError::IntersectingCardBansAndIncludes(cards) => format!("I can't ban and include cards! The following exist in the ban and include lists: {:?}", cards),
+        #[wasm_bindgen(typescript_type = "KC[]")]
+        pub type JsCards;
 
-            GenSetupError::TooManyCardsIncluded => "Too many cards were asked to be included! I currently can't generate a kingdom with more than 10 cards.".to_string(),
+        #[wasm_bindgen(typescript_type = "Expansion[]")]
+        pub type JsExpansions;
+
+        #[wasm_bindgen(typescript_type = "Partial<Record<Expansion, KC[]>>")]
+        pub type JsExpansionCards;
+
+        #[wasm_bindgen(typescript_type = "ProjectCount[]")]
+        pub type JsProjectCounts;
+
+        #[wasm_bindgen(typescript_type = "BaneCount[]")]
+        pub type JsBaneCounts;
+
+        #[wasm_bindgen(typescript_type = "Seat[]")]
+        pub type JsSeats;
+
+        #[wasm_bindgen(typescript_type = "View")]
+        pub type JsView;
+
+        #[wasm_bindgen(typescript_type = "Decision | null")]
+        pub type JsDecision;
+
+        #[wasm_bindgen(typescript_type = "Card[]")]
+        pub type JsChoice;
+
+        #[wasm_bindgen(typescript_type = "GameEvent[]")]
+        pub type JsEvents;
+
+        #[wasm_bindgen(typescript_type = "number[]")]
+        pub type JsScores;
+
+        #[wasm_bindgen(typescript_type = "Deck[]")]
+        pub type JsDecks;
+
+        #[wasm_bindgen(typescript_type = "Score[]")]
+        pub type JsTallies;
+    }
+
+    /// What every export throws when it can't return
+    #[derive(Debug, PartialEq, Serialize)]
+    #[serde(tag = "kind")]
+    pub enum ApiError {
+        /// An argument didn't have the shape its TypeScript type promises
+        InvalidInput { argument: String, message: String },
+
+        /// The config couldn't produce a setup
+        GenSetup {
+            error: GenSetupError,
+            message: String,
+        },
+
+        /// The draft didn't allow the move
+        Draft {
+            error: draft::DraftError,
+            message: String,
+        },
+
+        /// The game didn't allow the choice, or can't be played
+        Game {
+            error: engine::EngineError,
+            message: String,
+        },
+
+        /// A setup argument breaks the rules of setting up a game
+        InvalidSetup {
+            argument: String,
+            errors: Vec<SetupError>,
+            message: String,
+        },
+
+        /// A deck couldn't be scored
+        Scoring {
+            error: scoring::ScoringError,
+            message: String,
+        },
+
+        /// A result couldn't be handed back to JavaScript. This is a bug.
+        Internal { message: String },
+    }
+
+    impl From<GenSetupError> for ApiError {
+        fn from(error: GenSetupError) -> Self {
+            ApiError::GenSetup {
+                message: pretty::gen_error(error.clone()),
+                error,
+            }
         }
     }
-}
 
-pub mod hist {
-    use std::collections::HashMap;
-    use std::fmt::Debug;
-    use std::hash::Hash;
-    use std::ops::Add;
+    impl From<draft::DraftError> for ApiError {
+        fn from(error: draft::DraftError) -> Self {
+            ApiError::Draft {
+                message: pretty::draft_error(error.clone()),
+                error,
+            }
+        }
+    }
 
-    /// A histogram for counting instances
-    pub struct Hist<T> {
-        hist: HashMap<T, usize>,
+    impl From<engine::EngineError> for ApiError {
+        fn from(error: engine::EngineError) -> Self {
+            ApiError::Game {
+                message: pretty::engine_error(error.clone()),
+                error,
+            }
+        }
     }
 
-    impl<T> Hist<T> {
-        /// No values counted, the empty `Hist`
-        ///
-        /// ```
-        /// let c: dominion::hist::Hist<&str> = dominion::hist::Hist::empty();
-        /// assert_eq!(c.count(&"hi"), 0);
-        /// ```
-        pub fn empty() -> Self {
-            Hist {
-                hist: HashMap::new(),
+    impl From<scoring::ScoringError> for ApiError {
+        fn from(error: scoring::ScoringError) -> Self {
+            ApiError::Scoring {
+                message: pretty::scoring_error(error.clone()),
+                error,
             }
         }
     }
 
-    impl<T: Ord + Debug + Hash> Hist<T> {
-        pub fn pretty(self: &Self) -> String {
-            let mut keys: Vec<_> = self.hist.keys().collect();
-            keys.sort();
-            let displayed_keys = keys.iter().map(|k| format!("{:?}", k));
-            displayed_keys
-                .max_by_key(|k| k.len())
-                .map(|k| k.len())
-                .map(|key_length| {
-                    keys.iter()
-                        .map(|k| {
-                            let n = self.count(k);
-                            let k = format!("{:?}", k);
-                            format!("{:key_length$}: {:■<n$} ({})", k, "", n)
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                })
-                .unwrap_or(String::new())
+    impl From<ApiError> for JsValue {
+        fn from(error: ApiError) -> Self {
+            error
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+                .unwrap_or_else(|e| JsValue::from_str(&e.to_string()))
+        }
+    }
+
+    fn from_js<T: DeserializeOwned>(
+        argument: &str,
+        value: impl Into<JsValue>,
+    ) -> Result<T, ApiError> {
+        serde_wasm_bindgen::from_value(value.into()).map_err(|e| ApiError::InvalidInput {
+            argument: argument.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// A setup argument that follows the rules, like one from a friend or
+    /// an old history file might not
+    fn setup_from_js(argument: &str, value: impl Into<JsValue>) -> Result<Setup, ApiError> {
+        let setup: Setup = from_js(argument, value)?;
+        let errors = setup.validate();
+        if errors.is_empty() {
+            return Ok(setup);
         }
+
+        Err(ApiError::InvalidSetup {
+            argument: argument.to_string(),
+            message: errors
+                .iter()
+                .cloned()
+                .map(pretty::setup_error)
+                .collect::<Vec<_>>()
+                .join(" "),
+            errors,
+        })
     }
 
-    impl<T: Eq + Hash> Hist<T> {
-        /// Count a single instance
-        ///
-        /// ```
-        /// let c = dominion::hist::Hist::one("car".to_string());
-        /// assert_eq!(c.count(&"car".to_string()), 1);
-        /// ```
-        pub fn one(v: T) -> Self {
-            Hist::n(v, 1)
-        }
+    fn to_js<T: Serialize + ?Sized, J: JsCast>(value: &T) -> Result<J, ApiError> {
+        value
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map(JsCast::unchecked_into)
+            .map_err(|e| ApiError::Internal {
+                message: e.to_string(),
+            })
+    }
 
-        /// Count `n` instances
-        ///
-        /// ```
-        /// let c = dominion::hist::Hist::n("car".to_string(), 5);
-        /// assert_eq!(c.count(&"car".to_string()), 5);
-        /// ```
-        pub fn n(v: T, n: usize) -> Self {
-            Hist {
-                hist: HashMap::from([(v, n)]),
+    /// Every kingdom card, keyed by the expansions it's in
+    pub fn expansion_cards() -> HashMap<Expansion, Vec<KC>> {
+        let mut results: HashMap<Expansion, Vec<KC>> = HashMap::new();
+
+        for card in KC::iter() {
+            for expansion in card.expansions() {
+                match results.entry(expansion) {
+                    Entry::Occupied(mut entry) => {
+                        entry.get_mut().push(card.clone());
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(vec![card.clone()]);
+                    }
+                }
             }
         }
 
-        /// Get element count
-        pub fn count(self: &Self, v: &T) -> usize {
-            self.hist.get(&v).unwrap_or(&0).clone()
-        }
+        results
     }
 
-    impl<T: Clone + Eq + Hash> Add<Hist<T>> for Hist<T> {
-        type Output = Hist<T>;
+    #[wasm_bindgen]
+    pub fn api_version() -> u32 {
+        API_VERSION
+    }
 
-        /// Merge counts of elements
-        ///
-        /// ```
-        /// use dominion::hist::Hist;
-        /// let c = Hist::one("car".to_string()) + Hist::one("truck".to_string()) + Hist::one("car".to_string());
-        /// assert_eq!(c.count(&"car".to_string()), 2);
-        /// assert_eq!(c.count(&"truck".to_string()), 1);
-        /// assert_eq!(c.count(&"bus".to_string()), 0);
-        /// ```
-        fn add(self: Hist<T>, other: Hist<T>) -> Hist<T> {
-            let mut hist = self.hist.clone();
+    #[wasm_bindgen]
+    pub fn gen_setup_js(config: JsSetupConfig) -> Result<JsSetup, ApiError> {
+        let config = from_js("config", config)?;
+        to_js(&gen_setup(config)?)
+    }
 
-            for (v, count) in other.hist {
-                hist.entry(v).and_modify(|c| *c += count).or_insert(count);
+    #[wasm_bindgen]
+    pub fn reroll_js(
+        setup: JsSetup,
+        slot: JsSlot,
+        config: JsSetupConfig,
+    ) -> Result<JsSetup, ApiError> {
+        let setup = setup_from_js("setup", setup)?;
+        let slot = from_js("slot", slot)?;
+        let config = from_js("config", config)?;
+        to_js(&reroll(&setup, slot, config)?)
+    }
+
+    #[wasm_bindgen]
+    pub fn evolve_js(
+        parent: JsSetup,
+        evolve_config: JsEvolveConfig,
+        config: JsSetupConfig,
+        history: JsHistory,
+    ) -> Result<JsSetup, ApiError> {
+        let parent = setup_from_js("parent", parent)?;
+        let evolve_config = from_js("evolve_config", evolve_config)?;
+        let config = from_js("config", config)?;
+        let history: Vec<history::Played> = from_js("history", history)?;
+        to_js(&evolve(&parent, &evolve_config, config, &history)?)
+    }
+
+    #[wasm_bindgen]
+    pub fn rate_js(
+        history: JsHistory,
+        name: Option<String>,
+        rating: u8,
+    ) -> Result<JsHistory, ApiError> {
+        let mut history: Vec<history::Played> = from_js("history", history)?;
+        history::rate(&mut history, name.as_deref(), rating).map_err(|error| {
+            ApiError::InvalidInput {
+                argument: match error {
+                    history::RatingError::OutOfRange(_) => "rating",
+                    history::RatingError::NotPlayed(_) => "name",
+                }
+                .to_string(),
+                message: pretty::rating_error(error),
             }
+        })?;
+        to_js(&history)
+    }
 
-            Hist { hist }
-        }
+    #[wasm_bindgen]
+    pub fn setup_kingdom_cards_js(setup: JsSetup) -> Result<JsCards, ApiError> {
+        let setup = setup_from_js("setup", setup)?;
+        to_js(&setup.cards())
     }
-}
 
-pub mod game_name {
-    use super::*;
-    use rand::seq::SliceRandom;
+    #[wasm_bindgen]
+    pub fn kingdom_cards_js() -> Result<JsCards, ApiError> {
+        to_js(&KC::iter().collect::<Vec<_>>())
+    }
 
-    /// E.g. "The Witch's Bane"
-    fn random_the_cards_blank(card: &KC) -> String {
-        format!("The {:?} of the {}", card, random_end())
+    #[wasm_bindgen]
+    pub fn expansions_js() -> Result<JsExpansions, ApiError> {
+        to_js(&Expansion::iter().collect::<Vec<_>>())
     }
 
-    /// E.g. "The Bane of the Witch"
-    fn random_the_blank_of_the_card(card: &KC) -> String {
-        format!("The {} of the {:?}", random_end(), card)
+    #[wasm_bindgen]
+    pub fn expansion_cards_js() -> Result<JsExpansionCards, ApiError> {
+        to_js(&expansion_cards())
     }
 
-    /// E.g. "The Witch and the Village"
-    fn random_the_card_and_the_card(card1: &KC, card2: &KC) -> String {
-        format!("The {:?} and the {:?}", card1, card2)
+    #[wasm_bindgen]
+    pub fn project_counts_js() -> Result<JsProjectCounts, ApiError> {
+        to_js(&ProjectCount::iter().collect::<Vec<_>>())
     }
 
-    /// E.g. "The Witch's Village"
-    fn random_the_cards_card(card1: &KC, card2: &KC) -> String {
-        format!("The {:?}'s {:?}", card1, card2)
+    #[wasm_bindgen]
+    pub fn bane_counts_js() -> Result<JsBaneCounts, ApiError> {
+        to_js(&BaneCount::iter().collect::<Vec<_>>())
     }
 
-    /// E.g. "The Witch of the Forest"
-    fn random_the_card_of_the_place(card: &KC) -> String {
-        format!("The {:?} of the {}", card, random_place())
+    #[wasm_bindgen]
+    pub fn hists_js(setup: JsSetup) -> Result<String, ApiError> {
+        Ok(pretty::hists(&setup_from_js("setup", setup)?))
     }
 
-    /// E.g. "The End of the Forest"
-    fn random_the_blank_of_the_place() -> String {
-        format!("The {} of the {}", random_end(), random_place())
+    #[wasm_bindgen]
+    pub fn analysis_js(setup: JsSetup) -> Result<JsAnalysis, ApiError> {
+        to_js(&analysis::analyze(&setup_from_js("setup", setup)?))
     }
 
-    fn random_end() -> String {
-        let ends = vec![
-            "Adventure",
-            "Apprentice",
-            "Bane",
-            "Banishment",
-            "Captivity",
-            "Cleansing",
-            "Coffers",
-            "Coins",
-            "Copper",
-            "Crown",
-            "Curse",
-            "Deceit",
-            "Defeat",
-            "Demise",
-            "Destiny",
-            "Dismissal",
-            "End",
-            "Enigma",
-            "Entry",
-            "Err",
-            "Execution",
-            "Exit",
-            "Failing",
-            "Fate",
-            "Favor",
-            "Fettering",
-            "Flight",
-            "Foresight",
-            "Fortune",
-            "Game",
-            "Gauntlet",
-            "Help",
-            "Incident",
-            "Journey",
-            "Killing",
-            "Kinship",
-            "Loss",
-            "Love",
-            "Mystery",
-            "Nocturne",
-            "Overreach",
-            "Peace",
-            "Plight",
-            "Poverty",
-            "Prudence",
-            "Punishment",
-            "Quickening",
-            "Relief",
-            "Repose",
-            "Sacking",
-            "Screams",
-            "Surrender",
-            "Tell",
-            "Termination",
-            "Treasure",
-            "Triumph",
-            "Turn",
-            "Turning",
-            "Unfettering",
-            "Victory",
-            "Wealth",
-            "Winning",
-            "Yearning",
-            "Yells",
-            "Zeal",
-        ];
+    #[wasm_bindgen]
+    pub fn synergies_js(setup: JsSetup) -> Result<JsSynergies, ApiError> {
+        to_js(&synergy::present(&setup_from_js("setup", setup)?))
+    }
 
-        ends.choose(&mut rand::thread_rng()).unwrap().to_string()
+    #[wasm_bindgen]
+    pub fn similarity_js(a: JsSetup, b: JsSetup) -> Result<f64, ApiError> {
+        Ok(similarity::similarity(
+            &setup_from_js("a", a)?,
+            &setup_from_js("b", b)?,
+        ))
     }
 
-    fn random_place() -> String {
-        let places = vec![
-            "Battlefield",
-            "Battlement",
-            "Castle",
-            "Dungeon",
-            "Field",
-            "Forest",
-            "Kingdom",
-            "Mountain",
-            "Palace",
-            "Pit",
-            "Sea",
-            "Sky",
-            "Tower",
-            "Town",
-            "Village",
-            "Waste",
-            "Woods",
-        ];
+    #[wasm_bindgen]
+    pub fn nearest_js(
+        setup: JsSetup,
+        history: JsHistory,
+        count: usize,
+    ) -> Result<JsNeighbours, ApiError> {
+        let setup = setup_from_js("setup", setup)?;
+        let history: Vec<history::Played> = from_js("history", history)?;
+        to_js(&similarity::nearest(&setup, &history, count))
+    }
 
-        places.choose(&mut rand::thread_rng()).unwrap().to_string()
+    #[wasm_bindgen]
+    pub fn gen_error_js(error: JsGenSetupError) -> Result<String, ApiError> {
+        Ok(pretty::gen_error(from_js("error", error)?))
     }
 
-    pub fn random(setup: &Setup) -> String {
-        let all_cards = setup.cards();
-        let cards: Vec<_> = all_cards
-            .choose_multiple(&mut rand::thread_rng(), 2)
-            .collect();
+    #[wasm_bindgen]
+    pub fn draft_error_js(error: JsDraftError) -> Result<String, ApiError> {
+        Ok(pretty::draft_error(from_js("error", error)?))
+    }
 
-        let card1 = cards.get(0).unwrap();
-        let card2 = cards.get(1).unwrap();
+    #[wasm_bindgen]
+    pub fn draft_new_js(
+        config: JsSetupConfig,
+        players: usize,
+        format: JsDraftFormat,
+    ) -> Result<JsDraft, ApiError> {
+        let config = from_js("config", config)?;
+        let format = from_js("format", format)?;
+        to_js(&draft::Draft::new(config, players, format)?)
+    }
 
-        let names = vec![
-            random_the_cards_blank(card1),
-            random_the_blank_of_the_card(card1),
-            random_the_card_and_the_card(card1, card2),
-            random_the_cards_card(card1, card2),
-            random_the_card_of_the_place(card1),
-            random_the_blank_of_the_place(),
-        ];
+    #[wasm_bindgen]
+    pub fn draft_act_js(
+        draft: JsDraft,
+        player: usize,
+        action: JsAction,
+    ) -> Result<JsDraft, ApiError> {
+        let mut draft: draft::Draft = from_js("draft", draft)?;
+        let action = from_js("action", action)?;
+        draft.act(player, action)?;
+        to_js(&draft)
+    }
 
-        names.choose(&mut rand::thread_rng()).unwrap().to_string()
+    #[wasm_bindgen]
+    pub fn draft_to_act_js(draft: JsDraft) -> Result<Option<usize>, ApiError> {
+        let draft: draft::Draft = from_js("draft", draft)?;
+        Ok(draft.to_act())
+    }
+
+    #[wasm_bindgen]
+    pub fn draft_hand_js(draft: JsDraft, player: usize) -> Result<JsCards, ApiError> {
+        let draft: draft::Draft = from_js("draft", draft)?;
+        to_js(&draft.hand(player))
+    }
+
+    #[wasm_bindgen]
+    pub fn draft_finish_js(draft: JsDraft) -> Result<JsSetup, ApiError> {
+        let draft: draft::Draft = from_js("draft", draft)?;
+        to_js(&draft.finish()?)
+    }
+
+    #[wasm_bindgen]
+    pub fn svg_js(setup: JsSetup, title: &str, paper: JsPaperSize) -> Result<String, ApiError> {
+        let setup = setup_from_js("setup", setup)?;
+        let paper = from_js("paper", paper)?;
+        Ok(svg::sheet(&setup, title, &paper))
+    }
+
+    #[wasm_bindgen]
+    pub fn score_js(setup: JsSetup, decks: JsDecks) -> Result<JsTallies, ApiError> {
+        let setup = setup_from_js("setup", setup)?;
+        let decks: Vec<scoring::Deck> = from_js("decks", decks)?;
+        to_js(&scoring::score_all(&setup, &decks)?)
+    }
+
+    /// A game for hot-seat players and bots. Unlike drafts it stays on the
+    /// Rust side, as its shuffle seeds don't fit in a JavaScript number.
+    #[wasm_bindgen]
+    pub struct GameSession(session::Session);
+
+    #[wasm_bindgen]
+    impl GameSession {
+        /// Start a game of `setup` with a player per seat, playing the bots
+        /// until a person has to decide something
+        #[wasm_bindgen(constructor)]
+        pub fn new(setup: JsSetup, seats: JsSeats, seed: u32) -> Result<GameSession, ApiError> {
+            let setup = setup_from_js("setup", setup)?;
+            let seats = from_js("seats", seats)?;
+            Ok(GameSession(session::Session::new(
+                &setup,
+                seats,
+                seed.into(),
+            )?))
+        }
+
+        /// The game as `seat` can see it
+        pub fn view(&self, seat: usize) -> Result<JsView, ApiError> {
+            self.check_seat(seat)?;
+            to_js(&self.0.view(seat))
+        }
+
+        /// The decision the game waits on from `seat`, or null while it
+        /// waits on someone else (see `waiting_on`)
+        pub fn decision(&self, seat: usize) -> Result<JsDecision, ApiError> {
+            self.check_seat(seat)?;
+            to_js(&self.0.decision(seat))
+        }
+
+        /// The seat the game waits on, if it waits on anyone
+        pub fn waiting_on(&self) -> Option<usize> {
+            self.0.pending().map(|decision| decision.player)
+        }
+
+        /// Answer the pending decision as `seat`, then play the bots until
+        /// a person has to decide something again
+        pub fn choose(&mut self, seat: usize, choice: JsChoice) -> Result<(), ApiError> {
+            let choice = from_js("choice", choice)?;
+            Ok(self.0.choose(seat, choice)?)
+        }
+
+        /// What `seat` may see of everything that's happened, from the
+        /// `since`th event on
+        pub fn events(&self, seat: usize, since: usize) -> Result<JsEvents, ApiError> {
+            self.check_seat(seat)?;
+            to_js(&self.0.events(seat, since))
+        }
+
+        /// How many events there are, to pass as `since` next time
+        pub fn event_count(&self) -> usize {
+            self.0.game().events.len()
+        }
+
+        pub fn is_over(&self) -> bool {
+            self.0.game().is_over()
+        }
+
+        pub fn scores(&self) -> Result<JsScores, ApiError> {
+            let game = self.0.game();
+            to_js(
+                &(0..game.players.len())
+                    .map(|player| game.score(player))
+                    .collect::<Vec<_>>(),
+            )
+        }
+
+        fn check_seat(&self, seat: usize) -> Result<(), ApiError> {
+            if seat < self.0.seats.len() {
+                Ok(())
+            } else {
+                Err(ApiError::InvalidInput {
+                    argument: "seat".to_string(),
+                    message: format!("there are only {} seats", self.0.seats.len()),
+                })
+            }
+        }
+    }
+
+    fn union<T: Debug>(values: impl Iterator<Item = T>) -> String {
+        values.map(|v| format!("\n  | \"{:?}\"", v)).collect()
+    }
+
+    fn numbers(values: impl Iterator<Item = usize>) -> String {
+        values
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// The TypeScript definitions for everything the exports take and return
+    pub fn typescript() -> String {
+        format!(
+            r#"// Generated by `dominion::wasm::typescript()`, don't edit by hand.
+// Regenerate with `DOMINION_UPDATE_TYPES=1 cargo test typescript`.
+
+export type KC ={kc};
+
+export type Expansion ={expansion};
+
+export type Project ={project};
+
+export type BaneCard ={bane_card};
+
+export type CardType ={card_type};
+
+export type Role ={role};
+
+export type ProjectCount = {project_count};
+
+export type BaneCount = {bane_count};
+
+export type SwapPreference ={swap_preference};
+
+export type DraftFormat ={draft_format};
+
+export type PaperSize ={paper_size};
+
+export interface SetupConfig {{
+  include_expansions?: Expansion[] | null;
+  ban_cards?: KC[] | null;
+  include_cards?: KC[] | null;
+  project_count?: ProjectCount | null;
+  bane_count?: BaneCount | null;
+  require_combo?: boolean;
+  avoid_swingy?: boolean;
+  max_similarity?: number | null;
+  max_power?: number | null;
+  separate_top_cards?: boolean;
+  expansion_weights?: Partial<Record<Expansion, number>> | null;
+  card_weights?: Partial<Record<KC, number>> | null;
+  equal_expansions?: boolean;
+  expansion_counts?: Partial<Record<Expansion, ExpansionCount>> | null;
+  min_per_expansion?: number | null;
+}}
+
+export interface ExpansionCount {{
+  min?: number;
+  max?: number | null;
+}}
+
+export interface Setup {{
+  kingdom_cards: KC[];
+  bane_card: KC | null;
+  project_cards: Project[];
+  bane_cards: Partial<Record<KC, BaneCard>>;
+  second_zebra: KC | null;
+  parent?: string;
+}}
+
+export type Slot =
+  | {{ KingdomCard: KC }}
+  | {{ Project: Project }}
+  | "Bane"
+  | "SecondZebra";
+
+export interface EvolveConfig {{
+  kingdom_swaps: number;
+  project_swaps: number;
+  prefer: SwapPreference;
+}}
+
+export interface Played {{
+  name: string;
+  at: string;
+  setup: Setup;
+  rating?: number | null;
+  scores?: Score[];
+}}
+
+export interface Neighbour {{
+  similarity: number;
+  played: Played;
+}}
+
+export type Action = {{ Pick: KC }} | {{ Veto: KC }};
+
+export interface Draft {{
+  format: DraftFormat;
+  config: SetupConfig;
+  hands: KC[][];
+  picked: KC[];
+  vetoed: KC[];
+}}
+
+export type Label ={label};
+
+export interface Analysis {{
+  engine_potential: number;
+  attack_intensity: number;
+  counterplay: number;
+  big_money_viability: number;
+  label: Label;
+  reasons: string[];
+}}
+
+export type SynergyKind ={synergy_kind};
+
+export type Partner = {{ Card: KC }} | {{ Project: Project }};
+
+export interface Synergy {{
+  card: KC;
+  partner: Partner;
+  kind: SynergyKind;
+  why: string;
+}}
+
+export type GenSetupError =
+  | "CouldNotSatisfyProjectsFromExpansions"
+  | "CouldNotSatisfyKingdomCards"
+  | "CouldNotSatisfyBaneCard"
+  | "CouldNotSatisfySecondZebra"
+  | {{ IntersectingCardBansAndIncludes: KC[] }}
+  | "TooManyCardsIncluded"
+  | {{ SlotNotInSetup: Slot }}
+  | "CouldNotSatisfySynergies"
+  | "CouldNotSatisfyMaxSimilarity"
+  | "CouldNotSatisfyPower"
+  | {{ ImpossibleExpansionCount: Expansion }}
+  | "CouldNotSatisfyExpansionCounts"
+  | "InvalidWeight";
+
+export type SetupError =
+  | {{ KingdomCardCount: number }}
+  | {{ DuplicateCard: KC }}
+  | "MissingBane"
+  | {{ UnneededBane: KC }}
+  | {{ BaneCost: KC }}
+  | {{ BaneCardNotInKingdom: KC }}
+  | "MissingSecondZebra"
+  | {{ UnneededSecondZebra: KC }}
+  | {{ SecondZebraInKingdom: KC }}
+  | {{ TooManyProjects: number }};
+
+export type DraftError =
+  | {{ BadPlayerCount: number }}
+  | {{ NotYourTurn: {{ expected: number; got: number }} }}
+  | {{ WrongAction: Action }}
+  | {{ NotInHand: KC }}
+  | "AlreadyFinished"
+  | "NotFinished"
+  | {{ Setup: GenSetupError }};
+
+export type Card =
+  | "Copper"
+  | "Silver"
+  | "Gold"
+  | "Estate"
+  | "Duchy"
+  | "Province"
+  | "Curse"
+  | KC;
+
+export type ScoringCard = Card | "Platinum" | "Colony";
+
+export type Phase ={phase};
+
+export type DecisionKind ={decision_kind};
+
+export interface Decision {{
+  player: number;
+  kind: DecisionKind;
+  source: Card | null;
+  options: Card[];
+  min: number;
+  max: number;
+}}
+
+export interface Pile {{
+  card: Card;
+  count: number;
+}}
+
+export interface PlayerView {{
+  name: string;
+  deck: number;
+  hand: number;
+  discard: number;
+  discard_top: Card | null;
+  in_play: Card[];
+  score: number;
+}}
+
+export interface View {{
+  seat: number;
+  hand: Card[];
+  players: PlayerView[];
+  supply: Pile[];
+  trash: Card[];
+  turn: number;
+  current: number;
+  phase: Phase;
+  actions: number;
+  buys: number;
+  coins: number;
+  decision: Decision | null;
+  waiting_on: number | null;
+  over: boolean;
+}}
+
+/** Shuffles are never handed out, so players can't work out deck order. */
+export type GameEvent =
+  | {{ Turn: {{ player: number; turn: number }} }}
+  | {{ Decision: {{ decision: Decision; choice: Card[] }} }}
+  | {{ Gain: {{ player: number; card: Card }} }}
+  | {{ Trash: {{ player: number; card: Card }} }}
+  | {{ Score: {{ player: number; score: number }} }}
+  | {{ End: {{ scores: number[] }} }};
+
+export type Bot = "BigMoney" | "BigMoneySmithy" | {{ SingleCard: KC }};
+
+export type Seat = "Human" | {{ Bot: Bot }};
+
+export type EngineError =
+  | {{ UnsupportedCard: KC }}
+  | "UnsupportedSetup"
+  | {{ BadPlayerCount: number }}
+  | {{ NotInSupply: KC }}
+  | "Suspended"
+  | {{ NotYourDecision: number }}
+  | {{ InvalidChoice: {{ decision: Decision; choice: Card[] }} }}
+  | "GameOver";
+
+/** Counts of every card a player ended with, wherever it is. */
+export interface Deck {{
+  name: string;
+  cards: Partial<Record<ScoringCard, number>>;
+  vp_tokens?: number;
+}}
+
+export interface Score {{
+  name: string;
+  cards: Partial<Record<ScoringCard, number>>;
+  vp_tokens: number;
+  total: number;
+}}
+
+export type ScoringError = {{ NotInKingdom: KC }};
+
+/** What every export throws. `message` is always fit to show a player. */
+export type ApiError =
+  | {{ kind: "InvalidInput"; argument: string; message: string }}
+  | {{ kind: "InvalidSetup"; argument: string; errors: SetupError[]; message: string }}
+  | {{ kind: "GenSetup"; error: GenSetupError; message: string }}
+  | {{ kind: "Draft"; error: DraftError; message: string }}
+  | {{ kind: "Game"; error: EngineError; message: string }}
+  | {{ kind: "Scoring"; error: ScoringError; message: string }}
+  | {{ kind: "Internal"; message: string }};
+"#,
+            kc = union(KC::iter()),
+            expansion = union(Expansion::iter()),
+            project = union(Project::iter()),
+            bane_card = union(BaneCard::iter()),
+            card_type = union(CardType::iter()),
+            role = union(Role::iter()),
+            label = union(analysis::Label::iter()),
+            synergy_kind = union(synergy::Kind::iter()),
+            project_count = numbers(ProjectCount::iter().map(|c| c.count())),
+            bane_count = numbers(BaneCount::iter().map(|c| c.count())),
+            swap_preference = union(SwapPreference::iter()),
+            draft_format = union(draft::DraftFormat::iter()),
+            paper_size = union(svg::PaperSize::iter()),
+            phase = union(engine::Phase::iter()),
+            decision_kind = union(engine::DecisionKind::iter()),
+        )
     }
 }
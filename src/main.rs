@@ -1,17 +1,1042 @@
 use clap::{Arg, ArgMatches, Command};
-use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::fs;
 use std::hash::Hash;
+use std::io;
+use std::io::{BufRead, Write};
 use std::process;
 use std::str::FromStr;
 
 fn main() {
-    let matches = Command::new("Dominion")
+    let matches = cli().get_matches();
+
+    let history = matches.value_of("history").unwrap();
+    // The on/off constraints are turned on by their flag and off by its
+    // --no- counterpart, and left unset without either for the config file
+    // and preset to decide
+    let flag = |name: &str| {
+        if matches.is_present(name) {
+            Some(true)
+        } else if matches.is_present(format!("no-{}", name)) {
+            Some(false)
+        } else {
+            None
+        }
+    };
+    let flags = dominion::config::Preset {
+        include_expansions: optional_set(&matches, "include-expansions"),
+        ban_cards: optional_set(&matches, "ban-cards"),
+        include_cards: optional_set(&matches, "include-cards"),
+        project_count: matches
+            .value_of("project-count")
+            .map(|_| matches.value_of_t_or_exit("project-count")),
+        bane_count: matches
+            .value_of("bane-count")
+            .map(|_| matches.value_of_t_or_exit("bane-count")),
+        require_combo: flag("require-combo"),
+        avoid_swingy: flag("avoid-swingy"),
+        max_similarity: matches
+            .value_of("max-similarity")
+            .map(|_| matches.value_of_t_or_exit("max-similarity")),
+        max_power: matches
+            .value_of("max-power")
+            .map(|_| matches.value_of_t_or_exit("max-power")),
+        separate_top_cards: flag("separate-top-cards"),
+        expansion_weights: weights(&matches, |weight| match weight {
+            Weight::Expansion(expansion, weight) => Some((expansion, weight)),
+            Weight::Card(..) => None,
+        }),
+        card_weights: weights(&matches, |weight| match weight {
+            Weight::Card(card, weight) => Some((card, weight)),
+            Weight::Expansion(..) => None,
+        }),
+        equal_expansions: flag("equal-expansions"),
+        expansion_counts: matches.values_of("expansion-count").map(|values| {
+            values
+                .map(|value| parse_expansion_count(value).unwrap())
+                .collect()
+        }),
+        min_per_expansion: matches
+            .value_of("min-per-expansion")
+            .map(|_| matches.value_of_t_or_exit("min-per-expansion")),
+    };
+
+    let json = matches.is_present("output-json");
+
+    let config = read_config(matches.value_of("config"), json)
+        .resolve(matches.value_of("preset"), flags)
+        .unwrap_or_else(|err| {
+            let message = dominion::pretty::config_error(err.clone());
+            fail(json, err, message)
+        });
+    // The kingdoms played are only needed to keep new ones unlike them
+    let previous: Vec<dominion::Setup> = match config.max_similarity {
+        Some(_) => read_history(history, json)
+            .into_iter()
+            .map(|played| played.setup)
+            .collect(),
+        None => vec![],
+    };
+
+    let (subcommand, subcommand_matches) = matches.subcommand().unwrap_or(("generate", &matches));
+    if let Some(format) = subcommand_matches.value_of("output-format") {
+        if !formats(subcommand).contains(&format) {
+            eprintln!("{}", format_error(subcommand, format));
+            process::exit(1);
+        }
+    }
+
+    match matches.subcommand() {
+        Some(("interactive", _)) => interactive(config, history, json),
+        Some(("nearest", nearest_matches)) => nearest(nearest_matches, history, json),
+        Some(("simulate", simulate_matches)) => simulate(config, simulate_matches, json),
+        Some(("replay", replay_matches)) => replay(replay_matches, json),
+        Some(("score", score_matches)) => score(score_matches, history, json),
+        Some(("rate", rate_matches)) => rate(rate_matches, history, json),
+        Some(("rank", rank_matches)) => {
+            let seed = rank_matches
+                .value_of("seed")
+                .map(|_| rank_matches.value_of_t_or_exit("seed"))
+                .unwrap_or(0);
+            let table = dominion::power::rank(rank_matches.value_of_t_or_exit("games"), seed);
+            println!("{}", serde_json::to_string_pretty(&table).unwrap());
+        }
+        Some(("list", list_matches)) => list(list_matches),
+        Some(("show", show_matches)) => show(show_matches),
+        Some(("validate", validate_matches)) => validate(validate_matches),
+        Some(("count", count_matches)) => count(count_matches, &config),
+        Some(("draft", draft_matches)) => {
+            let setup = draft(config.clone(), draft_matches, json);
+            output_setup(draft_matches, &config, None, setup)
+        }
+        Some(("evolve", evolve_matches)) => {
+            let setup = evolve(config.clone(), evolve_matches, history, json);
+            output_setup(evolve_matches, &config, None, setup)
+        }
+        Some(("generate", generate_matches)) => generate(generate_matches, &config, &previous),
+        _ => generate(&matches, &config, &previous),
+    }
+}
+
+/// The `--format`s a subcommand takes
+fn formats(subcommand: &str) -> &'static [&'static str] {
+    match subcommand {
+        "generate" | "draft" | "evolve" => &["markdown", "html", "svg"],
+        "list" | "show" | "count" | "validate" => &["markdown", "html"],
+        _ => &[],
+    }
+}
+
+/// The subcommand's help on `--format`, listing what it takes
+fn formats_help(subcommand: &str) -> &'static str {
+    match formats(subcommand) {
+        [] => "--format isn't available here",
+        [_, _] => "--format takes markdown or html",
+        _ => "--format takes markdown, html or svg",
+    }
+}
+
+fn format_error(subcommand: &str, format: &str) -> String {
+    match formats(subcommand) {
+        [] => format!("{} doesn't take --format", subcommand),
+        formats => format!(
+            "{} doesn't take --format {}, only {}",
+            subcommand,
+            format,
+            formats.join(", ")
+        ),
+    }
+}
+
+/// How `list`, `show`, `count` and `validate` print: text, JSON (`--json`)
+/// or a document (`--format`)
+enum Output {
+    Text,
+    Json,
+    Markdown,
+    Html,
+}
+
+fn output(matches: &ArgMatches) -> Output {
+    if matches.is_present("output-json") {
+        return Output::Json;
+    }
+
+    match matches.value_of("output-format") {
+        Some("markdown") => Output::Markdown,
+        Some("html") => Output::Html,
+        Some(format) => unreachable!("--format {} is checked in main", format),
+        None => Output::Text,
+    }
+}
+
+/// Print `text` rendered by the renderer `output` picks, or `json`
+fn print_output(
+    output: Output,
+    text: impl Fn(&dyn dominion::pretty::Renderer) -> String,
+    json: impl FnOnce() -> String,
+) {
+    match output {
+        Output::Text => print!("{}", text(&dominion::pretty::Text)),
+        Output::Markdown => print!("{}", text(&dominion::pretty::Markdown)),
+        Output::Html => print!("{}", text(&dominion::pretty::Html)),
+        Output::Json => println!("{}", json()),
+    }
+}
+
+fn generate(matches: &ArgMatches, config: &dominion::SetupConfig, previous: &[dominion::Setup]) {
+    match matches.value_of("setup") {
+        Some(path) => {
+            let setup = read_setup_or_exit(path, matches.is_present("output-json"));
+            let slots: Vec<dominion::Slot> = matches
+                .value_of("reroll")
+                .map(|_| matches.values_of_t_or_exit("reroll"))
+                .unwrap_or_default();
+
+            let setup = slots.into_iter().try_fold(setup, |setup, slot| {
+                dominion::reroll_with_previous(&setup, slot, config.clone(), previous)
+            });
+            output_setup(matches, config, None, setup)
+        }
+        None => {
+            let seed = matches
+                .value_of("seed")
+                .map(|_| matches.value_of_t_or_exit("seed"))
+                .unwrap_or_else(rand::random);
+
+            let mut rng = StdRng::seed_from_u64(seed);
+            let setup = if matches.is_present("uniform") {
+                dominion::count::sample_with_rng(config.clone(), previous, &mut rng)
+            } else {
+                dominion::gen_setup_with_rng(config.clone(), previous, &mut rng)
+            };
+            output_setup(matches, config, Some(seed), setup)
+        }
+    }
+}
+
+fn output_setup(
+    matches: &ArgMatches,
+    config: &dominion::SetupConfig,
+    seed: Option<u64>,
+    setup: Result<dominion::Setup, dominion::GenSetupError>,
+) {
+    let json = matches.is_present("output-json");
+
+    match setup {
+        Ok(setup) if json => {
+            let name = if matches.is_present("output-name") {
+                Some(dominion::game_name::random(&setup))
+            } else {
+                None
+            };
+
+            println!("{}", dominion::json::setup(name, seed, config, &setup));
+        }
+        Ok(setup) if matches.is_present("output-format") => {
+            let title = if matches.is_present("output-name") {
+                dominion::game_name::random(&setup)
+            } else {
+                "Kingdom".to_string()
+            };
+
+            let document = match matches.value_of("output-format") {
+                Some("html") => dominion::pretty::render(&dominion::pretty::Html, &title, &setup),
+                Some("svg") => {
+                    dominion::svg::sheet(&setup, &title, &matches.value_of_t_or_exit("paper"))
+                }
+                _ => dominion::pretty::render(&dominion::pretty::Markdown, &title, &setup),
+            };
+
+            print!("{}", document);
+        }
+        Ok(setup) => {
+            let name = if matches.is_present("output-name") {
+                let name = dominion::game_name::random(&setup);
+                println!("== {} ==", name);
+                name
+            } else {
+                "Game".to_string()
+            };
+
+            if matches.is_present("output-pretty") {
+                println!("------------------ SETUP ------------------");
+                println!("");
+                println!("{}", dominion::pretty::pretty(&setup));
+                println!("");
+            }
+
+            if matches.is_present("output-raw") {
+                println!("------------------ RAW ------------------");
+                println!("");
+                println!("{:?}", setup);
+                println!("");
+            }
+
+            if matches.is_present("output-code") {
+                println!("------------------ CODE ------------------");
+                println!("");
+                println!("{}", dominion::pretty::code(name, &setup));
+                println!("");
+            }
+
+            if matches.is_present("output-hists") {
+                println!("------------------ HISTS ------------------");
+                println!("");
+                println!("{}", dominion::pretty::hists(&setup));
+                println!("");
+            }
+
+            if matches.is_present("output-analysis") {
+                println!("------------------ ANALYSIS ------------------");
+                println!();
+                println!(
+                    "{}",
+                    dominion::pretty::analysis(&dominion::analysis::analyze(&setup))
+                );
+                println!("{}", dominion::pretty::synergies(&setup));
+            }
+        }
+        Err(err) if json => {
+            println!("{}", dominion::json::error(err));
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!(
+                "Error generating kingdom!\n\n{}",
+                dominion::pretty::gen_error(err)
+            );
+            process::exit(1);
+        }
+    }
+}
+
+fn interactive(config: dominion::SetupConfig, history: &str, json: bool) {
+    use dominion::interactive::{Command, Session, HELP};
+
+    let mut session = Session::new(config).unwrap_or_else(|err| {
+        let message = format!(
+            "Error generating kingdom!\n\n{}",
+            dominion::pretty::gen_error(err.clone())
+        );
+        fail(json, err, message)
+    });
+
+    println!("{}", session.render());
+    println!("{}", HELP);
+
+    for line in io::stdin().lines() {
+        let line = line.expect("could not read from stdin");
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.parse::<Command>() {
+            Ok(Command::Quit) => break,
+            Ok(Command::Help) => println!("{}", HELP),
+            Ok(Command::Code) => {
+                let name = dominion::game_name::random(&session.setup);
+                println!("{}", dominion::pretty::code(name, &session.setup));
+            }
+            Ok(Command::Save) => {
+                let name = dominion::game_name::random(&session.setup);
+                let played = dominion::history::Played::now(name.clone(), session.setup.clone());
+                match fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(history)
+                    .and_then(|mut f| f.write_all(dominion::history::line(&played).as_bytes()))
+                {
+                    Ok(()) => println!("Saved \"{}\" to {}", name, history),
+                    Err(err) => eprintln!("Could not save to {}: {}", history, err),
+                }
+            }
+            Ok(command) => match session.apply(command) {
+                Ok(()) => println!("{}", session.render()),
+                Err(err) => eprintln!("{}", dominion::pretty::gen_error(err)),
+            },
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+fn draft(
+    config: dominion::SetupConfig,
+    matches: &ArgMatches,
+    json: bool,
+) -> Result<dominion::Setup, dominion::GenSetupError> {
+    use dominion::draft::{Action, Draft, DraftError};
+
+    let exit_with = |err: DraftError| -> ! {
+        let message = format!(
+            "Error drafting!\n\n{}",
+            dominion::pretty::draft_error(err.clone())
+        );
+        fail(json, err, message)
+    };
+
+    let players = matches.value_of_t_or_exit("players");
+    let format = matches.value_of_t_or_exit("draft-format");
+    let mut draft = Draft::new(config, players, format).unwrap_or_else(|err| exit_with(err));
+
+    let moves: Box<dyn BufRead> = match matches.value_of("script") {
+        Some(path) => Box::new(io::BufReader::new(fs::File::open(path).unwrap_or_else(
+            |err| {
+                fail(
+                    json,
+                    UNREADABLE,
+                    format!("Could not read {}: {}", path, err),
+                )
+            },
+        ))),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut moves = moves.lines();
+
+    while let Some(player) = draft.to_act() {
+        eprintln!("Player {}: {:?}", player + 1, draft.hand(player));
+
+        let line = match moves.next() {
+            Some(line) => line.expect("could not read moves"),
+            None => exit_with(DraftError::NotFinished),
+        };
+
+        match line.parse::<Action>() {
+            Ok(action) => {
+                if let Err(err) = draft.act(player, action) {
+                    eprintln!("{}", dominion::pretty::draft_error(err));
+                }
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    draft.finish().or_else(|err| match err {
+        DraftError::Setup(err) => Err(err),
+        err => exit_with(err),
+    })
+}
+
+fn evolve(
+    config: dominion::SetupConfig,
+    matches: &ArgMatches,
+    history: &str,
+    json: bool,
+) -> Result<dominion::Setup, dominion::GenSetupError> {
+    let played = read_history(history, json);
+
+    let parent = match matches.value_of("setup") {
+        Some(path) => read_setup_or_exit(path, json),
+        None => match played.last() {
+            Some(last) => last.setup.clone(),
+            None => fail(
+                json,
+                NO_SETUP,
+                format!(
+                    "Nothing to evolve! {} has no kingdoms, pass --setup",
+                    history
+                ),
+            ),
+        },
+    };
+
+    let evolve = dominion::EvolveConfig {
+        kingdom_swaps: matches.value_of_t_or_exit("swap"),
+        project_swaps: matches.value_of_t_or_exit("swap-projects"),
+        prefer: matches.value_of_t_or_exit("prefer"),
+    };
+
+    dominion::evolve(&parent, &evolve, config, &played)
+}
+
+fn nearest(matches: &ArgMatches, history: &str, json: bool) {
+    let setup = match matches.value_of("setup") {
+        Some(path) => read_setup_or_exit(path, json),
+        None => fail(json, NO_SETUP, "Nearest to what? Pass --setup".to_string()),
+    };
+    let played = read_history(history, json);
+    let neighbours =
+        dominion::similarity::nearest(&setup, &played, matches.value_of_t_or_exit("count"));
+
+    if json {
+        println!("{}", dominion::json::nearest(&setup, &neighbours));
+        return;
+    }
+
+    for neighbour in neighbours {
+        println!(
+            "{:.2}  {} ({})\n      {}",
+            neighbour.similarity,
+            neighbour.played.name,
+            neighbour.played.at,
+            dominion::pretty::format_setup(&neighbour.played.setup)
+        );
+    }
+}
+
+fn simulate(config: dominion::SetupConfig, matches: &ArgMatches, json: bool) {
+    let seed = matches
+        .value_of("seed")
+        .map(|_| matches.value_of_t_or_exit("seed"))
+        .unwrap_or_else(rand::random);
+    let bots: Vec<dominion::bots::Bot> = matches.values_of_t_or_exit("bots");
+
+    let setup = match matches.value_of("setup") {
+        Some(path) => read_setup_or_exit(path, json),
+        None => {
+            let config = dominion::SetupConfig {
+                include_cards: Some(
+                    bots.iter()
+                        .filter_map(dominion::bots::Bot::card)
+                        .chain(config.include_cards.into_iter().flatten())
+                        .collect(),
+                ),
+                include_expansions: config
+                    .include_expansions
+                    .or_else(|| Some([dominion::Expansion::Base2].into_iter().collect())),
+                project_count: config
+                    .project_count
+                    .or(Some(dominion::ProjectCount::NoProjects)),
+                ..config
+            };
+            dominion::gen_setup_with_rng(config, &[], &mut StdRng::seed_from_u64(seed))
+                .unwrap_or_else(|err| {
+                    let message = format!(
+                        "Error generating kingdom!\n\n{}",
+                        dominion::pretty::gen_error(err.clone())
+                    );
+                    fail(json, err, message)
+                })
+        }
+    };
+    if let Some(path) = matches.value_of("log") {
+        let log = dominion::bots::play(&setup, &bots, seed).map(|(game, _)| {
+            let names = bots.iter().map(|bot| bot.to_string()).collect();
+            dominion::gamelog::write(&setup, names, &game)
+        });
+        match log {
+            Ok(log) => fs::write(path, log).unwrap_or_else(|err| {
+                fail(
+                    json,
+                    "UnwritableFile",
+                    format!("Could not write log {}: {}", path, err),
+                )
+            }),
+            Err(err) => simulation_failed(json, err),
+        }
+    }
+
+    match dominion::bots::simulate(&setup, &bots, matches.value_of_t_or_exit("games"), seed) {
+        Ok(simulation) if json => {
+            println!("{}", dominion::json::simulation(seed, &setup, &simulation))
+        }
+        Ok(simulation) => {
+            println!("{}\n", dominion::pretty::format_setup(&setup));
+            print!("{}", dominion::pretty::simulation(&simulation));
+        }
+        Err(err) => simulation_failed(json, err),
+    }
+}
+
+fn simulation_failed(json: bool, err: dominion::engine::EngineError) -> ! {
+    let message = format!(
+        "Could not simulate the kingdom!\n\n{}",
+        dominion::pretty::engine_error(err.clone())
+    );
+    fail(json, err, message)
+}
+
+fn replay(matches: &ArgMatches, json: bool) {
+    let path = matches.value_of("log").unwrap();
+    let log = fs::read_to_string(path).unwrap_or_else(|err| {
+        fail(
+            json,
+            UNREADABLE,
+            format!("Could not read log {}: {}", path, err),
+        )
+    });
+    let (header, events) = dominion::gamelog::parse(&log)
+        .and_then(|(header, events)| {
+            dominion::gamelog::replay(&header, &events)?;
+            Ok((header, events))
+        })
+        .unwrap_or_else(|err| {
+            let message = format!(
+                "Bad game log!\n\n{}",
+                dominion::pretty::log_error(err.clone())
+            );
+            fail(json, err, message)
+        });
+
+    if json {
+        println!("{}", dominion::json::replay(&header, &events));
+        return;
+    }
+
+    if matches.is_present("verify") {
+        println!(
+            "{} replays the same: {} events over {} turns",
+            path,
+            events.len(),
+            dominion::gamelog::turns(&events).len() - 1
+        );
+        return;
+    }
+
+    println!("{}", dominion::pretty::format_setup(&header.setup));
+    for (seat, name) in header.names.iter().enumerate() {
+        println!("Player {}: {}", seat + 1, name);
+    }
+
+    let mut all = matches.is_present("all");
+    for turn in dominion::gamelog::turns(&events) {
+        println!();
+        for event in turn {
+            println!("{}", dominion::pretty::event(event));
+        }
+
+        if !all {
+            // Once stdin runs out, show the rest without waiting
+            all = io::stdin()
+                .lock()
+                .read_line(&mut String::new())
+                .unwrap_or(0)
+                == 0;
+        }
+    }
+}
+
+fn score(matches: &ArgMatches, history: &str, json: bool) {
+    let setup = match matches.value_of("setup") {
+        Some(path) => read_setup_or_exit(path, json),
+        None => fail(
+            json,
+            NO_SETUP,
+            "Score which kingdom? Pass --setup".to_string(),
+        ),
+    };
+
+    let path = matches.value_of("decks").unwrap();
+    let decks: Vec<dominion::scoring::Deck> = if path == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(path)
+    }
+    .map_err(|err| (UNREADABLE, format!("Could not read {}: {}", path, err)))
+    .and_then(|decks| {
+        serde_json::from_str(&decks)
+            .map_err(|err| ("BadDecks", format!("Could not parse {}: {}", path, err)))
+    })
+    .unwrap_or_else(|(err, message)| fail(json, err, message));
+
+    let scores = dominion::scoring::score_all(&setup, &decks).unwrap_or_else(|err| {
+        let message = format!(
+            "Error scoring game!\n\n{}",
+            dominion::pretty::scoring_error(err.clone())
+        );
+        fail(json, err, message)
+    });
+
+    if json {
+        println!("{}", dominion::json::scores(&setup, &scores));
+    } else {
+        print!("{}", dominion::pretty::scores(&scores));
+    }
+
+    if matches.is_present("save") {
+        let name = dominion::game_name::random(&setup);
+        let played = dominion::history::Played {
+            scores,
+            ..dominion::history::Played::now(name.clone(), setup)
+        };
+        match fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(history)
+            .and_then(|mut f| f.write_all(dominion::history::line(&played).as_bytes()))
+        {
+            Ok(()) => eprintln!("Saved \"{}\" to {}", name, history),
+            Err(err) => eprintln!("Could not save to {}: {}", history, err),
+        }
+    }
+}
+
+/// Rate a kingdom in the history log, rewriting the log
+fn rate(matches: &ArgMatches, history: &str, json: bool) {
+    let mut played = read_history(history, json);
+    let rating = matches.value_of_t_or_exit("rating");
+
+    let name = dominion::history::rate(&mut played, matches.value_of("game"), rating)
+        .unwrap_or_else(|err| {
+            let message = dominion::pretty::rating_error(err.clone());
+            fail(json, err, message)
+        });
+
+    let log: String = played.iter().map(dominion::history::line).collect();
+    match fs::write(history, log) {
+        Ok(()) => eprintln!("Rated \"{}\" {} in {}", name, rating, history),
+        Err(err) => fail(
+            json,
+            "UnwritableFile",
+            format!("Could not save to {}: {}", history, err),
+        ),
+    }
+}
+
+fn list(matches: &ArgMatches) {
+    use dominion::catalog::{Filter, Listing};
+
+    let listing: Listing = matches.value_of_t_or_exit("what");
+    let filter = Filter {
+        expansions: optional_set(matches, "expansion"),
+        types: optional_set(matches, "type"),
+        costs: optional_set(matches, "cost"),
+    };
+    let entries = dominion::catalog::list(listing, &filter);
+
+    print_output(
+        output(matches),
+        |renderer| dominion::pretty::listing(renderer, listing, &entries),
+        || dominion::json::listing(listing, &filter, &entries),
+    );
+}
+
+fn count(matches: &ArgMatches, config: &dominion::SetupConfig) {
+    let count = match dominion::count::count(config) {
+        Ok(count) => count,
+        Err(err) if matches.is_present("output-json") => {
+            println!("{}", dominion::json::error(err));
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!(
+                "Error counting kingdoms!\n\n{}",
+                dominion::pretty::gen_error(err)
+            );
+            process::exit(1);
+        }
+    };
+
+    print_output(
+        output(matches),
+        |renderer| dominion::pretty::count(renderer, config, &count),
+        || dominion::json::count(config, &count),
+    );
+}
+
+fn show(matches: &ArgMatches) {
+    let card: dominion::KC = matches.value_of_t_or_exit("card");
+    let details = dominion::catalog::details(card);
+
+    print_output(
+        output(matches),
+        |renderer| dominion::pretty::card(renderer, &details),
+        || dominion::json::card(&details),
+    );
+}
+
+/// Check a setup, or else a config file's defaults and every preset
+fn validate(matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let json = matches.is_present("output-json");
+    let contents = read_input(path).unwrap_or_else(|err| fail(json, UNREADABLE, err));
+
+    let valid = if let Some(setup) = parse_setup(&contents) {
+        report(
+            matches,
+            path,
+            &[("Setup".to_string(), setup.validate())],
+            dominion::pretty::setup_error,
+        )
+    } else if contents.contains("\"kingdom_cards\"") {
+        // Meant to be a setup, so say why it isn't one
+        let err = serde_json::from_str::<dominion::Setup>(&contents).unwrap_err();
+        fail(
+            json,
+            "BadSetup",
+            format!("{} isn't a valid setup: {}", path, err),
+        )
+    } else {
+        let is_json = path.ends_with(".json") || contents.trim_start().starts_with('{');
+        let file = dominion::config::ConfigFile::parse(&contents, is_json).unwrap_or_else(|err| {
+            let message = format!(
+                "{} is neither a setup nor a config!\n\n{}",
+                path,
+                dominion::pretty::config_error(err.clone())
+            );
+            fail(json, err, message)
+        });
+
+        let none = dominion::config::Preset::default();
+        let mut problems = vec![(
+            "Defaults".to_string(),
+            file.resolve(None, none.clone()).unwrap().validate(),
+        )];
+        for name in file.presets.keys() {
+            problems.push((
+                format!("Preset {}", name),
+                file.resolve(Some(name), none.clone()).unwrap().validate(),
+            ));
+        }
+        report(matches, path, &problems, dominion::pretty::gen_error)
+    };
+
+    if !valid {
+        process::exit(1);
+    }
+}
+
+/// Print the problems found in each part of `path`, returning whether there
+/// were none
+fn report<E: serde::Serialize + Clone>(
+    matches: &ArgMatches,
+    path: &str,
+    problems: &[(String, Vec<E>)],
+    message: fn(E) -> String,
+) -> bool {
+    let messages: Vec<(String, Vec<String>)> = problems
+        .iter()
+        .map(|(part, errors)| (part.clone(), errors.iter().cloned().map(message).collect()))
+        .collect();
+
+    print_output(
+        output(matches),
+        |renderer| dominion::pretty::validation(renderer, path, &messages),
+        || dominion::json::validation(problems, message),
+    );
+    problems.iter().all(|(_, errors)| errors.is_empty())
+}
+
+/// The config file at `path`, or the one in the user's config directory if
+/// there is one
+fn read_config(path: Option<&str>, json: bool) -> dominion::config::ConfigFile {
+    let (path, required) = match path {
+        Some(path) => (path.into(), true),
+        None => match std::env::var_os("XDG_CONFIG_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+            }) {
+            Some(dir) => (dir.join("dominion").join("config.toml"), false),
+            None => return dominion::config::ConfigFile::default(),
+        },
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+            return dominion::config::ConfigFile::default()
+        }
+        Err(err) => fail(
+            json,
+            UNREADABLE,
+            format!("Could not read config {}: {}", path.display(), err),
+        ),
+    };
+
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    dominion::config::ConfigFile::parse(&contents, is_json).unwrap_or_else(|err| {
+        let message = format!(
+            "Bad config {}!\n\n{}",
+            path.display(),
+            dominion::pretty::config_error(err.clone())
+        );
+        fail(json, err, message)
+    })
+}
+
+fn read_history(path: &str, json: bool) -> Vec<dominion::history::Played> {
+    match fs::read_to_string(path) {
+        Ok(log) => {
+            let played = dominion::history::parse(&log).unwrap_or_else(|err| {
+                fail(
+                    json,
+                    BAD_HISTORY,
+                    format!("Could not parse history log {}: {}", path, err),
+                )
+            });
+            for game in &played {
+                if let Some(problems) = broken_rules(&game.setup) {
+                    fail(
+                        json,
+                        BAD_HISTORY,
+                        format!(
+                            "\"{}\" in history log {} breaks the rules of setting up!\n\n{}",
+                            game.name, path, problems
+                        ),
+                    );
+                }
+            }
+            played
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => fail(
+            json,
+            UNREADABLE,
+            format!("Could not read history log {}: {}", path, err),
+        ),
+    }
+}
+
+fn read_setup_or_exit(path: &str, json: bool) -> dominion::Setup {
+    read_setup(path)
+        .unwrap_or_else(|err| fail(json, "BadSetup", format!("Error reading setup!\n\n{}", err)))
+}
+
+/// The `error` of a file that couldn't be read, with `--json`
+const UNREADABLE: &str = "UnreadableFile";
+
+/// The `error` of a history log that isn't one, or has a kingdom breaking
+/// the rules
+const BAD_HISTORY: &str = "BadHistory";
+
+/// The `error` of a subcommand that needs `--setup` and wasn't given one
+const NO_SETUP: &str = "NoSetup";
+
+/// Exit on `error`: as a JSON error object with `--json`, or else as
+/// `message` on stderr
+fn fail(json: bool, error: impl serde::Serialize, message: String) -> ! {
+    if json {
+        println!("{}", dominion::json::failure(error, message));
+    } else {
+        eprintln!("{}", message);
+    }
+    process::exit(1);
+}
+
+fn read_setup(path: &str) -> Result<dominion::Setup, String> {
+    let setup = parse_setup(&read_input(path)?)
+        .ok_or_else(|| format!("{} is neither a JSON setup nor a history code", path))?;
+
+    match broken_rules(&setup) {
+        None => Ok(setup),
+        Some(problems) => Err(format!(
+            "{} breaks the rules of setting up!\n\n{}",
+            path, problems
+        )),
+    }
+}
+
+/// Every rule of setting up that `setup` breaks, one per line, if it breaks any
+fn broken_rules(setup: &dominion::Setup) -> Option<String> {
+    let problems: Vec<String> = setup
+        .validate()
+        .into_iter()
+        .map(dominion::pretty::setup_error)
+        .collect();
+    (!problems.is_empty()).then(|| problems.join("\n"))
+}
+
+/// A JSON setup or a history code
+fn parse_setup(contents: &str) -> Option<dominion::Setup> {
+    serde_json::from_str(contents)
+        .ok()
+        .or_else(|| dominion::pretty::setup_from_code(contents))
+}
+
+/// A file's contents, or stdin's for `-`
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(path)
+    }
+    .map_err(|err| format!("Could not read {}: {}", path, err))
+}
+
+/// One `--weight`
+enum Weight {
+    Expansion(dominion::Expansion, f64),
+    Card(dominion::KC, f64),
+}
+
+fn parse_weight(value: &str) -> Result<Weight, String> {
+    let (name, weight) = value
+        .split_once('=')
+        .ok_or_else(|| format!("{} isn't NAME=WEIGHT", value))?;
+    let weight: f64 = weight
+        .parse()
+        .ok()
+        .filter(|weight: &f64| weight.is_finite() && *weight >= 0.0)
+        .ok_or_else(|| format!("{} isn't a weight, try a number like 2 or 0.5", weight))?;
+
+    if let Ok(expansion) = name.parse() {
+        Ok(Weight::Expansion(expansion, weight))
+    } else if let Ok(card) = name.parse() {
+        Ok(Weight::Card(card, weight))
+    } else {
+        Err(format!("{} is neither an expansion nor a card", name))
+    }
+}
+
+/// One `--expansion-count`
+fn parse_expansion_count(
+    value: &str,
+) -> Result<(dominion::Expansion, dominion::ExpansionCount), String> {
+    let (expansion, count) = value
+        .split_once('=')
+        .ok_or_else(|| format!("{} isn't EXPANSION=COUNT", value))?;
+    let expansion = expansion
+        .parse()
+        .map_err(|_| format!("{} isn't an expansion", expansion))?;
+    Ok((expansion, count.parse()?))
+}
+
+/// The `--weight`s `pick` keeps, if there are any
+fn weights<K: Hash + Eq>(
+    matches: &ArgMatches,
+    pick: impl Fn(Weight) -> Option<(K, f64)>,
+) -> Option<HashMap<K, f64>> {
+    let weights: HashMap<K, f64> = matches
+        .values_of("weight")?
+        .filter_map(|value| pick(parse_weight(value).unwrap()))
+        .collect();
+    (!weights.is_empty()).then_some(weights)
+}
+
+fn optional_set<R: FromStr + Clone + Hash + Eq>(
+    matches: &ArgMatches,
+    key: &str,
+) -> Option<HashSet<R>>
+where
+    <R as FromStr>::Err: Display,
+{
+    matches
+        .value_of(key)
+        .map(|_| matches.values_of_t_or_exit(key).iter().cloned().collect())
+}
+
+fn cli() -> Command<'static> {
+    Command::new("Dominion")
         .version("0.1.0")
         .author("Michael Gilliland <github.com/mjgpy3>")
         .about("Generate dominion kingdoms")
+        .arg(
+            Arg::new("config")
+                .global(true)
+                .long("config")
+                .takes_value(true)
+                .value_name("FILE")
+                .help_heading("LIMITING")
+                .help("Config file of defaults and presets (TOML, or .json) [default: ~/.config/dominion/config.toml]"),
+        )
+        .arg(
+            Arg::new("preset")
+                .global(true)
+                .long("preset")
+                .takes_value(true)
+                .value_name("NAME")
+                .help_heading("LIMITING")
+                .help("Settings from a named preset (casual, intro-friends or one from the config file), under any flags"),
+        )
         .arg(
             Arg::new("include-expansions")
+                .global(true)
                 .short('e')
                 .long("include-expansions")
                 .takes_value(true)
@@ -22,6 +1047,7 @@ fn main() {
         )
         .arg(
             Arg::new("project-count")
+                .global(true)
                 .short('p')
                 .long("project-count")
                 .takes_value(true)
@@ -32,6 +1058,7 @@ fn main() {
         )
         .arg(
             Arg::new("bane-count")
+                .global(true)
                 .long("bane-count")
                 .takes_value(true)
                 .value_name("NUMBER")
@@ -41,6 +1068,7 @@ fn main() {
         )
         .arg(
             Arg::new("ban-cards")
+                .global(true)
                 .short('b')
                 .long("ban-cards")
                 .takes_value(true)
@@ -51,6 +1079,7 @@ fn main() {
         )
         .arg(
             Arg::new("include-cards")
+                .global(true)
                 .short('c')
                 .long("include-cards")
                 .takes_value(true)
@@ -59,108 +1088,516 @@ fn main() {
                 .help_heading("LIMITING")
                 .help("Ensure these cards are included"),
         )
+        .arg(
+            Arg::new("require-combo")
+                .global(true)
+                .long("require-combo")
+                .help_heading("LIMITING")
+                .help("Only accept kingdoms with a known combo"),
+        )
+        .arg(
+            Arg::new("no-require-combo")
+                .global(true)
+                .long("no-require-combo")
+                .conflicts_with("require-combo")
+                .help_heading("LIMITING")
+                .help("Turn off --require-combo from the config file or preset"),
+        )
+        .arg(
+            Arg::new("avoid-swingy")
+                .global(true)
+                .long("avoid-swingy")
+                .help_heading("LIMITING")
+                .help("Reject kingdoms with a known swingy pair"),
+        )
+        .arg(
+            Arg::new("no-avoid-swingy")
+                .global(true)
+                .long("no-avoid-swingy")
+                .conflicts_with("avoid-swingy")
+                .help_heading("LIMITING")
+                .help("Turn off --avoid-swingy from the config file or preset"),
+        )
+        .arg(
+            Arg::new("max-similarity")
+                .global(true)
+                .long("max-similarity")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help_heading("LIMITING")
+                .help("Reject kingdoms more similar than this (0 to 1) to any in the history log"),
+        )
+        .arg(
+            Arg::new("max-power")
+                .global(true)
+                .long("max-power")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help_heading("LIMITING")
+                .help("Reject kingdoms whose cards' win rates against Big Money sum above this"),
+        )
+        .arg(
+            Arg::new("separate-top-cards")
+                .global(true)
+                .long("separate-top-cards")
+                .help_heading("LIMITING")
+                .help("Reject kingdoms with two of the strongest tenth of cards"),
+        )
+        .arg(
+            Arg::new("no-separate-top-cards")
+                .global(true)
+                .long("no-separate-top-cards")
+                .conflicts_with("separate-top-cards")
+                .help_heading("LIMITING")
+                .help("Turn off --separate-top-cards from the config file or preset"),
+        )
+        .arg(
+            Arg::new("weight")
+                .global(true)
+                .long("weight")
+                .takes_value(true)
+                .multiple_values(true)
+                .value_name("NAME=WEIGHT")
+                .validator(parse_weight)
+                .help_heading("LIMITING")
+                .help("Make an expansion's or a card's cards likelier (e.g. Seaside=2, Chapel=0)"),
+        )
+        .arg(
+            Arg::new("expansion-count")
+                .global(true)
+                .long("expansion-count")
+                .takes_value(true)
+                .multiple_values(true)
+                .value_name("EXPANSION=COUNT")
+                .validator(parse_expansion_count)
+                .help_heading("LIMITING")
+                .help("How many kingdom cards from an expansion (e.g. Renaissance=3, Base2=..2, Seaside=1..)"),
+        )
+        .arg(
+            Arg::new("min-per-expansion")
+                .global(true)
+                .long("min-per-expansion")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help_heading("LIMITING")
+                .help("At least this many kingdom cards from each included expansion"),
+        )
+        .arg(
+            Arg::new("equal-expansions")
+                .global(true)
+                .long("equal-expansions")
+                .help_heading("LIMITING")
+                .help("Give every expansion the same chance of cards however big it is"),
+        )
+        .arg(
+            Arg::new("no-equal-expansions")
+                .global(true)
+                .long("no-equal-expansions")
+                .conflicts_with("equal-expansions")
+                .help_heading("LIMITING")
+                .help("Turn off --equal-expansions from the config file or preset"),
+        )
+        .arg(
+            Arg::new("uniform")
+                .global(true)
+                .long("uniform")
+                .conflicts_with_all(&["weight", "equal-expansions"])
+                .help_heading("LIMITING")
+                .help("Draw every kingdom the config allows with the same chance (see count)"),
+        )
+        .arg(
+            Arg::new("seed")
+                .global(true)
+                .long("seed")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help_heading("LIMITING")
+                .help("Seed the generator to get the same kingdom every time"),
+        )
+        .arg(
+            Arg::new("setup")
+                .global(true)
+                .long("setup")
+                .takes_value(true)
+                .value_name("FILE")
+                .help_heading("REROLLING")
+                .help("Start from an existing setup (JSON or history code, - for stdin)"),
+        )
+        .arg(
+            Arg::new("reroll")
+                .global(true)
+                .short('r')
+                .long("reroll")
+                .takes_value(true)
+                .value_name("SLOT")
+                .multiple_values(true)
+                .requires("setup")
+                .help_heading("REROLLING")
+                .help("Replace these cards, projects, Bane or SecondZebra in the setup"),
+        )
         .arg(
             Arg::new("output-code")
+                .global(true)
                 .long("code")
                 .help_heading("OUTPUT")
                 .help("Output history code with the setup (largely) filled out"),
         )
         .arg(
             Arg::new("output-raw")
+                .global(true)
                 .long("raw")
                 .help_heading("OUTPUT")
                 .help("Output raw setup structure (from rust debug dump)"),
         )
         .arg(
             Arg::new("output-pretty")
+                .global(true)
                 .long("pretty")
                 .help_heading("OUTPUT")
                 .help("Prettify output to make physical setup easier"),
         )
         .arg(
             Arg::new("output-hists")
+                .global(true)
                 .long("hists")
                 .help_heading("OUTPUT")
                 .help("Write histograms"),
         )
+        .arg(
+            Arg::new("output-analysis")
+                .global(true)
+                .long("analysis")
+                .help_heading("OUTPUT")
+                .help("Size up the kingdom: engine or Big Money, attacks and counterplay"),
+        )
         .arg(
             Arg::new("output-name")
+                .global(true)
                 .long("name")
                 .help_heading("OUTPUT")
                 .help("Generate a kingdom name"),
         )
-        .get_matches();
-
-    let config = dominion::SetupConfig {
-        include_expansions: optional_set(&matches, "include-expansions"),
-        ban_cards: optional_set(&matches, "ban-cards"),
-        include_cards: optional_set(&matches, "include-cards"),
-        project_count: matches
-            .value_of("project-count")
-            .map(|_| matches.value_of_t_or_exit("project-count")),
-        bane_count: matches
-            .value_of("bane-count")
-            .map(|_| matches.value_of_t_or_exit("bane-count")),
-    };
-
-    let setup = dominion::gen_setup(config);
-
-    match setup {
-        Ok(setup) => {
-            let name = if matches.is_present("output-name") {
-                let name = dominion::game_name::random(&setup);
-                println!("== {} ==", name);
-                name
-            } else {
-                "Game".to_string()
-            };
-
-            if matches.is_present("output-pretty") {
-                println!("------------------ SETUP ------------------");
-                println!("");
-                println!("{}", dominion::pretty::pretty(&setup));
-                println!("");
-            }
+        .arg(
+            Arg::new("output-json")
+                .global(true)
+                .long("json")
+                .conflicts_with_all(&[
+                    "output-pretty",
+                    "output-raw",
+                    "output-code",
+                    "output-hists",
+                    "output-analysis",
+                ])
+                .help_heading("OUTPUT")
+                .help("Output setup, name, seed, config and histograms as JSON (see README)"),
+        )
+        .arg(
+            Arg::new("output-format")
+                .global(true)
+                .long("format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(["markdown", "html", "svg"])
+                .conflicts_with_all(&[
+                    "output-pretty",
+                    "output-raw",
+                    "output-code",
+                    "output-hists",
+                    "output-analysis",
+                    "output-json",
+                ])
+                .help_heading("OUTPUT")
+                .help("Output as a single document (svg is a printable sheet of a setup); each subcommand's help says which it takes"),
+        )
+        .arg(
+            Arg::new("paper")
+                .global(true)
+                .long("paper")
+                .takes_value(true)
+                .value_name("SIZE")
+                .default_value("A4")
+                .possible_values(["A4", "Letter"])
+                .help_heading("OUTPUT")
+                .help("Paper size for --format svg"),
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .takes_value(true)
+                .value_name("FILE")
+                .default_value("dominion-history.jsonl")
+                .help("History log to save kingdoms to"),
+        )
+        .subcommand(
+            Command::new("generate")
+                .after_help(formats_help("generate"))
+                .about("Generate a kingdom (what running without a subcommand does)"),
+        )
+        .subcommand(
+            Command::new("list")
+                .after_help(formats_help("list"))
+                .about("List cards, expansions, projects or custom bane cards")
+                .arg(
+                    Arg::new("what")
+                        .required(true)
+                        .value_name("WHAT")
+                        .possible_values(["cards", "expansions", "projects", "banes"])
+                        .help("What to list"),
+                )
+                .arg(
+                    Arg::new("expansion")
+                        .long("expansion")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .value_name("EXPANSION")
+                        .help("Only those in any of these expansions"),
+                )
+                .arg(
+                    Arg::new("type")
+                        .long("type")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .value_name("TYPE")
+                        .help("Only those of any of these types (Action, Attack, ...)"),
+                )
+                .arg(
+                    Arg::new("cost")
+                        .long("cost")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .value_name("NUMBER")
+                        .help("Only those costing any of these"),
+                ),
+        )
+        .subcommand(
+            Command::new("show")
+                .after_help(formats_help("show"))
+                .about("Show a card's cost, types, expansions, roles, power and known pairs")
+                .arg(
+                    Arg::new("card")
+                        .required(true)
+                        .value_name("CARD")
+                        .help("The card, e.g. YoungWitch"),
+                ),
+        )
+        .subcommand(
+            Command::new("count")
+                .after_help(formats_help("count"))
+                .about("Count the kingdoms the config allows, with a breakdown"),
+        )
+        .subcommand(
+            Command::new("validate")
+                .after_help(formats_help("validate"))
+                .about("Check a setup (JSON or history code) or a config file against the rules")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .value_name("FILE")
+                        .help("The setup or config (- for stdin)"),
+                ),
+        )
+        .subcommand(
+            Command::new("nearest")
+                .after_help(formats_help("nearest"))
+                .about("Show the kingdoms in the history log most like --setup")
+                .arg(
+                    Arg::new("count")
+                        .long("count")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("3")
+                        .help("How many kingdoms to show"),
+                ),
+        )
+        .subcommand(
+            Command::new("simulate")
+                .after_help(formats_help("simulate"))
+                .about("Play bots against each other on --setup (or a generated Base2 kingdom)")
+                .arg(
+                    Arg::new("bots")
+                        .long("bots")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .value_name("BOT")
+                        .default_values(&["BigMoney", "BigMoneySmithy"])
+                        .help("One bot per player: BigMoney, BigMoneySmithy or a kingdom card (Big Money buying it)"),
+                )
+                .arg(
+                    Arg::new("games")
+                        .long("games")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("100")
+                        .help("How many games to play"),
+                )
+                .arg(
+                    Arg::new("log")
+                        .long("log")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Write a replayable log of the first game"),
+                ),
+        )
+        .subcommand(
+            Command::new("replay")
+                .after_help(formats_help("replay"))
+                .about("Step through a game log turn by turn (Enter for the next turn)")
+                .arg(
+                    Arg::new("log")
+                        .required(true)
+                        .value_name("LOG")
+                        .help("The game log (JSON lines)"),
+                )
+                .arg(
+                    Arg::new("verify")
+                        .long("verify")
+                        .help("Only check that the log replays to the same end"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .help("Print every turn without waiting"),
+                ),
+        )
+        .subcommand(
+            Command::new("rank")
+                .after_help(formats_help("rank"))
+                .about("Rank every playable card by how well it + Big Money beats Big Money, as JSON for src/power.json")
+                .arg(
+                    Arg::new("games")
+                        .long("games")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("1000")
+                        .help("How many games to play per card"),
+                ),
+        )
+        .subcommand(
+            Command::new("score")
+                .after_help(formats_help("score"))
+                .about("Score a game of --setup from each player's final deck")
+                .arg(
+                    Arg::new("decks")
+                        .required(true)
+                        .value_name("DECKS")
+                        .help("JSON list of {name, cards: {CARD: COUNT}, vp_tokens} (- for stdin)"),
+                )
+                .arg(
+                    Arg::new("save")
+                        .long("save")
+                        .help("Save the kingdom and scores to the history log"),
+                ),
+        )
+        .subcommand(
+            Command::new("rate")
+                .after_help(formats_help("rate"))
+                .about("Rate the last kingdom in the history log (or --game), for evolve --prefer LowestRated")
+                .arg(
+                    Arg::new("rating")
+                        .required(true)
+                        .value_name("RATING")
+                        .help("From 1 (never again) to 5 (play it again)"),
+                )
+                .arg(
+                    Arg::new("game")
+                        .long("game")
+                        .takes_value(true)
+                        .value_name("NAME")
+                        .help("Rate the last kingdom with this name instead"),
+                ),
+        )
+        .subcommand(
+            Command::new("interactive")
+                .after_help(formats_help("interactive"))
+                .about("Build a kingdom step by step (reroll, lock, ban, ...)"),
+        )
+        .subcommand(
+            Command::new("draft")
+                .after_help(formats_help("draft"))
+                .about(
+                    "Draft the kingdom together, reading one `pick CARD` or `veto CARD` per line",
+                )
+                .arg(
+                    Arg::new("players")
+                        .long("players")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("2")
+                        .help("How many players draft"),
+                )
+                .arg(
+                    Arg::new("draft-format")
+                        .long("draft-format")
+                        .takes_value(true)
+                        .value_name("FORMAT")
+                        .default_value("Snake")
+                        .possible_values(["Snake", "VetoOne", "PickTwoOfFive"])
+                        .help("How picks and vetoes go around the table"),
+                )
+                .arg(
+                    Arg::new("script")
+                        .long("script")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Read moves from a file instead of stdin"),
+                ),
+        )
+        .subcommand(
+            Command::new("evolve")
+                .after_help(formats_help("evolve"))
+                .about(
+                    "Generate the next kingdom from the last one in the history log (or --setup)",
+                )
+                .arg(
+                    Arg::new("swap")
+                        .long("swap")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("3")
+                        .help("How many kingdom cards to swap"),
+                )
+                .arg(
+                    Arg::new("swap-projects")
+                        .long("swap-projects")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .default_value("0")
+                        .help("How many projects to swap"),
+                )
+                .arg(
+                    Arg::new("prefer")
+                        .long("prefer")
+                        .takes_value(true)
+                        .value_name("PREFERENCE")
+                        .default_value("Random")
+                        .possible_values(["Random", "LowestRated", "MostPlayed"])
+                        .help("Which kingdom cards to swap first"),
+                ),
+        )
+}
 
-            if matches.is_present("output-raw") {
-                println!("------------------ RAW ------------------");
-                println!("");
-                println!("{:?}", setup);
-                println!("");
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if matches.is_present("output-code") {
-                println!("------------------ CODE ------------------");
-                println!("");
-                println!("{}", dominion::pretty::code(name, &setup));
-                println!("");
-            }
+    #[test]
+    fn cli_definition_is_consistent() {
+        cli().debug_assert();
+        // Subcommands are only checked (e.g. against the global args) once
+        // they're parsed
+        for subcommand in cli().get_subcommands() {
+            let _ = cli().try_get_matches_from(["dominion", subcommand.get_name(), "--help"]);
+        }
+    }
 
-            if matches.is_present("output-hists") {
-                println!("------------------ HISTS ------------------");
-                println!("");
-                println!("{}", dominion::pretty::hists(&setup));
-                println!("");
+    #[test]
+    fn every_subcommand_lists_its_formats() {
+        for subcommand in cli().get_subcommands() {
+            let name = subcommand.get_name();
+            assert_eq!(subcommand.get_after_help(), Some(formats_help(name)));
+            for format in formats(name) {
+                assert!(formats_help(name).contains(format), "{}", name);
             }
         }
-        Err(err) => {
-            eprintln!(
-                "Error generating kingdom!\n\n{}",
-                dominion::pretty::gen_error(err)
-            );
-            process::exit(1);
-        }
     }
 }
-
-fn optional_set<R: FromStr + Clone + Hash + Eq>(
-    matches: &ArgMatches,
-    key: &str,
-) -> Option<HashSet<R>>
-where
-    <R as FromStr>::Err: Display,
-{
-    matches
-        .value_of(key)
-        .map(|_| matches.values_of_t_or_exit(key).iter().cloned().collect())
-}
@@ -0,0 +1,537 @@
+// Generated by `dominion::wasm::typescript()`, don't edit by hand.
+// Regenerate with `DOMINION_UPDATE_TYPES=1 cargo test typescript`.
+
+export type KC =
+  | "ActingTroupe"
+  | "Adventurer"
+  | "Advisor"
+  | "Ambassador"
+  | "Artisan"
+  | "Baker"
+  | "Bandit"
+  | "Bank"
+  | "Baron"
+  | "Bazaar"
+  | "Bishop"
+  | "BorderGuard"
+  | "BorderVillage"
+  | "Bridge"
+  | "Bureaucrat"
+  | "Butcher"
+  | "Cache"
+  | "CandlestickMaker"
+  | "Caravan"
+  | "CargoShip"
+  | "Cartographer"
+  | "Cellar"
+  | "Chancellor"
+  | "Chapel"
+  | "City"
+  | "Conspirator"
+  | "Contraband"
+  | "CouncilRoom"
+  | "CountingHouse"
+  | "Courtier"
+  | "Courtyard"
+  | "Crossroads"
+  | "Cutpurse"
+  | "Develop"
+  | "Diplomat"
+  | "Doctor"
+  | "Ducat"
+  | "Duchess"
+  | "Duke"
+  | "Embargo"
+  | "Embassy"
+  | "Expand"
+  | "Experiment"
+  | "Explorer"
+  | "Fairgrounds"
+  | "FarmingVillage"
+  | "Farmland"
+  | "Feast"
+  | "Festival"
+  | "FishingVillage"
+  | "FlagBearer"
+  | "FoolsGold"
+  | "Forge"
+  | "FortuneTeller"
+  | "Gardens"
+  | "GhostShip"
+  | "Goons"
+  | "GrandMarket"
+  | "Haggler"
+  | "Hamlet"
+  | "Harbinger"
+  | "Harem"
+  | "Harvest"
+  | "Haven"
+  | "Herald"
+  | "Hideout"
+  | "Highway"
+  | "Hoard"
+  | "HornOfPlenty"
+  | "HorseTraders"
+  | "HuntingParty"
+  | "IllGottenGains"
+  | "Improve"
+  | "Inn"
+  | "Inventor"
+  | "Ironworks"
+  | "Island"
+  | "JackOfAllTrades"
+  | "Jester"
+  | "Journeyman"
+  | "KingsCourt"
+  | "Laboratory"
+  | "Lackeys"
+  | "Library"
+  | "Lighthouse"
+  | "Loan"
+  | "Lookout"
+  | "Lurker"
+  | "Mandarin"
+  | "Margrave"
+  | "Market"
+  | "Masquerade"
+  | "Masterpiece"
+  | "Menagerie"
+  | "Merchant"
+  | "MerchantGuild"
+  | "MerchantShip"
+  | "Militia"
+  | "Mill"
+  | "Mine"
+  | "MiningVillage"
+  | "Minion"
+  | "Mint"
+  | "Moat"
+  | "Moneylender"
+  | "Monument"
+  | "MountainVillage"
+  | "Mountebank"
+  | "NativeVillage"
+  | "Navigator"
+  | "NobleBrigand"
+  | "Nobles"
+  | "NomadCamp"
+  | "Oasis"
+  | "OldWitch"
+  | "Oracle"
+  | "Outpost"
+  | "Patrol"
+  | "Patron"
+  | "Pawn"
+  | "PearlDiver"
+  | "Peddler"
+  | "PirateShip"
+  | "Plaza"
+  | "Poacher"
+  | "Priest"
+  | "Quarry"
+  | "Rabble"
+  | "Recruiter"
+  | "Remake"
+  | "Remodel"
+  | "Replace"
+  | "Research"
+  | "RoyalSeal"
+  | "Salvager"
+  | "Scepter"
+  | "Scheme"
+  | "Scholar"
+  | "Sculptor"
+  | "SeaHag"
+  | "SecretPassage"
+  | "Seer"
+  | "Sentry"
+  | "ShantyTown"
+  | "SilkMerchant"
+  | "SilkRoad"
+  | "Smithy"
+  | "Smugglers"
+  | "Soothsayer"
+  | "SpiceMerchant"
+  | "Spices"
+  | "Spy"
+  | "Stables"
+  | "Steward"
+  | "Stonemason"
+  | "Swashbuckler"
+  | "Swindler"
+  | "Tactician"
+  | "Talisman"
+  | "Taxman"
+  | "Thief"
+  | "ThroneRoom"
+  | "Torturer"
+  | "Tournament"
+  | "TradeRoute"
+  | "Trader"
+  | "TradingPost"
+  | "TreasureMap"
+  | "Treasurer"
+  | "Treasury"
+  | "Tunnel"
+  | "Upgrade"
+  | "Vassal"
+  | "Vault"
+  | "Venture"
+  | "Village"
+  | "Villain"
+  | "Warehouse"
+  | "Watchtower"
+  | "Wharf"
+  | "WishingWell"
+  | "Witch"
+  | "Woodcutter"
+  | "WorkersVillage"
+  | "Workshop"
+  | "YoungWitch";
+
+export type Expansion =
+  | "Base1"
+  | "Base2"
+  | "Renaissance"
+  | "Guilds"
+  | "Cornucopia"
+  | "Intrigue2"
+  | "Seaside"
+  | "Prosperity"
+  | "Hinterlands";
+
+export type Project =
+  | "Academy"
+  | "Barracks"
+  | "Canal"
+  | "Capitalism"
+  | "Cathedral"
+  | "Citadel"
+  | "CityGate"
+  | "CropRotation"
+  | "Exploration"
+  | "Fair"
+  | "Fleet"
+  | "Guildhall"
+  | "Innovation"
+  | "Pageant"
+  | "Piazza"
+  | "RoadNetwork"
+  | "Sewers"
+  | "Silos"
+  | "SinisterPlot"
+  | "StarChart";
+
+export type BaneCard =
+  | "Bargain"
+  | "BuyAndBuy"
+  | "CoverOfDarkness"
+  | "CursedHeirloom"
+  | "Exchange"
+  | "Flank"
+  | "FoolsGold"
+  | "Fortification"
+  | "Frontier"
+  | "Gambler"
+  | "MagicShield"
+  | "Opportune"
+  | "PlagueCart"
+  | "Rebate"
+  | "Sacrifice"
+  | "SecretPlans"
+  | "SilverMine"
+  | "Throne"
+  | "TreasuryKey"
+  | "Tunnel"
+  | "Vault"
+  | "Zebra";
+
+export type CardType =
+  | "Action"
+  | "Attack"
+  | "Reaction"
+  | "Victory"
+  | "Treasure"
+  | "Duration";
+
+export type Role =
+  | "Village"
+  | "Draw"
+  | "PlusBuy"
+  | "Trasher"
+  | "Gainer"
+  | "Payload"
+  | "Junker"
+  | "HandsizeAttack"
+  | "Defense"
+  | "AltVp"
+  | "Throne"
+  | "BigMoney";
+
+export type ProjectCount = 0 | 1 | 2;
+
+export type BaneCount = 0 | 1 | 2 | 3;
+
+export type SwapPreference =
+  | "Random"
+  | "LowestRated"
+  | "MostPlayed";
+
+export type DraftFormat =
+  | "Snake"
+  | "VetoOne"
+  | "PickTwoOfFive";
+
+export type PaperSize =
+  | "A4"
+  | "Letter";
+
+export interface SetupConfig {
+  include_expansions?: Expansion[] | null;
+  ban_cards?: KC[] | null;
+  include_cards?: KC[] | null;
+  project_count?: ProjectCount | null;
+  bane_count?: BaneCount | null;
+  require_combo?: boolean;
+  avoid_swingy?: boolean;
+  max_similarity?: number | null;
+  max_power?: number | null;
+  separate_top_cards?: boolean;
+  expansion_weights?: Partial<Record<Expansion, number>> | null;
+  card_weights?: Partial<Record<KC, number>> | null;
+  equal_expansions?: boolean;
+  expansion_counts?: Partial<Record<Expansion, ExpansionCount>> | null;
+  min_per_expansion?: number | null;
+}
+
+export interface ExpansionCount {
+  min?: number;
+  max?: number | null;
+}
+
+export interface Setup {
+  kingdom_cards: KC[];
+  bane_card: KC | null;
+  project_cards: Project[];
+  bane_cards: Partial<Record<KC, BaneCard>>;
+  second_zebra: KC | null;
+  parent?: string;
+}
+
+export type Slot =
+  | { KingdomCard: KC }
+  | { Project: Project }
+  | "Bane"
+  | "SecondZebra";
+
+export interface EvolveConfig {
+  kingdom_swaps: number;
+  project_swaps: number;
+  prefer: SwapPreference;
+}
+
+export interface Played {
+  name: string;
+  at: string;
+  setup: Setup;
+  rating?: number | null;
+  scores?: Score[];
+}
+
+export interface Neighbour {
+  similarity: number;
+  played: Played;
+}
+
+export type Action = { Pick: KC } | { Veto: KC };
+
+export interface Draft {
+  format: DraftFormat;
+  config: SetupConfig;
+  hands: KC[][];
+  picked: KC[];
+  vetoed: KC[];
+}
+
+export type Label =
+  | "Engine"
+  | "BigMoney"
+  | "Slog"
+  | "Mixed";
+
+export interface Analysis {
+  engine_potential: number;
+  attack_intensity: number;
+  counterplay: number;
+  big_money_viability: number;
+  label: Label;
+  reasons: string[];
+}
+
+export type SynergyKind =
+  | "Combo"
+  | "NonBo"
+  | "Swingy";
+
+export type Partner = { Card: KC } | { Project: Project };
+
+export interface Synergy {
+  card: KC;
+  partner: Partner;
+  kind: SynergyKind;
+  why: string;
+}
+
+export type GenSetupError =
+  | "CouldNotSatisfyProjectsFromExpansions"
+  | "CouldNotSatisfyKingdomCards"
+  | "CouldNotSatisfyBaneCard"
+  | "CouldNotSatisfySecondZebra"
+  | { IntersectingCardBansAndIncludes: KC[] }
+  | "TooManyCardsIncluded"
+  | { SlotNotInSetup: Slot }
+  | "CouldNotSatisfySynergies"
+  | "CouldNotSatisfyMaxSimilarity"
+  | "CouldNotSatisfyPower"
+  | { ImpossibleExpansionCount: Expansion }
+  | "CouldNotSatisfyExpansionCounts"
+  | "InvalidWeight";
+
+export type SetupError =
+  | { KingdomCardCount: number }
+  | { DuplicateCard: KC }
+  | "MissingBane"
+  | { UnneededBane: KC }
+  | { BaneCost: KC }
+  | { BaneCardNotInKingdom: KC }
+  | "MissingSecondZebra"
+  | { UnneededSecondZebra: KC }
+  | { SecondZebraInKingdom: KC }
+  | { TooManyProjects: number };
+
+export type DraftError =
+  | { BadPlayerCount: number }
+  | { NotYourTurn: { expected: number; got: number } }
+  | { WrongAction: Action }
+  | { NotInHand: KC }
+  | "AlreadyFinished"
+  | "NotFinished"
+  | { Setup: GenSetupError };
+
+export type Card =
+  | "Copper"
+  | "Silver"
+  | "Gold"
+  | "Estate"
+  | "Duchy"
+  | "Province"
+  | "Curse"
+  | KC;
+
+export type ScoringCard = Card | "Platinum" | "Colony";
+
+export type Phase =
+  | "Action"
+  | "Buy"
+  | "Cleanup";
+
+export type DecisionKind =
+  | "PlayAction"
+  | "Buy"
+  | "Discard"
+  | "Trash"
+  | "Gain"
+  | "TopDeck"
+  | "SetAside"
+  | "Play"
+  | "Reveal";
+
+export interface Decision {
+  player: number;
+  kind: DecisionKind;
+  source: Card | null;
+  options: Card[];
+  min: number;
+  max: number;
+}
+
+export interface Pile {
+  card: Card;
+  count: number;
+}
+
+export interface PlayerView {
+  name: string;
+  deck: number;
+  hand: number;
+  discard: number;
+  discard_top: Card | null;
+  in_play: Card[];
+  score: number;
+}
+
+export interface View {
+  seat: number;
+  hand: Card[];
+  players: PlayerView[];
+  supply: Pile[];
+  trash: Card[];
+  turn: number;
+  current: number;
+  phase: Phase;
+  actions: number;
+  buys: number;
+  coins: number;
+  decision: Decision | null;
+  waiting_on: number | null;
+  over: boolean;
+}
+
+/** Shuffles are never handed out, so players can't work out deck order. */
+export type GameEvent =
+  | { Turn: { player: number; turn: number } }
+  | { Decision: { decision: Decision; choice: Card[] } }
+  | { Gain: { player: number; card: Card } }
+  | { Trash: { player: number; card: Card } }
+  | { Score: { player: number; score: number } }
+  | { End: { scores: number[] } };
+
+export type Bot = "BigMoney" | "BigMoneySmithy" | { SingleCard: KC };
+
+export type Seat = "Human" | { Bot: Bot };
+
+export type EngineError =
+  | { UnsupportedCard: KC }
+  | "UnsupportedSetup"
+  | { BadPlayerCount: number }
+  | { NotInSupply: KC }
+  | "Suspended"
+  | { NotYourDecision: number }
+  | { InvalidChoice: { decision: Decision; choice: Card[] } }
+  | "GameOver";
+
+/** Counts of every card a player ended with, wherever it is. */
+export interface Deck {
+  name: string;
+  cards: Partial<Record<ScoringCard, number>>;
+  vp_tokens?: number;
+}
+
+export interface Score {
+  name: string;
+  cards: Partial<Record<ScoringCard, number>>;
+  vp_tokens: number;
+  total: number;
+}
+
+export type ScoringError = { NotInKingdom: KC };
+
+/** What every export throws. `message` is always fit to show a player. */
+export type ApiError =
+  | { kind: "InvalidInput"; argument: string; message: string }
+  | { kind: "InvalidSetup"; argument: string; errors: SetupError[]; message: string }
+  | { kind: "GenSetup"; error: GenSetupError; message: string }
+  | { kind: "Draft"; error: DraftError; message: string }
+  | { kind: "Game"; error: EngineError; message: string }
+  | { kind: "Scoring"; error: ScoringError; message: string }
+  | { kind: "Internal"; message: string };
@@ -0,0 +1,270 @@
+use std::process::{Command, Output};
+
+fn dominion(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dominion"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn json_errors_go_to_stdout_as_json() {
+    let script = format!("{}/empty-draft", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&script, "").unwrap();
+
+    for (args, error, message) in [
+        (
+            vec!["--json", "draft", "--script", &script],
+            r#""error": "NotFinished""#,
+            "Error drafting!",
+        ),
+        (
+            vec!["--json", "score", "decks.json"],
+            r#""error": "NoSetup""#,
+            "Pass --setup",
+        ),
+        (
+            vec!["--json", "simulate", "--setup", "no-such-setup.json"],
+            r#""error": "BadSetup""#,
+            "Error reading setup!",
+        ),
+        (
+            vec!["--json", "replay", "no-such-log.jsonl"],
+            r#""error": "UnreadableFile""#,
+            "Could not read log",
+        ),
+    ] {
+        let output = dominion(&args);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(stdout.contains(error), "{:?}: {}", args, stdout);
+        assert!(stdout.contains(message), "{:?}: {}", args, stdout);
+        assert!(!stderr.contains(message), "{:?}: {}", args, stderr);
+    }
+
+    let history = format!("{}/corrupt-history.jsonl", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(&history, "not a history log\n").unwrap();
+    for (args, error, message) in [
+        (
+            vec!["--json", "--config", "no-such-config.toml"],
+            r#""error": "UnreadableFile""#,
+            "Could not read config",
+        ),
+        (
+            vec!["--json", "--preset", "nosuch"],
+            r#""UnknownPreset""#,
+            "nosuch",
+        ),
+        (
+            vec!["--json", "--history", &history, "evolve"],
+            r#""error": "BadHistory""#,
+            "Could not parse history log",
+        ),
+        (
+            vec!["--json", "validate", "no-such-setup.json"],
+            r#""error": "UnreadableFile""#,
+            "no-such-setup.json",
+        ),
+    ] {
+        let output = dominion(&args);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(stdout.contains(error), "{:?}: {}", args, stdout);
+        assert!(stdout.contains(message), "{:?}: {}", args, stdout);
+        assert!(output.stderr.is_empty(), "{:?}", args);
+    }
+
+    let output = dominion(&["score", "decks.json"]);
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Pass --setup"));
+}
+
+#[test]
+fn no_flags_turn_off_what_a_preset_turns_on() {
+    let config = |args: &[&str]| {
+        let output = dominion(&[&["--json", "--preset", "casual", "--seed", "1"], args].concat());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["config"].clone()
+    };
+
+    let casual = config(&[]);
+    assert_eq!(casual["avoid_swingy"], true);
+    assert_eq!(casual["separate_top_cards"], true);
+
+    let relaxed = config(&["--no-avoid-swingy", "--no-separate-top-cards"]);
+    assert_eq!(relaxed["avoid_swingy"], false);
+    assert_eq!(relaxed["separate_top_cards"], false);
+
+    let output = dominion(&["--avoid-swingy", "--no-avoid-swingy"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn history_logs_must_follow_the_rules_of_setting_up() {
+    let history = format!("{}/broken-history.jsonl", env!("CARGO_TARGET_TMPDIR"));
+    std::fs::write(
+        &history,
+        r#"{"name":"Short","at":"today","setup":{"kingdom_cards":["Smithy"],"bane_card":null,"project_cards":[],"bane_cards":{},"second_zebra":null}}"#,
+    )
+    .unwrap();
+
+    let output = dominion(&["--history", &history, "--max-similarity", "0.5"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr.contains("\"Short\"") && stderr.contains("breaks the rules of setting up"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn format_fails_on_subcommands_without_it() {
+    for (args, message) in [
+        (
+            vec!["simulate", "--format", "html"],
+            "simulate doesn't take --format",
+        ),
+        (
+            vec!["--format", "svg", "list", "cards"],
+            "list doesn't take --format svg, only markdown, html",
+        ),
+    ] {
+        let output = dominion(&args);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(String::from_utf8(output.stderr).unwrap().contains(message));
+    }
+
+    let output = dominion(&["list", "expansions", "--format", "markdown"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn rated_kingdoms_decide_what_lowest_rated_swaps_first() {
+    let history = format!("{}/rated-history.jsonl", env!("CARGO_TARGET_TMPDIR"));
+    let played = |name: &str, cards: &[&str]| {
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "name": name,
+                "at": "2026-10-19T20:00:00+00:00",
+                "setup": {
+                    "kingdom_cards": cards,
+                    "bane_card": null,
+                    "project_cards": [],
+                    "bane_cards": {},
+                    "second_zebra": null,
+                },
+            })
+        )
+    };
+    // Only Smithy was in the kingdom everyone disliked
+    let disliked = [
+        "Smithy",
+        "Cellar",
+        "Chapel",
+        "Moat",
+        "Harbinger",
+        "Merchant",
+        "Vassal",
+        "Village",
+        "Workshop",
+        "Bureaucrat",
+    ];
+    let liked = [
+        "Smithy",
+        "Gardens",
+        "Militia",
+        "Moneylender",
+        "Poacher",
+        "Remodel",
+        "ThroneRoom",
+        "Bandit",
+        "CouncilRoom",
+        "Festival",
+    ];
+    std::fs::write(
+        &history,
+        played("Dull", &disliked) + &played("Lively", &liked),
+    )
+    .unwrap();
+
+    for args in [
+        ["rate", "1", "--game", "Dull"],
+        ["rate", "5", "--game", "Lively"],
+    ] {
+        let output = dominion(&[&["--history", &history], &args[..]].concat());
+        assert!(output.status.success(), "{:?}", output);
+    }
+    let log = std::fs::read_to_string(&history).unwrap();
+    assert!(log.contains(r#""rating":1"#) && log.contains(r#""rating":5"#));
+
+    // Lively is evolved, and of its cards Smithy has the lowest rating
+    let output = dominion(&[
+        "--json",
+        "--history",
+        &history,
+        "evolve",
+        "--swap",
+        "1",
+        "--prefer",
+        "LowestRated",
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let cards = json["setup"]["kingdom_cards"].as_array().unwrap();
+    assert!(!cards.contains(&"Smithy".into()));
+    for card in &liked[1..] {
+        assert!(cards.contains(&(*card).into()), "{} was swapped", card);
+    }
+
+    let output = dominion(&["--json", "--history", &history, "rate", "9"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains(r#""OutOfRange": 9"#));
+}
+
+#[test]
+fn nearest_and_replay_print_json_under_json() {
+    let dir = env!("CARGO_TARGET_TMPDIR");
+    let log = format!("{}/replayed-game.jsonl", dir);
+    let output = dominion(&["simulate", "--games", "1", "--seed", "1", "--log", &log]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = dominion(&["--json", "replay", &log]);
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["header"]["players"], 2);
+    assert!(json["turns"].as_array().unwrap().len() > 1);
+
+    // The game's own kingdom is the nearest to itself
+    let setup = format!("{}/replayed-setup.json", dir);
+    std::fs::write(&setup, json["header"]["setup"].to_string()).unwrap();
+    let history = format!("{}/nearest-history.jsonl", dir);
+    let played = serde_json::json!({
+        "name": "Replayed",
+        "at": "2026-10-19T20:00:00+00:00",
+        "setup": json["header"]["setup"],
+    });
+    std::fs::write(&history, format!("{}\n", played)).unwrap();
+
+    let output = dominion(&[
+        "--json",
+        "--history",
+        &history,
+        "--setup",
+        &setup,
+        "nearest",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let neighbours = json["neighbours"].as_array().unwrap();
+    assert_eq!(neighbours.len(), 1);
+    assert_eq!(neighbours[0]["similarity"], 1.0);
+    assert_eq!(neighbours[0]["played"]["name"], "Replayed");
+}